@@ -0,0 +1,181 @@
+use std::{io, net::SocketAddr};
+
+use crate::ftp::error::Error as FtpError;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Represents the errors of the program around the FTP client: reading and writing files,
+/// understanding snapshots and patterns, serving trees and indexing crawls.
+#[derive(Debug)]
+pub enum Error {
+    /// An error of the FTP client.
+    Ftp(FtpError),
+
+    /// Error occurred while writing the output of the program.
+    OutputError { path: String, source: io::Error },
+
+    /// The output file already exists and overwriting it was not allowed.
+    OutputExists { path: String },
+
+    /// Error occurred while reading an input file of the program.
+    InputError { path: String, source: io::Error },
+
+    /// A tree snapshot could not be understood.
+    InvalidSnapshot { reason: String },
+
+    /// Error occurred while serving a tree over FTP.
+    ServeError { addr: SocketAddr, source: io::Error },
+
+    /// A glob or regular expression given to filter the tree is not valid.
+    InvalidPattern { pattern: String, reason: String },
+
+    /// A size, permission or other test given to find entries is not valid.
+    InvalidPredicate { value: String, reason: String },
+
+    /// Error occurred while reading or writing the SQLite index.
+    #[cfg(feature = "sqlite")]
+    DatabaseError {
+        path: String,
+        source: rusqlite::Error,
+    },
+
+    /// The SQLite index was written by a newer version of the program.
+    #[cfg(feature = "sqlite")]
+    IncompatibleDatabase { path: String, version: i64 },
+}
+
+/// The broad classes of errors, each reported with its own process exit code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// The program was invoked with invalid arguments.
+    Usage,
+
+    /// An input file holds data that could not be understood.
+    Data,
+
+    /// An input file could not be read.
+    Input,
+
+    /// The server address could not be resolved.
+    Resolution,
+
+    /// The server could not be reached or the connection was lost.
+    Connection,
+
+    /// The server rejected the credentials.
+    Authentication,
+
+    /// The server replied with a transient failure, retrying later may succeed.
+    TransientReply,
+
+    /// The server replied with a permanent failure or sent data that could not be understood.
+    Protocol,
+
+    /// Reading from or writing to a connection failed.
+    Io,
+
+    /// The output could not be written.
+    Output,
+}
+
+impl ErrorClass {
+    /// Returns the process exit code of the class, following the BSD `sysexits.h` values.
+    ///
+    /// # Returns
+    ///
+    /// The exit code to terminate the program with.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ErrorClass::Usage => 64,
+            ErrorClass::Data => 65,
+            ErrorClass::Input => 66,
+            ErrorClass::Resolution => 68,
+            ErrorClass::Connection => 69,
+            ErrorClass::Output => 73,
+            ErrorClass::Io => 74,
+            ErrorClass::TransientReply => 75,
+            ErrorClass::Protocol => 76,
+            ErrorClass::Authentication => 77,
+        }
+    }
+}
+
+impl Error {
+    /// Returns the class of the error.
+    ///
+    /// # Returns
+    ///
+    /// The `ErrorClass` the error belongs to, the one of the FTP error it wraps if any.
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            Error::Ftp(error) => error.class(),
+            Error::OutputError { .. } | Error::OutputExists { .. } => ErrorClass::Output,
+            Error::InputError { .. } => ErrorClass::Input,
+            Error::InvalidSnapshot { .. } => ErrorClass::Data,
+            Error::ServeError { .. } => ErrorClass::Connection,
+            Error::InvalidPattern { .. } | Error::InvalidPredicate { .. } => ErrorClass::Usage,
+            #[cfg(feature = "sqlite")]
+            Error::DatabaseError { .. } => ErrorClass::Output,
+            #[cfg(feature = "sqlite")]
+            Error::IncompatibleDatabase { .. } => ErrorClass::Data,
+        }
+    }
+
+    /// Returns the process exit code matching the class of the error.
+    ///
+    /// # Returns
+    ///
+    /// The exit code to terminate the program with.
+    pub fn exit_code(&self) -> u8 {
+        self.class().exit_code()
+    }
+}
+
+impl From<FtpError> for Error {
+    fn from(error: FtpError) -> Self {
+        Error::Ftp(error)
+    }
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
+        match self {
+            Error::Ftp(error) => write!(fmt, "{}", error),
+            Error::OutputError { path, .. } => write!(fmt, "could not write {}", path),
+            Error::OutputExists { path } => {
+                write!(fmt, "{} already exists, use --force to overwrite it", path)
+            }
+            Error::InputError { path, .. } => write!(fmt, "could not read {}", path),
+            Error::InvalidSnapshot { reason } => write!(fmt, "invalid snapshot: {}", reason),
+            Error::ServeError { addr, .. } => write!(fmt, "could not serve on {}", addr),
+            Error::InvalidPattern { pattern, reason } => {
+                write!(fmt, "invalid pattern {:?}: {}", pattern, reason)
+            }
+            Error::InvalidPredicate { value, reason } => {
+                write!(fmt, "invalid test {:?}: {}", value, reason)
+            }
+            #[cfg(feature = "sqlite")]
+            Error::DatabaseError { path, .. } => write!(fmt, "could not update {}", path),
+            #[cfg(feature = "sqlite")]
+            Error::IncompatibleDatabase { path, version } => write!(
+                fmt,
+                "{} uses schema version {}, which is newer than this program",
+                path, version
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Ftp(error) => error.source(),
+            Error::OutputError { source, .. }
+            | Error::InputError { source, .. }
+            | Error::ServeError { source, .. } => Some(source),
+            #[cfg(feature = "sqlite")]
+            Error::DatabaseError { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
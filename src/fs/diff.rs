@@ -0,0 +1,582 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::SystemTime,
+};
+
+use serde::Serialize;
+
+use crate::utils::path;
+
+use super::{
+    directory::Directory,
+    metadata::Metadata,
+    node::{Node, NodeEnum},
+    timestamp::same_modified_time,
+};
+
+/// The escape sequences used to colour the text report.
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const MAGENTA: &str = "\x1b[35m";
+const RESET: &str = "\x1b[0m";
+
+/// How an entry changed between two trees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// The entry only exists in the new tree.
+    Added,
+
+    /// The entry only exists in the old tree.
+    Removed,
+
+    /// The size, modification time or permissions of the entry changed.
+    Modified,
+
+    /// A file became a directory or the other way around.
+    TypeChanged,
+}
+
+/// Whether an entry is a file or a directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeType {
+    /// A directory.
+    #[value(alias = "d")]
+    Directory,
+
+    /// A file.
+    #[value(alias = "f")]
+    File,
+}
+
+/// One side of a change.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    /// Whether the entry is a file or a directory.
+    #[serde(rename = "type")]
+    pub node_type: NodeType,
+
+    /// The metadata of the entry.
+    #[serde(skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: Metadata,
+}
+
+impl From<&NodeEnum> for Entry {
+    fn from(node: &NodeEnum) -> Self {
+        match node {
+            NodeEnum::Directory(dir) => Entry {
+                node_type: NodeType::Directory,
+                metadata: dir.metadata.clone(),
+            },
+            NodeEnum::File(file) => Entry {
+                node_type: NodeType::File,
+                metadata: file.metadata.clone(),
+            },
+        }
+    }
+}
+
+/// A field whose value differs between the two sides of a change.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    /// The name of the field: `type`, `size`, `modified` or `permissions`.
+    pub field: &'static str,
+
+    /// The value in the old tree.
+    pub old: String,
+
+    /// The value in the new tree.
+    pub new: String,
+}
+
+/// A difference between two trees.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Change {
+    /// How the entry changed.
+    pub kind: ChangeKind,
+
+    /// The path of the entry, relative to the roots of the trees.
+    pub path: String,
+
+    /// The entry in the old tree, if it exists there.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Entry>,
+
+    /// The entry in the new tree, if it exists there.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Entry>,
+
+    /// The fields that differ, for modified and type-changed entries.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldChange>,
+}
+
+impl Change {
+    /// Returns the entry the change is about, the new one unless it was removed.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the `Entry`.
+    fn entry(&self) -> &Entry {
+        self.new
+            .as_ref()
+            .or(self.old.as_ref())
+            .expect("a change has at least one side")
+    }
+
+    /// Returns the path of the entry, followed by `/` for directories.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the displayed path.
+    fn display_path(&self) -> String {
+        match self.entry().node_type {
+            NodeType::Directory if self.path != "/" => format!("{}/", self.path),
+            _ => self.path.clone(),
+        }
+    }
+}
+
+/// Counts the changes of each kind.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct DiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub modified: usize,
+    pub type_changed: usize,
+}
+
+impl DiffSummary {
+    /// Counts the changes of each kind.
+    ///
+    /// # Arguments
+    ///
+    /// * `changes` - The changes to count.
+    ///
+    /// # Returns
+    ///
+    /// The `DiffSummary` of the changes.
+    pub fn new(changes: &[Change]) -> Self {
+        let mut summary: DiffSummary = DiffSummary::default();
+
+        for change in changes {
+            match change.kind {
+                ChangeKind::Added => summary.added += 1,
+                ChangeKind::Removed => summary.removed += 1,
+                ChangeKind::Modified => summary.modified += 1,
+                ChangeKind::TypeChanged => summary.type_changed += 1,
+            }
+        }
+
+        summary
+    }
+}
+
+/// The formats a diff can be reported in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DiffFormat {
+    /// One line per change, coloured when written to a terminal.
+    #[default]
+    Text,
+
+    /// A JSON document holding the changes and their summary.
+    Json,
+
+    /// A unified-style report, removed lines prefixed with `-` and added lines with `+`.
+    Unified,
+}
+
+/// Compares two trees.
+///
+/// Entries are matched by their path relative to the roots, so trees crawled from
+/// different starting directories can be compared. Metadata fields are only compared when
+/// both sides know them, so snapshots without metadata do not report every file as
+/// modified, and modification times are compared as times rather than as sent, so a file
+/// listed as `Jun 01 12:00` then as `Jun 01 2025` is not modified. The entries inside
+/// added and removed directories are reported too.
+///
+/// # Arguments
+///
+/// * `old` - The root of the old tree.
+/// * `new` - The root of the new tree.
+///
+/// # Returns
+///
+/// The changes, sorted by path.
+pub fn diff(old: &Directory, new: &Directory) -> Vec<Change> {
+    let mut changes: Vec<Change> = Vec::new();
+
+    diff_directories(old, new, "/", SystemTime::now(), &mut changes);
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+    changes
+}
+
+/// Compares the children of two directories found at the same path.
+///
+/// Children sharing a name are matched in order.
+///
+/// # Arguments
+///
+/// * `old` - The directory in the old tree.
+/// * `new` - The directory in the new tree.
+/// * `dir_path` - The path of the directories, relative to the roots.
+/// * `now` - The current time, to date the modification times missing their year.
+/// * `changes` - The changes found so far.
+fn diff_directories(
+    old: &Directory,
+    new: &Directory,
+    dir_path: &str,
+    now: SystemTime,
+    changes: &mut Vec<Change>,
+) {
+    let mut by_name: HashMap<&str, VecDeque<usize>> = HashMap::new();
+    let mut matched: Vec<bool> = vec![false; new.nodes.len()];
+
+    for (index, new_node) in new.nodes.iter().enumerate() {
+        by_name.entry(new_node.name()).or_default().push_back(index);
+    }
+
+    for old_node in &old.nodes {
+        let node_path: String = path::join(dir_path, old_node.name());
+        let index: Option<usize> = by_name
+            .get_mut(old_node.name())
+            .and_then(VecDeque::pop_front);
+
+        match index {
+            Some(index) => {
+                matched[index] = true;
+                diff_nodes(old_node, &new.nodes[index], &node_path, now, changes);
+            }
+            None => report_tree(ChangeKind::Removed, old_node, &node_path, changes),
+        }
+    }
+
+    for (new_node, _) in new
+        .nodes
+        .iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+    {
+        report_tree(
+            ChangeKind::Added,
+            new_node,
+            &path::join(dir_path, new_node.name()),
+            changes,
+        );
+    }
+}
+
+/// Compares two nodes found at the same path.
+///
+/// # Arguments
+///
+/// * `old` - The node in the old tree.
+/// * `new` - The node in the new tree.
+/// * `node_path` - The path of the nodes, relative to the roots.
+/// * `now` - The current time, to date the modification times missing their year.
+/// * `changes` - The changes found so far.
+fn diff_nodes(
+    old: &NodeEnum,
+    new: &NodeEnum,
+    node_path: &str,
+    now: SystemTime,
+    changes: &mut Vec<Change>,
+) {
+    let (kind, fields): (ChangeKind, Vec<FieldChange>) = match (old, new) {
+        (NodeEnum::File(old_file), NodeEnum::File(new_file)) => (
+            ChangeKind::Modified,
+            diff_metadata(&old_file.metadata, &new_file.metadata, true, now),
+        ),
+        (NodeEnum::Directory(old_dir), NodeEnum::Directory(new_dir)) => {
+            diff_directories(old_dir, new_dir, node_path, now, changes);
+
+            (
+                ChangeKind::Modified,
+                diff_metadata(&old_dir.metadata, &new_dir.metadata, false, now),
+            )
+        }
+        _ => {
+            let type_name = |node: &NodeEnum| match node {
+                NodeEnum::Directory(_) => "directory".to_string(),
+                NodeEnum::File(_) => "file".to_string(),
+            };
+
+            report_children(ChangeKind::Removed, old, node_path, changes);
+            report_children(ChangeKind::Added, new, node_path, changes);
+
+            (
+                ChangeKind::TypeChanged,
+                vec![FieldChange {
+                    field: "type",
+                    old: type_name(old),
+                    new: type_name(new),
+                }],
+            )
+        }
+    };
+
+    if !fields.is_empty() {
+        changes.push(Change {
+            kind,
+            path: node_path.to_string(),
+            old: Some(Entry::from(old)),
+            new: Some(Entry::from(new)),
+            fields,
+        });
+    }
+}
+
+/// Compares the metadata fields known on both sides.
+///
+/// # Arguments
+///
+/// * `old` - The metadata in the old tree.
+/// * `new` - The metadata in the new tree.
+/// * `compare_size` - Whether sizes are compared, which is meaningless for directories.
+/// * `now` - The current time, to date the modification times missing their year.
+///
+/// # Returns
+///
+/// The fields that differ.
+fn diff_metadata(
+    old: &Metadata,
+    new: &Metadata,
+    compare_size: bool,
+    now: SystemTime,
+) -> Vec<FieldChange> {
+    let mut fields: Vec<FieldChange> = Vec::new();
+    let mut compare = |field: &'static str,
+                       old: Option<String>,
+                       new: Option<String>,
+                       same: &dyn Fn(&str, &str) -> bool| {
+        if let (Some(old), Some(new)) = (old, new) {
+            if !same(&old, &new) {
+                fields.push(FieldChange { field, old, new });
+            }
+        }
+    };
+    let equal = |old: &str, new: &str| old == new;
+
+    if compare_size {
+        compare(
+            "size",
+            old.size.map(|size| size.to_string()),
+            new.size.map(|size| size.to_string()),
+            &equal,
+        );
+    }
+
+    compare(
+        "modified",
+        old.modified.clone(),
+        new.modified.clone(),
+        &|old: &str, new: &str| same_modified_time(old, new, now),
+    );
+    compare(
+        "permissions",
+        old.permissions.clone(),
+        new.permissions.clone(),
+        &equal,
+    );
+
+    fields
+}
+
+/// Reports a node and everything below it as added or removed.
+///
+/// # Arguments
+///
+/// * `kind` - Whether the node was added or removed.
+/// * `node` - The node.
+/// * `node_path` - The path of the node, relative to the root.
+/// * `changes` - The changes found so far.
+fn report_tree(kind: ChangeKind, node: &NodeEnum, node_path: &str, changes: &mut Vec<Change>) {
+    let entry: Entry = Entry::from(node);
+    let (old, new): (Option<Entry>, Option<Entry>) = match kind {
+        ChangeKind::Removed => (Some(entry), None),
+        _ => (None, Some(entry)),
+    };
+
+    changes.push(Change {
+        kind,
+        path: node_path.to_string(),
+        old,
+        new,
+        fields: Vec::new(),
+    });
+
+    report_children(kind, node, node_path, changes);
+}
+
+/// Reports everything below a node as added or removed.
+///
+/// # Arguments
+///
+/// * `kind` - Whether the children were added or removed.
+/// * `node` - The node, nothing being reported for files.
+/// * `node_path` - The path of the node, relative to the root.
+/// * `changes` - The changes found so far.
+fn report_children(kind: ChangeKind, node: &NodeEnum, node_path: &str, changes: &mut Vec<Change>) {
+    if let NodeEnum::Directory(dir) = node {
+        for child in &dir.nodes {
+            report_tree(kind, child, &path::join(node_path, child.name()), changes);
+        }
+    }
+}
+
+/// Formats the changes as text, one line per change followed by a summary.
+///
+/// Added entries are prefixed with `+`, removed ones with `-`, modified ones with `~` and
+/// type-changed ones with `!`.
+///
+/// # Arguments
+///
+/// * `changes` - The changes to report.
+/// * `color` - Whether to colour the lines with ANSI escape sequences.
+///
+/// # Returns
+///
+/// A `String` containing the report.
+pub fn to_text(changes: &[Change], color: bool) -> String {
+    let mut text: String = String::new();
+
+    for change in changes {
+        let (sign, colour): (char, &str) = match change.kind {
+            ChangeKind::Added => ('+', GREEN),
+            ChangeKind::Removed => ('-', RED),
+            ChangeKind::Modified => ('~', YELLOW),
+            ChangeKind::TypeChanged => ('!', MAGENTA),
+        };
+        let details: String = change
+            .fields
+            .iter()
+            .map(|field| format!("{} {} -> {}", field.field, field.old, field.new))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let line: String = if details.is_empty() {
+            format!("{} {}", sign, change.display_path())
+        } else {
+            format!("{} {}  ({})", sign, change.display_path(), details)
+        };
+
+        if color {
+            text.push_str(&format!("{}{}{}\n", colour, line, RESET));
+        } else {
+            text.push_str(&format!("{}\n", line));
+        }
+    }
+
+    let summary: DiffSummary = DiffSummary::new(changes);
+
+    text.push_str(&format!(
+        "{} added, {} removed, {} modified, {} type changed",
+        summary.added, summary.removed, summary.modified, summary.type_changed
+    ));
+
+    text
+}
+
+/// Formats the changes as a JSON document holding the changes and their summary.
+///
+/// # Arguments
+///
+/// * `changes` - The changes to report.
+///
+/// # Returns
+///
+/// A `String` containing the JSON document.
+pub fn to_json(changes: &[Change]) -> String {
+    #[derive(Serialize)]
+    struct Report<'a> {
+        summary: DiffSummary,
+        changes: &'a [Change],
+    }
+
+    serde_json::to_string(&Report {
+        summary: DiffSummary::new(changes),
+        changes,
+    })
+    .expect("changes only hold serializable values")
+}
+
+/// Formats the changes as a unified-style report.
+///
+/// Every entry is written on a line holding its path and metadata. The old side of a
+/// change is prefixed with `-` and the new side with `+`.
+///
+/// # Arguments
+///
+/// * `changes` - The changes to report.
+/// * `old_label` - The name of the old tree, such as the path of its snapshot.
+/// * `new_label` - The name of the new tree.
+///
+/// # Returns
+///
+/// A `String` containing the report.
+pub fn to_unified(changes: &[Change], old_label: &str, new_label: &str) -> String {
+    let mut text: String = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let line = |path: &str, entry: &Entry| {
+        let mut line: String = match entry.node_type {
+            NodeType::Directory if path != "/" => format!("{}/", path),
+            _ => path.to_string(),
+        };
+
+        if let Some(size) = entry.metadata.size {
+            line.push_str(&format!("\tsize={}", size));
+        }
+
+        if let Some(modified) = &entry.metadata.modified {
+            line.push_str(&format!("\tmodified={}", modified));
+        }
+
+        if let Some(permissions) = &entry.metadata.permissions {
+            line.push_str(&format!("\tpermissions={}", permissions));
+        }
+
+        line
+    };
+
+    for change in changes {
+        text.push_str(&format!("@@ {} @@\n", change.path));
+
+        if let Some(old) = &change.old {
+            text.push_str(&format!("-{}\n", line(&change.path, old)));
+        }
+
+        if let Some(new) = &change.new {
+            text.push_str(&format!("+{}\n", line(&change.path, new)));
+        }
+    }
+
+    text
+}
+
+/// Formats the changes in the given format.
+///
+/// # Arguments
+///
+/// * `changes` - The changes to report.
+/// * `format` - The format of the report.
+/// * `color` - Whether to colour the text report.
+/// * `old_label` - The name of the old tree.
+/// * `new_label` - The name of the new tree.
+///
+/// # Returns
+///
+/// A `String` containing the report.
+pub fn render(
+    changes: &[Change],
+    format: DiffFormat,
+    color: bool,
+    old_label: &str,
+    new_label: &str,
+) -> String {
+    match format {
+        DiffFormat::Text => to_text(changes, color),
+        DiffFormat::Json => to_json(changes),
+        DiffFormat::Unified => to_unified(changes, old_label, new_label),
+    }
+}
@@ -1,28 +1,41 @@
 use std::collections::VecDeque;
 
-use serde::ser::SerializeMap;
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Serialize};
 
-use super::node::{Node, NodeEnum};
+use super::metadata::Metadata;
+use super::node::{Node, NodeEnum, TraversalType};
+use super::tree::TreeOptions;
+use super::usage::{self, Usage};
 
 /// Represents a directory in the filesystem.
 ///
-/// A `Directory` contains a name and a list of nodes, which can be either
-/// subdirectories or files.
-#[derive(Clone, Debug, Default)]
+/// A `Directory` contains a name, the full remote path it was listed at and a
+/// list of nodes, which can be either subdirectories or files.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Directory {
     /// The name of the directory.
     pub name: String,
 
+    /// The full remote path of the directory.
+    pub path: String,
+
+    /// The permissions and owners of the directory, when known.
+    #[serde(default, skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: Metadata,
+
     /// The nodes contained within the directory.
     ///
     /// This can include both subdirectories and files, represented by the `NodeEnum` enum.
+    /// They are serialized as an ordered `children` array, so entries sharing a name are kept.
+    #[serde(rename = "children", default)]
     pub nodes: Vec<NodeEnum>,
 }
 
 impl Directory {
     /// Creates a new directory with the given name.
     ///
+    /// The path of the directory defaults to its name.
+    ///
     /// # Arguments
     ///
     /// * `name` - A `String` that holds the name of the directory.
@@ -32,7 +45,28 @@ impl Directory {
     /// A new `Directory` instance.
     pub fn new(name: String) -> Self {
         Directory {
+            path: name.clone(),
             name,
+            metadata: Metadata::default(),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Creates a new directory with the given name and full remote path.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A `String` that holds the name of the directory.
+    /// * `path` - A `String` that holds the full remote path of the directory.
+    ///
+    /// # Returns
+    ///
+    /// A new `Directory` instance.
+    pub fn with_path(name: String, path: String) -> Self {
+        Directory {
+            name,
+            path,
+            metadata: Metadata::default(),
             nodes: Vec::new(),
         }
     }
@@ -46,39 +80,80 @@ impl Directory {
         self.nodes.push(node.into());
     }
 
-    /// Converts the directory and its contents to a string with the given indentation using DFS.
+    /// Computes the recursive totals of the directory: bytes, files, directories and the
+    /// deepest level.
+    ///
+    /// # Returns
+    ///
+    /// The `Usage` of the directory.
+    pub fn usage(&self) -> Usage {
+        usage::du(self, 0).1
+    }
+
+    /// Converts the contents of the directory to one full remote path per line, like
+    /// find(1).
+    ///
+    /// In the long format, every path is preceded by tab-separated type (`d` or `f`), size
+    /// and modification time columns, unknown values being written as `-`.
     ///
     /// # Arguments
     ///
-    /// * `indent` - A string slice that holds the current level of indentation.
+    /// * `traversal_type` - The order the entries are listed in.
+    /// * `long` - Whether to write the type, size and modification time columns.
     ///
     /// # Returns
     ///
-    /// A `String` representation of the directory and its contents in DFS order.
-    pub fn to_string_dfs(&self, indent: &str) -> String {
-        let mut ret: String = String::new();
-
-        for (i, node) in self.nodes.iter().enumerate() {
-            let is_last: bool = i == self.nodes.len() - 1;
-            let prefix: &str = if is_last { "└── " } else { "├── " };
+    /// A `String` holding one line per entry, not including the directory itself.
+    pub fn to_string_flat(&self, traversal_type: TraversalType, long: bool) -> String {
+        let mut result: String = String::new();
+        let mut queue: VecDeque<&Directory> = VecDeque::from([self]);
+
+        while let Some(current_dir) = queue.pop_front() {
+            for node in &current_dir.nodes {
+                let (kind, metadata): (&str, &Metadata) = match node {
+                    NodeEnum::Directory(subdir) => ("d", &subdir.metadata),
+                    NodeEnum::File(file) => ("f", &file.metadata),
+                };
+
+                if long {
+                    result.push_str(&format!(
+                        "{}\t{}\t{}\t",
+                        kind,
+                        metadata
+                            .size
+                            .map_or_else(|| "-".to_string(), |size| size.to_string()),
+                        metadata.modified.as_deref().unwrap_or("-")
+                    ));
+                }
 
-            match node {
-                NodeEnum::Directory(directory) => {
-                    ret.push_str(&format!("{}{}{}\n", indent, prefix, directory.name()));
+                result.push_str(node.path());
+                result.push('\n');
 
-                    if is_last {
-                        ret.push_str(&directory.to_string_dfs(&format!("{}    ", indent)));
-                    } else {
-                        ret.push_str(&directory.to_string_dfs(&format!("{}│    ", indent)));
+                if let NodeEnum::Directory(subdir) = node {
+                    match traversal_type {
+                        TraversalType::BFS => queue.push_back(subdir),
+                        TraversalType::DFS => {
+                            result.push_str(&subdir.to_string_flat(TraversalType::DFS, long))
+                        }
                     }
                 }
-                NodeEnum::File(file) => {
-                    ret.push_str(&format!("{}{}{}\n", indent, prefix, file.name()))
-                }
             }
         }
 
-        ret
+        result
+    }
+
+    /// Converts the directory and its contents to a string with the given indentation using DFS.
+    ///
+    /// # Arguments
+    ///
+    /// * `indent` - A string slice that holds the current level of indentation.
+    ///
+    /// # Returns
+    ///
+    /// A `String` representation of the directory and its contents in DFS order.
+    pub fn to_string_dfs(&self, indent: &str) -> String {
+        TreeOptions::default().entries(self, TraversalType::DFS, indent)
     }
 
     /// Converts the directory and its contents to a string with the given indentation using BFS.
@@ -91,41 +166,7 @@ impl Directory {
     ///
     /// A `String` representation of the directory and its contents in BFS order.
     pub fn to_string_bfs(&self, indent: &str) -> String {
-        let mut result: String = String::new();
-        let mut queue: VecDeque<(&Directory, String)> = VecDeque::new();
-
-        queue.push_back((self, indent.to_string()));
-
-        while let Some((current_dir, current_indent)) = queue.pop_front() {
-            for (i, node) in current_dir.nodes.iter().enumerate() {
-                let is_last: bool = i == current_dir.nodes.len() - 1;
-                let prefix: &str = if is_last { "└── " } else { "├── " };
-
-                match node {
-                    NodeEnum::Directory(subdir) => {
-                        result.push_str(&format!(
-                            "{}{}{}\n",
-                            current_indent,
-                            prefix,
-                            subdir.name()
-                        ));
-
-                        let new_indent = if is_last {
-                            format!("{}    ", current_indent)
-                        } else {
-                            format!("{}|   ", current_indent)
-                        };
-
-                        queue.push_back((&subdir, new_indent));
-                    }
-                    NodeEnum::File(file) => {
-                        result.push_str(&format!("{}{}{}\n", current_indent, prefix, file.name()));
-                    }
-                }
-            }
-        }
-
-        result
+        TreeOptions::default().entries(self, TraversalType::BFS, indent)
     }
 }
 
@@ -138,36 +179,13 @@ impl Node for Directory {
     fn name(&self) -> &str {
         &self.name
     }
-}
 
-impl Serialize for Directory {
-    /// Serializes the directory and its contents.
-    ///
-    /// # Arguments
-    ///
-    /// * `serializer` - The serializer to use for serialization.
+    /// Returns the full remote path of the directory.
     ///
     /// # Returns
     ///
-    /// A `Result` containing the serialized output or an error.
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let mut map: <S as Serializer>::SerializeMap =
-            serializer.serialize_map(Some(self.nodes.len()))?;
-
-        for node in &self.nodes {
-            match node {
-                NodeEnum::Directory(dir) => {
-                    map.serialize_entry(&dir.name, dir)?;
-                }
-                NodeEnum::File(file) => {
-                    map.serialize_entry(&file.name, file)?;
-                }
-            }
-        }
-
-        map.end()
+    /// A string slice that holds the path of the directory.
+    fn path(&self) -> &str {
+        &self.path
     }
 }
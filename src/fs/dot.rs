@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use super::{
+    directory::Directory,
+    format::TreeFormatter,
+    node::NodeEnum,
+    usage::{human_size, usage_by_path, Usage},
+};
+
+/// Draws a tree as a Graphviz graph, to be rendered with `dot -Tsvg`.
+///
+/// By default every entry is a node linked to its parent directory. When clustered, every
+/// directory is drawn as a box holding its entries instead.
+#[derive(Clone, Debug, Default)]
+pub struct Dot {
+    /// The deepest level drawn, 1 for the entries of the root. Deeper entries are left
+    /// out, but still counted in the labels of their ancestors.
+    pub max_depth: Option<usize>,
+
+    /// Whether to draw directories as boxes holding their entries rather than as nodes.
+    pub cluster: bool,
+
+    /// Whether to label files with their size and directories with their total size and
+    /// number of files.
+    pub sizes: bool,
+}
+
+impl TreeFormatter for Dot {
+    fn format(&self, root: &Directory) -> String {
+        let mut graph: Graph = Graph {
+            options: self,
+            text: String::from(
+                "digraph tree {\n  rankdir=LR;\n  node [shape=box, fontname=\"Helvetica\"];\n",
+            ),
+            next_id: 0,
+            usages: if self.sizes {
+                usage_by_path(root)
+            } else {
+                HashMap::new()
+            },
+        };
+
+        if self.cluster {
+            graph.cluster(root, &root.path, 0, "  ");
+        } else {
+            let label: String = graph.directory_label(root, &root.path);
+            let id: String = graph.node("  ", &root.path, &label, true);
+
+            graph.edges(root, &id, 1);
+        }
+
+        graph.text.push_str("}\n");
+        graph.text
+    }
+}
+
+/// The graph being written.
+struct Graph<'a> {
+    /// The options of the graph.
+    options: &'a Dot,
+
+    /// The graph written so far.
+    text: String,
+
+    /// The number of the next node or cluster.
+    next_id: usize,
+
+    /// The totals of the directories by path, computed once when sizes are shown.
+    usages: HashMap<String, Usage>,
+}
+
+impl Graph<'_> {
+    /// Writes the entries of a directory as nodes linked to the node of the directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory.
+    /// * `parent_id` - The identifier of the node of the directory.
+    /// * `depth` - The depth of the entries of the directory.
+    fn edges(&mut self, dir: &Directory, parent_id: &str, depth: usize) {
+        if self
+            .options
+            .max_depth
+            .is_some_and(|max_depth| depth > max_depth)
+        {
+            return;
+        }
+
+        for node in &dir.nodes {
+            let id: String = match node {
+                NodeEnum::Directory(subdir) => {
+                    let label: String = self.directory_label(subdir, &subdir.name);
+
+                    self.node("  ", &subdir.path, &label, true)
+                }
+                NodeEnum::File(file) => {
+                    let label: String = self.file_label(&file.name, file.metadata.size);
+
+                    self.node("  ", &file.path, &label, false)
+                }
+            };
+
+            self.text.push_str(&format!("  {} -> {};\n", parent_id, id));
+
+            if let NodeEnum::Directory(subdir) = node {
+                self.edges(subdir, &id, depth + 1);
+            }
+        }
+    }
+
+    /// Writes a directory as a cluster holding its files and the clusters of its
+    /// subdirectories.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory.
+    /// * `name` - The name written for the directory.
+    /// * `depth` - The depth of the directory, 0 for the root.
+    /// * `indent` - The indentation of the cluster.
+    fn cluster(&mut self, dir: &Directory, name: &str, depth: usize, indent: &str) {
+        let id: usize = self.next_id;
+        let label: String = self.directory_label(dir, name);
+        let inner: String = format!("{}  ", indent);
+        let mut empty: bool = true;
+
+        self.next_id += 1;
+        self.text.push_str(&format!(
+            "{}subgraph cluster_{} {{\n{}label=\"{}\";\n{}tooltip=\"{}\";\n",
+            indent,
+            id,
+            inner,
+            label,
+            inner,
+            escape(&dir.path)
+        ));
+
+        if self
+            .options
+            .max_depth
+            .is_none_or(|max_depth| depth < max_depth)
+        {
+            for node in &dir.nodes {
+                empty = false;
+
+                match node {
+                    NodeEnum::Directory(subdir) => {
+                        self.cluster(subdir, &subdir.name, depth + 1, &inner)
+                    }
+                    NodeEnum::File(file) => {
+                        let label: String = self.file_label(&file.name, file.metadata.size);
+
+                        self.node(&inner, &file.path, &label, false);
+                    }
+                }
+            }
+        }
+
+        if empty {
+            let id: String = self.next_node_id();
+
+            self.text.push_str(&format!(
+                "{}{} [label=\"\", shape=point, style=invis];\n",
+                inner, id
+            ));
+        }
+
+        self.text.push_str(&format!("{}}}\n", indent));
+    }
+
+    /// Declares a node.
+    ///
+    /// # Arguments
+    ///
+    /// * `indent` - The indentation of the declaration.
+    /// * `path` - The full remote path of the entry, shown as a tooltip.
+    /// * `label` - The escaped label of the node.
+    /// * `is_dir` - Whether the entry is a directory, drawn as a folder.
+    ///
+    /// # Returns
+    ///
+    /// The identifier of the node.
+    fn node(&mut self, indent: &str, path: &str, label: &str, is_dir: bool) -> String {
+        let id: String = self.next_node_id();
+        let shape: &str = if is_dir { "folder" } else { "note" };
+
+        self.text.push_str(&format!(
+            "{}{} [label=\"{}\", tooltip=\"{}\", shape={}];\n",
+            indent,
+            id,
+            label,
+            escape(path),
+            shape
+        ));
+
+        id
+    }
+
+    /// Returns the identifier of a new node.
+    ///
+    /// # Returns
+    ///
+    /// A `String` such as `n12`.
+    fn next_node_id(&mut self) -> String {
+        let id: String = format!("n{}", self.next_id);
+
+        self.next_id += 1;
+
+        id
+    }
+
+    /// Builds the label of a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory.
+    /// * `name` - The name written for the directory.
+    ///
+    /// # Returns
+    ///
+    /// The escaped label, holding the totals of the directory when sizes are shown.
+    fn directory_label(&self, dir: &Directory, name: &str) -> String {
+        let mut label: String = escape(name);
+
+        if self.options.sizes {
+            let usage: Usage = self.usages.get(&dir.path).copied().unwrap_or_default();
+            let files: &str = if usage.files == 1 { "file" } else { "files" };
+
+            label.push_str(&format!(
+                "\\n{} in {} {}",
+                human_size(usage.bytes),
+                usage.files,
+                files
+            ));
+        }
+
+        label
+    }
+
+    /// Builds the label of a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the file.
+    /// * `size` - The size of the file, when known.
+    ///
+    /// # Returns
+    ///
+    /// The escaped label, holding the size of the file when sizes are shown and known.
+    fn file_label(&self, name: &str, size: Option<u64>) -> String {
+        let mut label: String = escape(name);
+
+        if let Some(size) = size.filter(|_| self.options.sizes) {
+            label.push_str(&format!("\\n{}", human_size(size)));
+        }
+
+        label
+    }
+}
+
+/// Escapes a string written between double quotes in a DOT file.
+///
+/// # Arguments
+///
+/// * `value` - The string.
+///
+/// # Returns
+///
+/// A `String` containing the escaped string.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -1,19 +1,30 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
+use super::metadata::Metadata;
 use super::node::Node;
 
 /// Represents a file in the filesystem.
 ///
-/// A `File` contains a name.
-#[derive(Clone, Debug, Serialize)]
+/// A `File` contains a name, the full remote path it was listed at and the metadata
+/// reported by the server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct File {
     /// The name of the file.
     pub name: String,
+
+    /// The full remote path of the file.
+    pub path: String,
+
+    /// The size, permissions and owners of the file, when known.
+    #[serde(default, skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: Metadata,
 }
 
 impl File {
     /// Creates a new file with the given name.
     ///
+    /// The path of the file defaults to its name.
+    ///
     /// # Arguments
     ///
     /// * `name` - A `String` that holds the name of the file.
@@ -22,7 +33,29 @@ impl File {
     ///
     /// A new `File` instance.
     pub fn new(name: String) -> Self {
-        File { name }
+        File {
+            path: name.clone(),
+            name,
+            metadata: Metadata::default(),
+        }
+    }
+
+    /// Creates a new file with the given name and full remote path.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A `String` that holds the name of the file.
+    /// * `path` - A `String` that holds the full remote path of the file.
+    ///
+    /// # Returns
+    ///
+    /// A new `File` instance.
+    pub fn with_path(name: String, path: String) -> Self {
+        File {
+            name,
+            path,
+            metadata: Metadata::default(),
+        }
     }
 }
 
@@ -35,4 +68,13 @@ impl Node for File {
     fn name(&self) -> &str {
         &self.name
     }
-}
\ No newline at end of file
+
+    /// Returns the full remote path of the file.
+    ///
+    /// # Returns
+    ///
+    /// A string slice that holds the path of the file.
+    fn path(&self) -> &str {
+        &self.path
+    }
+}
@@ -0,0 +1,162 @@
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
+
+use crate::error::{Error, Result};
+
+use super::node::{Node, NodeEnum};
+
+/// How a pattern matches.
+#[derive(Clone, Debug)]
+enum Matcher {
+    /// A shell glob such as `*.csv` or `/pub/**/*.tmp`.
+    Glob(GlobMatcher),
+
+    /// A regular expression, matching anywhere unless anchored.
+    Regex(Regex),
+}
+
+/// A glob or regular expression matched against the entries of a tree.
+///
+/// A glob containing a `/` is matched against the full remote path of an entry, any other
+/// glob against its name only. A regular expression is matched against the name unless
+/// told otherwise with `with_full_path`, since a `/` in it, as in `[^/]`, does not tell
+/// what it is meant to match.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    matcher: Matcher,
+    full_path: bool,
+}
+
+impl Pattern {
+    /// Compiles a shell glob.
+    ///
+    /// In globs matched against the full path, `*` does not cross a `/`, while `**` does.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The glob, such as `*.tmp` or `/pub/**/.git`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Pattern` or `Error::InvalidPattern`.
+    pub fn glob(pattern: &str) -> Result<Self> {
+        let matcher: GlobMatcher = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| Error::InvalidPattern {
+                pattern: pattern.to_string(),
+                reason: e.kind().to_string(),
+            })?
+            .compile_matcher();
+
+        Ok(Pattern {
+            matcher: Matcher::Glob(matcher),
+            full_path: pattern.contains('/'),
+        })
+    }
+
+    /// Compiles a regular expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The regular expression, such as `^\.git$` or `^/pub/.*\.csv$`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Pattern` or `Error::InvalidPattern`.
+    pub fn regex(pattern: &str) -> Result<Self> {
+        let regex: Regex = Regex::new(pattern).map_err(|e| Error::InvalidPattern {
+            pattern: pattern.to_string(),
+            reason: e.to_string(),
+        })?;
+
+        Ok(Pattern {
+            matcher: Matcher::Regex(regex),
+            full_path: false,
+        })
+    }
+
+    /// Sets whether the pattern is matched against the full remote path of an entry
+    /// rather than its name.
+    ///
+    /// # Arguments
+    ///
+    /// * `full_path` - Whether to match the full path.
+    ///
+    /// # Returns
+    ///
+    /// The `Pattern`.
+    pub fn with_full_path(mut self, full_path: bool) -> Self {
+        self.full_path = full_path;
+        self
+    }
+
+    /// Returns whether the pattern matches an entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the entry.
+    /// * `path` - The full remote path of the entry.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the pattern matches.
+    pub fn matches(&self, name: &str, path: &str) -> bool {
+        let subject: &str = if self.full_path { path } else { name };
+
+        match &self.matcher {
+            Matcher::Glob(glob) => glob.is_match(subject),
+            Matcher::Regex(regex) => regex.is_match(subject),
+        }
+    }
+}
+
+/// Selects the entries kept in a tree.
+///
+/// Entries matching an exclude pattern are dropped, and excluded directories are not
+/// listed at all. When include patterns are given, only the files matching one of them
+/// are kept. Directories are kept whatever the include patterns, since the files they
+/// hold may match, and they are kept even when none of their files does, so the tree
+/// still shows every directory that was walked.
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    /// The patterns selecting the files to keep.
+    pub include: Vec<Pattern>,
+
+    /// The patterns selecting the files and directories to drop.
+    pub exclude: Vec<Pattern>,
+}
+
+impl Filter {
+    /// Returns whether an entry is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The entry, holding its full remote path.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the entry is kept.
+    pub fn keeps(&self, node: &NodeEnum) -> bool {
+        let (name, path): (&str, &str) = (node.name(), node.path());
+
+        if self
+            .exclude
+            .iter()
+            .any(|pattern| pattern.matches(name, path))
+        {
+            return false;
+        }
+
+        match node {
+            NodeEnum::Directory(_) => true,
+            NodeEnum::File(_) => {
+                self.include.is_empty()
+                    || self
+                        .include
+                        .iter()
+                        .any(|pattern| pattern.matches(name, path))
+            }
+        }
+    }
+}
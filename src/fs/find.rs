@@ -0,0 +1,412 @@
+use std::{
+    process::Command,
+    time::{Duration, SystemTime},
+};
+
+use log::warn;
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+
+use super::{
+    diff::NodeType,
+    directory::Directory,
+    filter::Pattern,
+    metadata::{permission_mode, Metadata},
+    node::{Node, NodeEnum},
+    timestamp::modified_time,
+};
+
+/// How a size is compared with the bound of a `SizeBound`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    /// The size is smaller than the bound.
+    Less,
+
+    /// The size is equal to the bound.
+    Equal,
+
+    /// The size is larger than the bound.
+    Greater,
+}
+
+/// A bound on the size of the files found, such as `+1M` or `-10k`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeBound {
+    /// How the size is compared with the bound.
+    pub comparison: Comparison,
+
+    /// The bound, in bytes.
+    pub bytes: u64,
+}
+
+impl SizeBound {
+    /// Parses a bound written like the `-size` test of find(1).
+    ///
+    /// A leading `+` selects larger files, a leading `-` smaller ones and no sign files of
+    /// exactly that size. The number may be followed by the binary units `k`, `M`, `G` or
+    /// `T`, optionally followed by `B` or `iB`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The bound, such as `+1M`, `-512k` or `0`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `SizeBound` or `Error::InvalidPredicate`.
+    pub fn parse(value: &str) -> Result<Self> {
+        let invalid = |reason: &str| Error::InvalidPredicate {
+            value: value.to_string(),
+            reason: reason.to_string(),
+        };
+        let (comparison, rest): (Comparison, &str) = match value.as_bytes().first() {
+            Some(b'+') => (Comparison::Greater, &value[1..]),
+            Some(b'-') => (Comparison::Less, &value[1..]),
+            _ => (Comparison::Equal, value),
+        };
+        let digits: usize = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let number: u64 = rest[..digits]
+            .parse()
+            .map_err(|_| invalid("expected a number of bytes"))?;
+        let unit: String = rest[digits..].to_ascii_lowercase();
+        let shift: u32 = match unit.trim_end_matches("ib").trim_end_matches('b') {
+            "" => 0,
+            "k" => 10,
+            "m" => 20,
+            "g" => 30,
+            "t" => 40,
+            _ => return Err(invalid("expected a unit among k, M, G and T")),
+        };
+        let bytes: u64 = number
+            .checked_mul(1 << shift)
+            .ok_or_else(|| invalid("the size is too large"))?;
+
+        Ok(SizeBound { comparison, bytes })
+    }
+
+    /// Returns whether a size is within the bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The size, in bytes.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the size is within the bound.
+    pub fn matches(&self, size: u64) -> bool {
+        match self.comparison {
+            Comparison::Less => size < self.bytes,
+            Comparison::Equal => size == self.bytes,
+            Comparison::Greater => size > self.bytes,
+        }
+    }
+}
+
+/// How the permission bits of an entry are compared with those of a `PermissionBits`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PermissionMatch {
+    /// The permissions are exactly the given bits.
+    Exact,
+
+    /// Every given bit is set.
+    All,
+
+    /// At least one of the given bits is set.
+    Any,
+}
+
+/// The permission bits of the entries found, such as `644` or `/022`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PermissionBits {
+    /// How the bits are compared.
+    pub kind: PermissionMatch,
+
+    /// The bits, such as `0o644`.
+    pub mode: u32,
+}
+
+impl PermissionBits {
+    /// Parses octal permission bits written like the `-perm` test of find(1).
+    ///
+    /// A leading `-` requires every given bit to be set, a leading `/` at least one of
+    /// them, and no prefix exactly those bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The bits, such as `644`, `-444` or `/022`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `PermissionBits` or `Error::InvalidPredicate`.
+    pub fn parse(value: &str) -> Result<Self> {
+        let (kind, octal): (PermissionMatch, &str) = match value.as_bytes().first() {
+            Some(b'-') => (PermissionMatch::All, &value[1..]),
+            Some(b'/') => (PermissionMatch::Any, &value[1..]),
+            _ => (PermissionMatch::Exact, value),
+        };
+        let mode: u32 = u32::from_str_radix(octal, 8)
+            .ok()
+            .filter(|mode| *mode <= 0o777)
+            .ok_or_else(|| Error::InvalidPredicate {
+                value: value.to_string(),
+                reason: "expected octal permission bits up to 777".to_string(),
+            })?;
+
+        Ok(PermissionBits { kind, mode })
+    }
+
+    /// Returns whether permissions such as `-rw-r--r--` match the bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `permissions` - The permissions, as listed by the server.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the permissions match, `false` when they cannot be understood.
+    pub fn matches(&self, permissions: &str) -> bool {
+        let Some(mode) = permission_mode(permissions) else {
+            return false;
+        };
+
+        match self.kind {
+            PermissionMatch::Exact => mode == self.mode,
+            PermissionMatch::All => mode & self.mode == self.mode,
+            PermissionMatch::Any => self.mode == 0 || mode & self.mode != 0,
+        }
+    }
+}
+
+/// The tests an entry has to pass to be found.
+///
+/// Every test that is set has to pass. Entries missing the metadata a test needs, such as
+/// a file of unknown size when a size bound is given, do not pass it.
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    /// Globs matched against the name of the entry, or its full path when they contain a
+    /// `/`. One of them has to match.
+    pub name: Vec<Pattern>,
+
+    /// Regular expressions matched against the name of the entry, or its full path when
+    /// built `with_full_path`. One of them has to match.
+    pub regex: Vec<Pattern>,
+
+    /// The type of the entry.
+    pub node_type: Option<NodeType>,
+
+    /// Bounds on the size of the entry, which all have to hold. Directories never pass.
+    pub size: Vec<SizeBound>,
+
+    /// The entry was modified less than this long ago.
+    pub newer_than: Option<Duration>,
+
+    /// The entry was modified more than this long ago.
+    pub older_than: Option<Duration>,
+
+    /// The permission bits of the entry.
+    pub permissions: Option<PermissionBits>,
+
+    /// The user owning the entry.
+    pub owner: Option<String>,
+
+    /// The group owning the entry.
+    pub group: Option<String>,
+}
+
+impl Query {
+    /// Returns whether an entry passes every test of the query.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The entry, holding its full remote path.
+    /// * `now` - The time the modification ages are measured from.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the entry is found.
+    pub fn matches(&self, node: &NodeEnum, now: SystemTime) -> bool {
+        let (name, path): (&str, &str) = (node.name(), node.path());
+        let (node_type, metadata): (NodeType, &Metadata) = match node {
+            NodeEnum::Directory(dir) => (NodeType::Directory, &dir.metadata),
+            NodeEnum::File(file) => (NodeType::File, &file.metadata),
+        };
+        let any = |patterns: &[Pattern]| {
+            patterns.is_empty() || patterns.iter().any(|pattern| pattern.matches(name, path))
+        };
+
+        if !any(&self.name) || !any(&self.regex) {
+            return false;
+        }
+
+        if self.node_type.is_some_and(|wanted| wanted != node_type) {
+            return false;
+        }
+
+        if !self.size.is_empty() {
+            let size: Option<u64> = metadata.size.filter(|_| node_type == NodeType::File);
+
+            if !size.is_some_and(|size| self.size.iter().all(|bound| bound.matches(size))) {
+                return false;
+            }
+        }
+
+        if self.newer_than.is_some() || self.older_than.is_some() {
+            let Some(age) = metadata
+                .modified
+                .as_deref()
+                .and_then(|modified| modified_time(modified, now))
+                .map(|time| now.duration_since(time).unwrap_or_default())
+            else {
+                return false;
+            };
+
+            if self.newer_than.is_some_and(|limit| age >= limit)
+                || self.older_than.is_some_and(|limit| age <= limit)
+            {
+                return false;
+            }
+        }
+
+        if let Some(bits) = &self.permissions {
+            if !metadata
+                .permissions
+                .as_deref()
+                .is_some_and(|permissions| bits.matches(permissions))
+            {
+                return false;
+            }
+        }
+
+        let same = |wanted: &Option<String>, actual: &Option<String>| {
+            wanted.is_none() || wanted.as_deref() == actual.as_deref()
+        };
+
+        same(&self.owner, &metadata.owner) && same(&self.group, &metadata.group)
+    }
+}
+
+/// An entry found by a query.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Match {
+    /// The full remote path of the entry.
+    pub path: String,
+
+    /// Whether the entry is a file or a directory.
+    #[serde(rename = "type")]
+    pub node_type: NodeType,
+
+    /// The metadata of the entry.
+    #[serde(skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: Metadata,
+}
+
+impl From<&NodeEnum> for Match {
+    fn from(node: &NodeEnum) -> Self {
+        let (node_type, metadata): (NodeType, &Metadata) = match node {
+            NodeEnum::Directory(dir) => (NodeType::Directory, &dir.metadata),
+            NodeEnum::File(file) => (NodeType::File, &file.metadata),
+        };
+
+        Match {
+            path: node.path().to_string(),
+            node_type,
+            metadata: metadata.clone(),
+        }
+    }
+}
+
+/// Walks a tree depth-first and returns the entries passing the query, parents before
+/// their children. The root itself is never returned.
+///
+/// # Arguments
+///
+/// * `root` - The root of the tree.
+/// * `query` - The tests the entries have to pass.
+///
+/// # Returns
+///
+/// The entries found, in the order they were walked.
+pub fn find(root: &Directory, query: &Query) -> Vec<Match> {
+    let mut found: Vec<Match> = Vec::new();
+
+    walk(root, query, SystemTime::now(), &mut found);
+
+    found
+}
+
+/// Collects the entries of a directory passing the query.
+///
+/// # Arguments
+///
+/// * `dir` - The directory.
+/// * `query` - The tests the entries have to pass.
+/// * `now` - The time the modification ages are measured from.
+/// * `found` - The entries found so far.
+fn walk(dir: &Directory, query: &Query, now: SystemTime, found: &mut Vec<Match>) {
+    for node in &dir.nodes {
+        if query.matches(node, now) {
+            found.push(Match::from(node));
+        }
+
+        if let NodeEnum::Directory(subdir) = node {
+            walk(subdir, query, now, found);
+        }
+    }
+}
+
+/// Formats the entries found as a JSON array.
+///
+/// # Arguments
+///
+/// * `found` - The entries found.
+///
+/// # Returns
+///
+/// A `String` containing the JSON array.
+pub fn to_json(found: &[Match]) -> String {
+    serde_json::to_string_pretty(found).expect("matches are always serializable")
+}
+
+/// Runs a shell command for an entry found, like the `-exec` action of find(1).
+///
+/// Every `{}` in the command is replaced by the quoted remote path of the entry. The
+/// command is run by `sh -c` with the `TREE_FTP_PATH` and `TREE_FTP_TYPE` environment
+/// variables set, and its failures are logged.
+///
+/// # Arguments
+///
+/// * `command` - The shell command, such as `echo {}`.
+/// * `found` - The entry found.
+///
+/// # Returns
+///
+/// `true` if the command ran and succeeded.
+pub fn exec(command: &str, found: &Match) -> bool {
+    let quoted: String = format!("'{}'", found.path.replace('\'', r"'\''"));
+    let node_type: &str = match found.node_type {
+        NodeType::Directory => "directory",
+        NodeType::File => "file",
+    };
+
+    match Command::new("sh")
+        .arg("-c")
+        .arg(command.replace("{}", &quoted))
+        .env("TREE_FTP_PATH", &found.path)
+        .env("TREE_FTP_TYPE", node_type)
+        .status()
+    {
+        Ok(status) if status.success() => true,
+        Ok(status) => {
+            warn!("Command failed for {}: {}", found.path, status);
+
+            false
+        }
+        Err(e) => {
+            warn!("Cannot run the command for {}: {}", found.path, e);
+
+            false
+        }
+    }
+}
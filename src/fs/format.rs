@@ -0,0 +1,495 @@
+use serde_json::json;
+
+use super::{
+    directory::Directory,
+    dot::Dot,
+    jsonl::Record,
+    metadata::Metadata,
+    ncdu,
+    node::{Node, NodeEnum},
+    report, snapshot,
+    tree::TreeOptions,
+    usage::{human_size, Usage},
+};
+
+/// Turns a crawled tree into text.
+///
+/// The formats of `--format` are implemented on top of this trait, and library users can
+/// implement it to render trees their own way.
+pub trait TreeFormatter {
+    /// Formats a tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The root of the tree, its entries holding their full remote paths.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the formatted tree, ending with a newline.
+    fn format(&self, root: &Directory) -> String;
+}
+
+/// The formats the tree can be written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// An indented tree drawn with box-drawing characters.
+    #[default]
+    Tree,
+
+    /// A versioned JSON snapshot, which the subcommands can read back.
+    Json,
+
+    /// One JSON object per entry, written as the entries are listed.
+    Jsonl,
+
+    /// One CSV row per entry, with a header row.
+    Csv,
+
+    /// A YAML document mirroring the JSON snapshot.
+    Yaml,
+
+    /// An XML document in the spirit of `tree -X`.
+    Xml,
+
+    /// A self-contained HTML report with a collapsible tree, a search box, size bars and
+    /// sortable columns.
+    Html,
+
+    /// A Markdown nested list.
+    #[value(alias = "md")]
+    Markdown,
+
+    /// A Graphviz graph, drawn as configured by `Dot`.
+    Dot,
+
+    /// The JSON export format of ncdu, to browse the tree with `ncdu -f`.
+    Ncdu,
+}
+
+impl OutputFormat {
+    /// Returns the formatter writing the format.
+    ///
+    /// # Returns
+    ///
+    /// A `Box` holding the formatter.
+    pub fn formatter(&self) -> Box<dyn TreeFormatter> {
+        match self {
+            OutputFormat::Tree => Box::new(TreeText {
+                options: TreeOptions::default(),
+            }),
+            OutputFormat::Json => Box::new(Json),
+            OutputFormat::Jsonl => Box::new(JsonLines),
+            OutputFormat::Csv => Box::new(Csv),
+            OutputFormat::Yaml => Box::new(Yaml),
+            OutputFormat::Xml => Box::new(Xml),
+            OutputFormat::Html => Box::new(Html),
+            OutputFormat::Markdown => Box::new(Markdown),
+            OutputFormat::Dot => Box::new(Dot::default()),
+            OutputFormat::Ncdu => Box::new(Ncdu),
+        }
+    }
+}
+
+/// The indented tree printed by default, drawn like tree(1).
+pub struct TreeText {
+    /// The characters, order and details of the tree.
+    pub options: TreeOptions,
+}
+
+impl TreeFormatter for TreeText {
+    fn format(&self, root: &Directory) -> String {
+        self.options.render(root)
+    }
+}
+
+/// The versioned JSON snapshot described by `Snapshot`.
+pub struct Json;
+
+impl TreeFormatter for Json {
+    fn format(&self, root: &Directory) -> String {
+        format!("{}\n", snapshot::to_json(root))
+    }
+}
+
+/// The JSON Lines written by `JsonLinesWriter`, for a tree that is already crawled.
+pub struct JsonLines;
+
+impl TreeFormatter for JsonLines {
+    fn format(&self, root: &Directory) -> String {
+        let mut text: String = String::new();
+
+        walk(root, 1, &mut |node, depth| {
+            text.push_str(
+                &serde_json::to_string(&Record::new(node, depth))
+                    .expect("records are always serializable"),
+            );
+            text.push('\n');
+        });
+
+        text
+    }
+}
+
+/// One CSV row per entry, following RFC 4180.
+pub struct Csv;
+
+impl TreeFormatter for Csv {
+    fn format(&self, root: &Directory) -> String {
+        let mut text: String =
+            String::from("path,type,depth,size,modified,permissions,owner,group\n");
+
+        walk(root, 1, &mut |node, depth| {
+            let Entry {
+                node_type,
+                metadata,
+                ..
+            } = Entry::of_node(node);
+            let fields: [String; 8] = [
+                node.path().to_string(),
+                node_type.to_string(),
+                depth.to_string(),
+                metadata
+                    .size
+                    .map(|size| size.to_string())
+                    .unwrap_or_default(),
+                metadata.modified.clone().unwrap_or_default(),
+                metadata.permissions.clone().unwrap_or_default(),
+                metadata.owner.clone().unwrap_or_default(),
+                metadata.group.clone().unwrap_or_default(),
+            ];
+            let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+
+            text.push_str(&row.join(","));
+            text.push('\n');
+        });
+
+        text
+    }
+}
+
+/// A YAML document holding the same fields as the JSON snapshot.
+///
+/// Strings are written double-quoted, so names looking like numbers or booleans keep
+/// their type.
+pub struct Yaml;
+
+impl TreeFormatter for Yaml {
+    fn format(&self, root: &Directory) -> String {
+        let mut text: String = String::new();
+
+        for line in yaml_mapping(&Entry::of_directory(root)) {
+            text.push_str(&line);
+            text.push('\n');
+        }
+
+        text
+    }
+}
+
+/// An XML document in the spirit of `tree -X`, ending with a report of the totals.
+pub struct Xml;
+
+impl TreeFormatter for Xml {
+    fn format(&self, root: &Directory) -> String {
+        let usage: Usage = root.usage();
+        let mut text: String = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tree>\n");
+
+        xml_node(&Entry::of_directory(root), "  ", &mut text);
+        text.push_str(&format!(
+            "  <report>\n    <directories>{}</directories>\n    <files>{}</files>\n  </report>\n</tree>\n",
+            usage.dirs, usage.files
+        ));
+
+        text
+    }
+}
+
+/// The self-contained interactive report written by `report::to_html`.
+pub struct Html;
+
+impl TreeFormatter for Html {
+    fn format(&self, root: &Directory) -> String {
+        report::to_html(root)
+    }
+}
+
+/// The JSON export written by `ncdu::to_ncdu_json`.
+pub struct Ncdu;
+
+impl TreeFormatter for Ncdu {
+    fn format(&self, root: &Directory) -> String {
+        format!("{}\n", ncdu::to_ncdu_json(root))
+    }
+}
+
+/// A Markdown nested list under a heading naming the root.
+pub struct Markdown;
+
+impl TreeFormatter for Markdown {
+    fn format(&self, root: &Directory) -> String {
+        let mut text: String = format!("# {}\n\n", escape_markdown(&root.path));
+
+        walk(root, 1, &mut |node, depth| {
+            let indent: String = "  ".repeat(depth - 1);
+
+            match node {
+                NodeEnum::Directory(dir) => text.push_str(&format!(
+                    "{}- **{}/**\n",
+                    indent,
+                    escape_markdown(&dir.name)
+                )),
+                NodeEnum::File(file) => {
+                    text.push_str(&format!("{}- {}", indent, escape_markdown(&file.name)));
+
+                    if let Some(size) = file.metadata.size {
+                        text.push_str(&format!(" ({})", human_size(size)));
+                    }
+
+                    text.push('\n');
+                }
+            }
+        });
+
+        text
+    }
+}
+
+/// Visits the entries of a directory depth-first, parents before their children.
+///
+/// # Arguments
+///
+/// * `dir` - The directory.
+/// * `depth` - The depth of the entries of the directory.
+/// * `visit` - The callback receiving every entry and its depth.
+fn walk(dir: &Directory, depth: usize, visit: &mut dyn FnMut(&NodeEnum, usize)) {
+    for node in &dir.nodes {
+        visit(node, depth);
+
+        if let NodeEnum::Directory(subdir) = node {
+            walk(subdir, depth + 1, visit);
+        }
+    }
+}
+
+/// The fields of an entry written by the formats, borrowed from a node or from the root.
+struct Entry<'a> {
+    /// The type, `directory` or `file`.
+    node_type: &'static str,
+
+    /// The name of the entry.
+    name: &'a str,
+
+    /// The full remote path of the entry.
+    path: &'a str,
+
+    /// The metadata of the entry.
+    metadata: &'a Metadata,
+
+    /// The entries of a directory, `None` for a file.
+    children: Option<&'a [NodeEnum]>,
+}
+
+impl<'a> Entry<'a> {
+    /// Borrows the fields of a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory.
+    ///
+    /// # Returns
+    ///
+    /// The `Entry` of the directory.
+    fn of_directory(dir: &'a Directory) -> Self {
+        Entry {
+            node_type: "directory",
+            name: &dir.name,
+            path: &dir.path,
+            metadata: &dir.metadata,
+            children: Some(&dir.nodes),
+        }
+    }
+
+    /// Borrows the fields of a node.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node.
+    ///
+    /// # Returns
+    ///
+    /// The `Entry` of the node.
+    fn of_node(node: &'a NodeEnum) -> Self {
+        match node {
+            NodeEnum::Directory(dir) => Self::of_directory(dir),
+            NodeEnum::File(file) => Entry {
+                node_type: "file",
+                name: &file.name,
+                path: &file.path,
+                metadata: &file.metadata,
+                children: None,
+            },
+        }
+    }
+}
+
+/// Quotes a CSV field when it holds a separator, a quote or a line break.
+///
+/// # Arguments
+///
+/// * `field` - The field.
+///
+/// # Returns
+///
+/// A `String` containing the field, quoted if needed.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Converts an entry to the lines of a YAML mapping, without indentation.
+///
+/// # Arguments
+///
+/// * `entry` - The entry.
+///
+/// # Returns
+///
+/// The lines of the mapping.
+fn yaml_mapping(entry: &Entry) -> Vec<String> {
+    let quote = |value: &str| json!(value).to_string();
+    let metadata: &Metadata = entry.metadata;
+    let mut lines: Vec<String> = vec![
+        format!("name: {}", quote(entry.name)),
+        format!("path: {}", quote(entry.path)),
+        format!("type: {}", entry.node_type),
+    ];
+
+    if !metadata.is_empty() {
+        lines.push("metadata:".to_string());
+
+        if let Some(size) = metadata.size {
+            lines.push(format!("  size: {}", size));
+        }
+
+        for (key, value) in [
+            ("modified", &metadata.modified),
+            ("permissions", &metadata.permissions),
+            ("owner", &metadata.owner),
+            ("group", &metadata.group),
+        ] {
+            if let Some(value) = value {
+                lines.push(format!("  {}: {}", key, quote(value)));
+            }
+        }
+    }
+
+    if let Some(children) = entry.children {
+        if children.is_empty() {
+            lines.push("children: []".to_string());
+        } else {
+            lines.push("children:".to_string());
+
+            for child in children {
+                for (i, line) in yaml_mapping(&Entry::of_node(child)).into_iter().enumerate() {
+                    let prefix: &str = if i == 0 { "  - " } else { "    " };
+
+                    lines.push(format!("{}{}", prefix, line));
+                }
+            }
+        }
+    }
+
+    lines
+}
+
+/// Writes an entry as an XML element, its attributes holding its name, path and metadata.
+///
+/// # Arguments
+///
+/// * `entry` - The entry.
+/// * `indent` - The indentation of the element.
+/// * `text` - The document written so far.
+fn xml_node(entry: &Entry, indent: &str, text: &mut String) {
+    let (node_type, metadata): (&str, &Metadata) = (entry.node_type, entry.metadata);
+    let size: Option<String> = metadata.size.map(|size| size.to_string());
+    let mut attributes: String = String::new();
+
+    for (key, value) in [
+        ("name", Some(entry.name)),
+        ("path", Some(entry.path)),
+        ("size", size.as_deref()),
+        ("modified", metadata.modified.as_deref()),
+        ("permissions", metadata.permissions.as_deref()),
+        ("owner", metadata.owner.as_deref()),
+        ("group", metadata.group.as_deref()),
+    ] {
+        if let Some(value) = value {
+            attributes.push_str(&format!(" {}=\"{}\"", key, escape_markup(value)));
+        }
+    }
+
+    match entry.children {
+        Some(children) if !children.is_empty() => {
+            text.push_str(&format!("{}<{}{}>\n", indent, node_type, attributes));
+
+            for child in children {
+                xml_node(&Entry::of_node(child), &format!("{}  ", indent), text);
+            }
+
+            text.push_str(&format!("{}</{}>\n", indent, node_type));
+        }
+        _ => text.push_str(&format!("{}<{}{}/>\n", indent, node_type, attributes)),
+    }
+}
+
+/// Escapes the characters with a meaning in XML and HTML.
+///
+/// # Arguments
+///
+/// * `value` - The text or attribute value.
+///
+/// # Returns
+///
+/// A `String` containing the escaped value.
+pub(crate) fn escape_markup(value: &str) -> String {
+    let mut escaped: String = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Escapes the characters with a meaning in Markdown with backslashes.
+///
+/// # Arguments
+///
+/// * `value` - The text.
+///
+/// # Returns
+///
+/// A `String` containing the escaped text.
+fn escape_markdown(value: &str) -> String {
+    let mut escaped: String = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        if "\\`*_[]<>#|~".contains(c) {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
@@ -0,0 +1,101 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use super::{
+    diff::NodeType,
+    metadata::Metadata,
+    node::{Node, NodeEnum},
+};
+
+/// One line of a JSON Lines stream, describing a single entry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Record<'a> {
+    /// The full remote path of the entry.
+    pub path: &'a str,
+
+    /// Whether the entry is a file or a directory.
+    #[serde(rename = "type")]
+    pub node_type: NodeType,
+
+    /// The depth of the entry, 1 for the entries of the root.
+    pub depth: usize,
+
+    /// The metadata of the entry.
+    #[serde(skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: &'a Metadata,
+}
+
+impl<'a> Record<'a> {
+    /// Describes an entry, without the children of directories.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The entry, holding its full remote path.
+    /// * `depth` - The depth of the entry.
+    ///
+    /// # Returns
+    ///
+    /// The `Record` of the entry.
+    pub fn new(node: &'a NodeEnum, depth: usize) -> Self {
+        let (node_type, metadata): (NodeType, &Metadata) = match node {
+            NodeEnum::Directory(dir) => (NodeType::Directory, &dir.metadata),
+            NodeEnum::File(file) => (NodeType::File, &file.metadata),
+        };
+
+        Record {
+            path: node.path(),
+            node_type,
+            depth,
+            metadata,
+        }
+    }
+}
+
+/// Writes entries as JSON Lines while a tree is crawled, one object per entry.
+///
+/// Every line is flushed as soon as it is written, so readers see the entries without
+/// waiting for the end of the crawl and a crawl that fails leaves complete lines behind.
+pub struct JsonLinesWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    /// Creates a writer on top of the given output.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The output, such as the standard output or a file.
+    ///
+    /// # Returns
+    ///
+    /// A new `JsonLinesWriter` instance.
+    pub fn new(writer: W) -> Self {
+        JsonLinesWriter { writer }
+    }
+
+    /// Writes and flushes the line of an entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The entry, holding its full remote path.
+    /// * `depth` - The depth of the entry, 1 for the entries of the root.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating success or failure.
+    pub fn write(&mut self, node: &NodeEnum, depth: usize) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &Record::new(node, depth))?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+
+    /// Returns the underlying output.
+    ///
+    /// # Returns
+    ///
+    /// The output given to `JsonLinesWriter::new`.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
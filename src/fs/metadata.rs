@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+
+/// Describes a file or directory as reported by the server.
+///
+/// Every field is optional, since servers do not all report the same details and
+/// directories loaded from older snapshots carry none.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metadata {
+    /// The size of the entry, in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+
+    /// The last modification time, as formatted by the server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+
+    /// The permissions of the entry, such as `drwxr-xr-x`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<String>,
+
+    /// The user owning the entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+
+    /// The group owning the entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+impl Metadata {
+    /// Parses the metadata out of a line of a Unix-style LIST reply.
+    ///
+    /// The line is expected to look like the output of `ls -l`:
+    /// `-rw-r--r-- 1 owner group 1024 Jan 01 00:00 name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line of the listing.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `Metadata`, empty if the line does not have the expected columns.
+    pub fn from_list_line(line: &str) -> Self {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+
+        if parts.len() < 9 {
+            return Metadata::default();
+        }
+
+        Metadata {
+            size: parts[4].parse().ok(),
+            modified: Some(parts[5..8].join(" ")),
+            permissions: Some(parts[0].to_string()),
+            owner: Some(parts[2].to_string()),
+            group: Some(parts[3].to_string()),
+        }
+    }
+
+    /// Parses the metadata out of the facts of an MLSD line, as described in RFC 3659.
+    ///
+    /// The facts look like `type=file;size=1024;modify=20250101000000;UNIX.mode=0644;`.
+    /// The modification time is kept as sent, and a `UNIX.mode` is turned into permissions
+    /// such as `-rw-r--r--`.
+    ///
+    /// # Arguments
+    ///
+    /// * `facts` - The facts of the line, without the name of the entry.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `Metadata`, holding only the facts sent by the server.
+    pub fn from_mlsd_facts(facts: &str) -> Self {
+        let mut metadata: Metadata = Metadata::default();
+        let mut is_dir: bool = false;
+        let mut mode: Option<u32> = None;
+
+        for fact in facts.split(';') {
+            let Some((key, value)) = fact.trim().split_once('=') else {
+                continue;
+            };
+
+            match key.to_ascii_lowercase().as_str() {
+                "type" => is_dir = value.eq_ignore_ascii_case("dir"),
+                "size" | "sizd" => metadata.size = value.parse().ok(),
+                "modify" => metadata.modified = Some(value.to_string()),
+                "unix.mode" => mode = u32::from_str_radix(value, 8).ok(),
+                "unix.owner" | "unix.uid" if metadata.owner.is_none() => {
+                    metadata.owner = Some(value.to_string())
+                }
+                "unix.group" | "unix.gid" if metadata.group.is_none() => {
+                    metadata.group = Some(value.to_string())
+                }
+                _ => {}
+            }
+        }
+
+        metadata.permissions = mode.map(|mode| {
+            let mut permissions: String = String::from(if is_dir { "d" } else { "-" });
+
+            for shift in [6, 3, 0] {
+                let bits: u32 = (mode >> shift) & 0o7;
+
+                permissions.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+                permissions.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+                permissions.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+            }
+
+            permissions
+        });
+
+        metadata
+    }
+
+    /// Returns whether no detail is known.
+    ///
+    /// # Returns
+    ///
+    /// `true` if every field is `None`.
+    pub fn is_empty(&self) -> bool {
+        *self == Metadata::default()
+    }
+}
+
+/// Converts permissions such as `drwxr-xr-x` to their octal bits.
+///
+/// The type character is skipped, as are the `+` or `@` some servers append for ACLs and
+/// extended attributes. Set-id and sticky flags count as the execute bit they replace
+/// when lowercase.
+///
+/// # Arguments
+///
+/// * `permissions` - The permissions, as listed by the server.
+///
+/// # Returns
+///
+/// The bits, such as `0o755`, or `None` when the permissions cannot be understood.
+pub fn permission_mode(permissions: &str) -> Option<u32> {
+    let chars: Vec<char> = permissions.chars().collect();
+    let start: usize = if chars.len() == 9 { 0 } else { 1 };
+    let bits: &[char] = chars.get(start..start + 9)?;
+    let mut mode: u32 = 0;
+
+    for (index, c) in bits.iter().enumerate() {
+        let set: bool = match (index % 3, c) {
+            (_, '-' | 'S' | 'T') => false,
+            (0, 'r') | (1, 'w') | (2, 'x' | 's' | 't') => true,
+            _ => return None,
+        };
+
+        mode = (mode << 1) | u32::from(set);
+    }
+
+    Some(mode)
+}
@@ -1,3 +1,18 @@
+pub mod diff;
 pub mod directory;
+pub mod dot;
 pub mod file;
-pub mod node;
\ No newline at end of file
+pub mod filter;
+pub mod find;
+pub mod format;
+pub mod jsonl;
+pub mod metadata;
+pub mod ncdu;
+pub mod node;
+pub mod report;
+pub mod snapshot;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod timestamp;
+pub mod tree;
+pub mod usage;
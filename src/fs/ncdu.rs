@@ -0,0 +1,85 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Map, Value};
+
+use super::{directory::Directory, node::NodeEnum};
+
+/// The major version of the ncdu export format.
+const NCDU_MAJOR_VERSION: u64 = 1;
+
+/// The minor version of the ncdu export format.
+const NCDU_MINOR_VERSION: u64 = 2;
+
+/// Converts a tree to the JSON export format of ncdu, so it can be browsed with
+/// `ncdu -f dump.json`.
+///
+/// The export is an array holding the format version, a header and the root directory.
+/// Every directory is an array whose first element describes the directory and whose
+/// other elements are its children:
+///
+/// ```json
+/// [1,2,{"progname":"tree-ftp","progver":"0.1.0","timestamp":1767225600},
+///   [{"name":"/"},{"name":"a.txt","asize":3,"dsize":3},[{"name":"pub"}]]]
+/// ```
+///
+/// Files report their size as both their apparent and disk size, since the server does
+/// not tell how many blocks they use.
+///
+/// # Arguments
+///
+/// * `root` - The root of the tree, named after its full remote path.
+///
+/// # Returns
+///
+/// A `String` containing the JSON document.
+pub fn to_ncdu_json(root: &Directory) -> String {
+    let timestamp: u64 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let export: Value = json!([
+        NCDU_MAJOR_VERSION,
+        NCDU_MINOR_VERSION,
+        {
+            "progname": env!("CARGO_PKG_NAME"),
+            "progver": env!("CARGO_PKG_VERSION"),
+            "timestamp": timestamp,
+        },
+        directory_entry(root, &root.path),
+    ]);
+
+    export.to_string()
+}
+
+/// Converts a directory and its children to an ncdu directory array.
+///
+/// # Arguments
+///
+/// * `dir` - The directory.
+/// * `name` - The name written for the directory.
+///
+/// # Returns
+///
+/// The `Value` of the directory.
+fn directory_entry(dir: &Directory, name: &str) -> Value {
+    let mut entries: Vec<Value> = vec![json!({ "name": name })];
+
+    for node in &dir.nodes {
+        entries.push(match node {
+            NodeEnum::Directory(subdir) => directory_entry(subdir, &subdir.name),
+            NodeEnum::File(file) => {
+                let mut info: Map<String, Value> = Map::new();
+
+                info.insert("name".to_string(), json!(file.name));
+
+                if let Some(size) = file.metadata.size {
+                    info.insert("asize".to_string(), json!(size));
+                    info.insert("dsize".to_string(), json!(size));
+                }
+
+                Value::Object(info)
+            }
+        });
+    }
+
+    Value::Array(entries)
+}
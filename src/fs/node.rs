@@ -1,8 +1,9 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::directory::Directory;
 use super::file::File;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TraversalType {
     BFS,
     DFS,
@@ -18,10 +19,20 @@ pub trait Node {
     ///
     /// A string slice that holds the name of the node.
     fn name(&self) -> &str;
+
+    /// Returns the full remote path of the node.
+    ///
+    /// # Returns
+    ///
+    /// A string slice that holds the path of the node.
+    fn path(&self) -> &str;
 }
 
 /// An enum representing either a file or a directory in the filesystem.
-#[derive(Clone, Debug, Serialize)]
+///
+/// Nodes are serialized with a `type` field set to `directory` or `file`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum NodeEnum {
     /// A directory node.
     Directory(Directory),
@@ -63,6 +74,18 @@ impl Node for NodeEnum {
             NodeEnum::File(file) => file.name(),
         }
     }
+
+    /// Returns the full remote path of the node.
+    ///
+    /// # Returns
+    ///
+    /// A string slice that holds the path of the node.
+    fn path(&self) -> &str {
+        match self {
+            NodeEnum::Directory(dir) => dir.path(),
+            NodeEnum::File(file) => file.path(),
+        }
+    }
 }
 
 /// Implementing the `From` trait for `File` to allow easy conversion from `File` to `NodeEnum`.
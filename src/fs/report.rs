@@ -0,0 +1,306 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use serde_json::{json, Value};
+
+use super::{
+    directory::Directory,
+    format::escape_markup,
+    metadata::Metadata,
+    node::NodeEnum,
+    timestamp::modified_time,
+    usage::{human_size, usage_by_path, Usage},
+};
+
+/// The style sheet of the report.
+const STYLE: &str = r#"
+body { font: 14px/1.4 system-ui, sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.4em; margin: 0 0 0.2em; word-break: break-all; }
+.summary { color: #666; margin: 0 0 1em; }
+.controls { display: flex; gap: 0.5em; margin-bottom: 1em; }
+.controls input { flex: 1; padding: 0.4em 0.6em; font: inherit; }
+.controls button { font: inherit; padding: 0.4em 0.8em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { padding: 0.25em 0.6em; border-bottom: 1px solid #eee; text-align: left; white-space: nowrap; }
+th { cursor: pointer; user-select: none; background: #f6f6f6; position: sticky; top: 0; }
+th[aria-sort="ascending"]::after { content: " \25B2"; }
+th[aria-sort="descending"]::after { content: " \25BC"; }
+td.name { white-space: normal; word-break: break-all; }
+td.number { text-align: right; font-variant-numeric: tabular-nums; }
+td.size { width: 14em; }
+.bar { height: 4px; background: #4a90d9; border-radius: 2px; margin-top: 2px; }
+.toggle { border: 0; background: none; cursor: pointer; width: 1.5em; padding: 0; font: inherit; }
+.spacer { display: inline-block; width: 1.5em; }
+.hit { background: #fff3a3; }
+footer { color: #999; margin-top: 1em; font-size: 0.9em; }
+"#;
+
+/// The script rendering the tree, filtering it and sorting its columns.
+const SCRIPT: &str = r#"
+(function () {
+  "use strict";
+  var root = JSON.parse(document.getElementById("tree-data").textContent);
+  var rows = document.getElementById("rows");
+  var search = document.getElementById("search");
+  var headers = document.querySelectorAll("th[data-key]");
+  var units = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+  var total = root.s || 1;
+  var expanded = new Set();
+  var sortKey = "n";
+  var sortDir = 1;
+
+  function humanSize(bytes) {
+    var size = bytes, unit = 0;
+    while (size >= 1024 && unit < units.length - 1) { size /= 1024; unit += 1; }
+    return unit === 0 ? bytes + " B" : size.toFixed(1) + " " + units[unit];
+  }
+
+  function each(node, visit) {
+    (node.c || []).forEach(function (child) { visit(child); each(child, visit); });
+  }
+
+  function mark(node, query) {
+    node.hit = query !== "" && node.n.toLowerCase().indexOf(query) !== -1;
+    node.keep = query === "" || node.hit;
+    (node.c || []).forEach(function (child) {
+      if (mark(child, query)) { node.keep = true; }
+    });
+    return node.keep;
+  }
+
+  function compare(a, b) {
+    var x = a[sortKey], y = b[sortKey];
+    if (x === undefined || x === null) { x = sortKey === "n" ? "" : -1; }
+    if (y === undefined || y === null) { y = sortKey === "n" ? "" : -1; }
+    if (typeof x === "string") { x = x.toLowerCase(); y = String(y).toLowerCase(); }
+    if (x < y) { return -sortDir; }
+    if (x > y) { return sortDir; }
+    return a.n < b.n ? -1 : a.n > b.n ? 1 : 0;
+  }
+
+  function cell(tr, className, text) {
+    var td = document.createElement("td");
+    td.className = className;
+    td.textContent = text;
+    tr.appendChild(td);
+    return td;
+  }
+
+  function row(node, depth, searching) {
+    var tr = document.createElement("tr");
+    var name = cell(tr, "name", "");
+    var open = searching || expanded.has(node.p);
+    var label = document.createElement("span");
+
+    name.style.paddingLeft = (0.6 + depth * 1.25) + "em";
+
+    if (node.d) {
+      var toggle = document.createElement("button");
+      toggle.className = "toggle";
+      toggle.textContent = open ? "▾" : "▸";
+      toggle.setAttribute("aria-expanded", String(open));
+      toggle.addEventListener("click", function () {
+        if (expanded.has(node.p)) { expanded.delete(node.p); } else { expanded.add(node.p); }
+        render();
+      });
+      name.appendChild(toggle);
+    } else {
+      var spacer = document.createElement("span");
+      spacer.className = "spacer";
+      name.appendChild(spacer);
+    }
+
+    label.textContent = node.d ? node.n + "/" : node.n;
+    label.title = node.p;
+    if (node.hit) { label.className = "hit"; }
+    name.appendChild(label);
+
+    var size = cell(tr, "number size", node.s === null ? "" : humanSize(node.s));
+    if (node.s !== null) {
+      var bar = document.createElement("div");
+      bar.className = "bar";
+      bar.style.width = Math.max(100 * node.s / total, node.s > 0 ? 0.5 : 0) + "%";
+      size.appendChild(bar);
+    }
+
+    cell(tr, "number", node.d ? String(node.f) : "");
+    cell(tr, "", node.t || "");
+    cell(tr, "", node.r || "");
+    return tr;
+  }
+
+  function render() {
+    var query = search.value.trim().toLowerCase();
+    var fragment = document.createDocumentFragment();
+
+    mark(root, query);
+    (function add(node, depth) {
+      (node.c || []).slice().sort(compare).forEach(function (child) {
+        if (!child.keep) { return; }
+        fragment.appendChild(row(child, depth, query !== ""));
+        if (child.d && (query !== "" || expanded.has(child.p))) { add(child, depth + 1); }
+      });
+    })(root, 0);
+    rows.replaceChildren(fragment);
+
+    headers.forEach(function (th) {
+      th.setAttribute("aria-sort", th.dataset.key === sortKey
+        ? (sortDir > 0 ? "ascending" : "descending") : "none");
+    });
+  }
+
+  headers.forEach(function (th) {
+    th.addEventListener("click", function () {
+      if (sortKey === th.dataset.key) {
+        sortDir = -sortDir;
+      } else {
+        sortKey = th.dataset.key;
+        sortDir = sortKey === "n" || sortKey === "r" ? 1 : -1;
+      }
+      render();
+    });
+  });
+
+  document.getElementById("expand").addEventListener("click", function () {
+    each(root, function (node) { if (node.d) { expanded.add(node.p); } });
+    render();
+  });
+
+  document.getElementById("collapse").addEventListener("click", function () {
+    expanded.clear();
+    render();
+  });
+
+  search.addEventListener("input", render);
+  render();
+})();
+"#;
+
+/// Converts a tree to a self-contained HTML report, to share crawl results with people
+/// who do not use the command line.
+///
+/// The report holds a collapsible tree with a search box, size bars relative to the
+/// whole tree and columns sortable by name, size, number of files, modification time and
+/// permissions. The tree is embedded as JSON and rendered by an inline script, so the file
+/// needs no network access. Directories report the recursive totals of their files.
+///
+/// # Arguments
+///
+/// * `root` - The root of the tree.
+///
+/// # Returns
+///
+/// A `String` containing the HTML page.
+pub fn to_html(root: &Directory) -> String {
+    let usages: HashMap<String, Usage> = usage_by_path(root);
+    let data: Value = directory_data(root, &usages, SystemTime::now());
+    let usage: Usage = usages.get(&root.path).copied().unwrap_or_default();
+    let title: String = escape_markup(&root.path);
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>{style}</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p class="summary">{dirs} directories, {files} files, {size}</p>
+<div class="controls">
+<input id="search" type="search" placeholder="Search names" aria-label="Search names">
+<button id="expand" type="button">Expand all</button>
+<button id="collapse" type="button">Collapse all</button>
+</div>
+<table>
+<thead>
+<tr><th data-key="n">Name</th><th data-key="s">Size</th><th data-key="f">Files</th><th data-key="m">Modified</th><th data-key="r">Permissions</th></tr>
+</thead>
+<tbody id="rows"></tbody>
+</table>
+<noscript><p>Enable JavaScript to browse the tree.</p></noscript>
+<footer>Generated by {name} {version}</footer>
+<script type="application/json" id="tree-data">{data}</script>
+<script>{script}</script>
+</body>
+</html>
+"#,
+        title = title,
+        style = STYLE,
+        dirs = usage.dirs,
+        files = usage.files,
+        size = human_size(usage.bytes),
+        name = env!("CARGO_PKG_NAME"),
+        version = env!("CARGO_PKG_VERSION"),
+        data = data.to_string().replace('<', "\\u003c"),
+        script = SCRIPT,
+    )
+}
+
+/// Converts a directory to the JSON object read by the script.
+///
+/// Every entry is an object holding its name `n`, path `p`, whether it is a directory
+/// `d`, its size `s`, its modification time `m` in seconds since the Unix epoch, the same
+/// time as sent by the server `t` and its permissions `r`. The columns are sorted on `m`
+/// and `t` is only displayed. Directories also hold their number of files `f` and their
+/// children `c`.
+///
+/// # Arguments
+///
+/// * `dir` - The directory.
+/// * `usages` - The totals of the directories of the tree, by path.
+/// * `now` - The current time, to date the modification times missing their year.
+///
+/// # Returns
+///
+/// The object of the directory.
+fn directory_data(dir: &Directory, usages: &HashMap<String, Usage>, now: SystemTime) -> Value {
+    let usage: Usage = usages.get(&dir.path).copied().unwrap_or_default();
+    let children: Vec<Value> = dir
+        .nodes
+        .iter()
+        .map(|node| match node {
+            NodeEnum::Directory(subdir) => directory_data(subdir, usages, now),
+            NodeEnum::File(file) => json!({
+                "n": file.name,
+                "p": file.path,
+                "d": false,
+                "s": file.metadata.size,
+                "m": epoch(&file.metadata, now),
+                "t": file.metadata.modified,
+                "r": file.metadata.permissions,
+            }),
+        })
+        .collect();
+
+    json!({
+        "n": dir.name,
+        "p": dir.path,
+        "d": true,
+        "s": usage.bytes,
+        "f": usage.files,
+        "m": epoch(&dir.metadata, now),
+        "t": dir.metadata.modified,
+        "r": dir.metadata.permissions,
+        "c": children,
+    })
+}
+
+/// Returns the modification time of an entry in seconds since the Unix epoch.
+///
+/// # Arguments
+///
+/// * `metadata` - The metadata of the entry.
+/// * `now` - The current time, to date the modification times missing their year.
+///
+/// # Returns
+///
+/// The number of seconds, or `None` when the time is unknown or cannot be understood.
+fn epoch(metadata: &Metadata, now: SystemTime) -> Option<u64> {
+    let time: SystemTime = modified_time(metadata.modified.as_deref()?, now)?;
+
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_secs())
+}
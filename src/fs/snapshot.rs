@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    error::{Error, Result},
+    utils::path,
+};
+
+use super::{directory::Directory, file::File, metadata::Metadata, node::NodeEnum};
+
+/// The version of the snapshot schema written by this version of the program.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// A versioned tree snapshot, as written by `--json`.
+///
+/// Every node carries a `type` of `directory` or `file`, its name, path and metadata, and
+/// directories list their `children` in order:
+///
+/// ```json
+/// {"version":1,"root":{"type":"directory","name":".","path":"/","children":[
+///   {"type":"file","name":"a.txt","path":"/a.txt","metadata":{"size":3}}]}}
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The version of the schema.
+    pub version: u32,
+
+    /// The root of the tree.
+    pub root: NodeEnum,
+}
+
+impl Snapshot {
+    /// Creates a snapshot of the given tree in the current schema version.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The root of the tree.
+    ///
+    /// # Returns
+    ///
+    /// A new `Snapshot` instance.
+    pub fn new(root: NodeEnum) -> Self {
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            root,
+        }
+    }
+
+    /// Serializes the snapshot to JSON.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the JSON document.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("the tree only holds serializable values")
+    }
+}
+
+/// The tree of a snapshot being written, borrowed rather than held like in `Snapshot`.
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    version: u32,
+    root: RootRef<'a>,
+}
+
+/// The root of a snapshot being written, tagged like a `NodeEnum`.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RootRef<'a> {
+    Directory(&'a Directory),
+}
+
+/// Writes a tree as a JSON snapshot in the current schema version, like `Snapshot`
+/// without taking the tree.
+///
+/// # Arguments
+///
+/// * `root` - The root directory of the tree.
+///
+/// # Returns
+///
+/// A `String` containing the JSON document.
+pub fn to_json(root: &Directory) -> String {
+    serde_json::to_string(&SnapshotRef {
+        version: SNAPSHOT_VERSION,
+        root: RootRef::Directory(root),
+    })
+    .expect("the tree only holds serializable values")
+}
+
+/// Loads a tree from a JSON snapshot written by `--json`.
+///
+/// Versioned snapshots are read with the schema of `Snapshot`. Snapshots written before
+/// the schema was versioned are still understood: they map every entry name to its
+/// content, a file being an object holding a string `name` and any other object being a
+/// directory, with the root wrapped in a `Directory` key.
+///
+/// # Arguments
+///
+/// * `json` - The content of the snapshot.
+///
+/// # Returns
+///
+/// A `Result` containing the root directory or an `Error` if the snapshot cannot be read.
+pub fn from_json(json: &str) -> Result<Directory> {
+    let invalid_snapshot = |reason: String| Error::InvalidSnapshot { reason };
+    let value: Value = serde_json::from_str(json).map_err(|e| invalid_snapshot(e.to_string()))?;
+
+    if let Some(version) = value.get("version") {
+        if version.as_u64() != Some(SNAPSHOT_VERSION.into()) {
+            return Err(invalid_snapshot(format!(
+                "unsupported version {}, expected {}",
+                version, SNAPSHOT_VERSION
+            )));
+        }
+
+        let snapshot: Snapshot =
+            serde_json::from_value(value).map_err(|e| invalid_snapshot(e.to_string()))?;
+
+        return match snapshot.root {
+            NodeEnum::Directory(root) => Ok(root),
+            NodeEnum::File(_) => Err(invalid_snapshot("the root is not a directory".to_string())),
+        };
+    }
+
+    from_legacy_json(&value)
+}
+
+/// Loads a tree from a snapshot written before the schema was versioned.
+///
+/// # Arguments
+///
+/// * `value` - The parsed snapshot.
+///
+/// # Returns
+///
+/// A `Result` containing the root directory, with the paths rebuilt from `/`, or an
+/// `Error` if the snapshot cannot be read.
+fn from_legacy_json(value: &Value) -> Result<Directory> {
+    let invalid_snapshot = |reason: String| Error::InvalidSnapshot { reason };
+    let entries: &Map<String, Value> = match value {
+        Value::Object(map) if map.len() == 1 && map.contains_key("Directory") => {
+            match &map["Directory"] {
+                Value::Object(entries) => entries,
+                _ => return Err(invalid_snapshot("the root is not a directory".to_string())),
+            }
+        }
+        Value::Object(map) if map.contains_key("File") => {
+            return Err(invalid_snapshot("the root is not a directory".to_string()))
+        }
+        Value::Object(entries) => entries,
+        _ => return Err(invalid_snapshot("the root is not an object".to_string())),
+    };
+    let mut root: Directory = Directory::with_path(".".to_string(), "/".to_string());
+
+    load_entries(&mut root, entries)?;
+
+    Ok(root)
+}
+
+/// Adds the entries of a snapshot directory to the given directory.
+///
+/// # Arguments
+///
+/// * `dir` - The directory to fill, holding its path.
+/// * `entries` - The entries of the directory in the snapshot.
+///
+/// # Returns
+///
+/// A `Result` indicating success or failure.
+fn load_entries(dir: &mut Directory, entries: &Map<String, Value>) -> Result<()> {
+    for (name, value) in entries {
+        let node_path: String = path::join(&dir.path, name);
+        let object: &Map<String, Value> =
+            value.as_object().ok_or_else(|| Error::InvalidSnapshot {
+                reason: format!("{} is neither a file nor a directory", node_path),
+            })?;
+
+        if let Some(Value::String(file_name)) = object.get("name") {
+            let mut file: File = File::with_path(file_name.to_string(), node_path);
+
+            file.metadata = load_metadata(object.get("metadata"));
+            dir.add(file);
+        } else {
+            let mut subdir: Directory = Directory::with_path(name.to_string(), node_path);
+
+            load_entries(&mut subdir, object)?;
+            dir.add(subdir);
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the metadata recorded for a file, ignoring the fields that cannot be understood.
+///
+/// # Arguments
+///
+/// * `value` - The `metadata` object of the file, if any.
+///
+/// # Returns
+///
+/// The `Metadata` of the file.
+fn load_metadata(value: Option<&Value>) -> Metadata {
+    let field = |key: &str| {
+        value
+            .and_then(|metadata| metadata.get(key))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+
+    Metadata {
+        size: value
+            .and_then(|metadata| metadata.get("size"))
+            .and_then(Value::as_u64),
+        modified: field("modified"),
+        permissions: field("permissions"),
+        owner: field("owner"),
+        group: field("group"),
+    }
+}
@@ -0,0 +1,256 @@
+use std::{path::Path, time::SystemTime};
+
+use rusqlite::{params, Connection, Transaction};
+
+use crate::error::{Error, Result};
+
+use super::{directory::Directory, node::NodeEnum, timestamp::modified_time};
+
+/// The version of the schema, stored in the `user_version` pragma of the database.
+pub const SCHEMA_VERSION: i64 = 2;
+
+/// The tables of the index.
+///
+/// Every crawl gets a row in `crawls`, and every entry it found a row in `entries`
+/// pointing at it, so crawls are only ever appended. Two crawls are compared by joining
+/// their entries on `path`, which the `entries_path` index serves. `modified` holds the
+/// modification time as sent by the server and `modified_at` the same time in RFC 3339,
+/// such as `2025-01-01T12:00:00Z`, so times compare and sort as text.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS crawls (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    host TEXT NOT NULL,
+    root TEXT NOT NULL,
+    started_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS entries (
+    id INTEGER PRIMARY KEY,
+    crawl_id INTEGER NOT NULL REFERENCES crawls (id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    parent TEXT NOT NULL,
+    name TEXT NOT NULL,
+    type TEXT NOT NULL CHECK (type IN ('directory', 'file')),
+    depth INTEGER NOT NULL,
+    size INTEGER,
+    modified TEXT,
+    modified_at TEXT,
+    permissions TEXT,
+    owner TEXT,
+    \"group\" TEXT
+);
+CREATE INDEX IF NOT EXISTS entries_crawl ON entries (crawl_id, parent);
+CREATE INDEX IF NOT EXISTS entries_path ON entries (path, crawl_id);
+CREATE INDEX IF NOT EXISTS crawls_host ON crawls (host, started_at);
+";
+
+/// The statements upgrading a database of the first version of the schema.
+const MIGRATE_FROM_1: &str = "ALTER TABLE entries ADD COLUMN modified_at TEXT;";
+
+/// A SQLite database indexing the entries of crawled trees, to query them with SQL
+/// across servers and dates.
+pub struct SqliteIndex {
+    connection: Connection,
+    path: String,
+}
+
+impl SqliteIndex {
+    /// Opens the database at the given path, creating it and its tables if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the database file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `SqliteIndex`, or `Error::IncompatibleDatabase` when the
+    /// file was written by a newer version of the program.
+    pub fn open(path: &Path) -> Result<Self> {
+        let name: String = path.display().to_string();
+        let connection: Connection =
+            Connection::open(path).map_err(|e| database_error(&name, e))?;
+
+        Self::with_connection(connection, name)
+    }
+
+    /// Opens a database held in memory, which is lost when the index is dropped.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `SqliteIndex`.
+    pub fn open_in_memory() -> Result<Self> {
+        let name: String = String::from(":memory:");
+        let connection: Connection =
+            Connection::open_in_memory().map_err(|e| database_error(&name, e))?;
+
+        Self::with_connection(connection, name)
+    }
+
+    /// Creates the tables of a freshly opened database, or upgrades those of an older one.
+    ///
+    /// Foreign keys are enforced, so deleting a crawl deletes its entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `connection` - The connection to the database.
+    /// * `path` - The path of the database, used in error messages.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `SqliteIndex`.
+    fn with_connection(connection: Connection, path: String) -> Result<Self> {
+        let version: i64 = connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(|e| database_error(&path, e))?;
+
+        if version > SCHEMA_VERSION {
+            return Err(Error::IncompatibleDatabase { path, version });
+        }
+
+        connection
+            .pragma_update(None, "foreign_keys", true)
+            .and_then(|_| match version {
+                1 => connection.execute_batch(MIGRATE_FROM_1),
+                _ => Ok(()),
+            })
+            .and_then(|_| connection.execute_batch(SCHEMA))
+            .and_then(|_| connection.pragma_update(None, "user_version", SCHEMA_VERSION))
+            .map_err(|e| database_error(&path, e))?;
+
+        Ok(SqliteIndex { connection, path })
+    }
+
+    /// Appends a crawl and all of its entries, in a single transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The server the tree was crawled from, such as `ftp.example.com:21`.
+    /// * `started_at` - The time the crawl started.
+    /// * `root` - The root of the crawled tree.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the id of the new crawl.
+    pub fn insert_crawl(
+        &mut self,
+        host: &str,
+        started_at: SystemTime,
+        root: &Directory,
+    ) -> Result<i64> {
+        let path: String = self.path.clone();
+        let transaction: Transaction = self
+            .connection
+            .transaction()
+            .map_err(|e| database_error(&path, e))?;
+
+        transaction
+            .execute(
+                "INSERT INTO crawls (host, root, started_at) VALUES (?1, ?2, ?3)",
+                params![
+                    host,
+                    root.path,
+                    humantime::format_rfc3339_seconds(started_at).to_string()
+                ],
+            )
+            .map_err(|e| database_error(&path, e))?;
+
+        let crawl_id: i64 = transaction.last_insert_rowid();
+
+        insert_entries(&transaction, crawl_id, started_at, root, 1)
+            .map_err(|e| database_error(&path, e))?;
+        transaction.commit().map_err(|e| database_error(&path, e))?;
+
+        Ok(crawl_id)
+    }
+
+    /// Returns the connection to the database, to query it.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the `Connection`.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+}
+
+/// Inserts the entries of a directory and of its subdirectories.
+///
+/// # Arguments
+///
+/// * `transaction` - The transaction of the crawl.
+/// * `crawl_id` - The id of the crawl.
+/// * `started_at` - The time the crawl started, to date the modification times missing
+///   their year.
+/// * `dir` - The directory.
+/// * `depth` - The depth of the entries of the directory, 1 for the root.
+///
+/// # Returns
+///
+/// A `rusqlite::Result` indicating success or failure.
+fn insert_entries(
+    transaction: &Transaction,
+    crawl_id: i64,
+    started_at: SystemTime,
+    dir: &Directory,
+    depth: usize,
+) -> rusqlite::Result<()> {
+    let mut statement = transaction.prepare_cached(
+        "INSERT INTO entries (crawl_id, path, parent, name, type, depth, size, modified, \
+         modified_at, permissions, owner, \"group\") \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+    )?;
+
+    for node in &dir.nodes {
+        let (name, path, node_type, metadata) = match node {
+            NodeEnum::Directory(subdir) => {
+                (&subdir.name, &subdir.path, "directory", &subdir.metadata)
+            }
+            NodeEnum::File(file) => (&file.name, &file.path, "file", &file.metadata),
+        };
+
+        statement.execute(params![
+            crawl_id,
+            path,
+            dir.path,
+            name,
+            node_type,
+            depth as i64,
+            metadata.size.map(|size| size as i64),
+            metadata.modified,
+            metadata
+                .modified
+                .as_deref()
+                .and_then(|modified| modified_time(modified, started_at))
+                .map(|time| humantime::format_rfc3339_seconds(time).to_string()),
+            metadata.permissions,
+            metadata.owner,
+            metadata.group,
+        ])?;
+    }
+
+    drop(statement);
+
+    for node in &dir.nodes {
+        if let NodeEnum::Directory(subdir) = node {
+            insert_entries(transaction, crawl_id, started_at, subdir, depth + 1)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Wraps an error of the database.
+///
+/// # Arguments
+///
+/// * `path` - The path of the database.
+/// * `source` - The error.
+///
+/// # Returns
+///
+/// The `Error::DatabaseError`.
+fn database_error(path: &str, source: rusqlite::Error) -> Error {
+    Error::DatabaseError {
+        path: path.to_string(),
+        source,
+    }
+}
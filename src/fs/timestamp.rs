@@ -0,0 +1,186 @@
+use std::time::{Duration, SystemTime};
+
+/// The abbreviated month names used by Unix-style LIST replies.
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// The number of seconds in a day.
+const DAY: u64 = 24 * 60 * 60;
+
+/// The number of seconds in half a year, past which `ls -l` prints the year of a time
+/// instead of its time of day.
+const HALF_YEAR: u64 = 365 * DAY / 2;
+
+/// Parses a modification time as sent by the server, in UTC.
+///
+/// Both the `20250101120000` form of MLSD and the `Jan 01 12:00` or `Jan 01 2024` forms of
+/// Unix-style LIST replies are understood. LIST replies leave out the year of recent
+/// entries, which is taken to be the one making the time closest to `now` without being
+/// in the future.
+///
+/// # Arguments
+///
+/// * `modified` - The modification time.
+/// * `now` - The current time.
+///
+/// # Returns
+///
+/// The `SystemTime` of the modification, or `None` when it cannot be understood.
+pub fn modified_time(modified: &str, now: SystemTime) -> Option<SystemTime> {
+    let digits: &str = modified.split('.').next().unwrap_or_default();
+
+    if digits.len() == 14 && digits.bytes().all(|b| b.is_ascii_digit()) {
+        let parts: Vec<u32> = [0..4, 4..6, 6..8, 8..10, 10..12, 12..14]
+            .into_iter()
+            .filter_map(|range| digits[range].parse().ok())
+            .collect();
+
+        return utc(parts[0], parts[1], parts[2], parts[3], parts[4], parts[5]);
+    }
+
+    let [month, day, year_or_time] = modified.split_whitespace().collect::<Vec<&str>>()[..] else {
+        return None;
+    };
+    let month: u32 = MONTHS
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(month))? as u32
+        + 1;
+    let day: u32 = day.parse().ok()?;
+
+    match year_or_time.split_once(':') {
+        Some((hour, minute)) => {
+            let (hour, minute): (u32, u32) = (hour.parse().ok()?, minute.parse().ok()?);
+            let year: u32 = humantime::format_rfc3339(now).to_string()[..4]
+                .parse()
+                .ok()?;
+            let time: SystemTime = utc(year, month, day, hour, minute, 0)?;
+
+            if time > now + Duration::from_secs(DAY) {
+                utc(year - 1, month, day, hour, minute, 0)
+            } else {
+                Some(time)
+            }
+        }
+        None => utc(year_or_time.parse().ok()?, month, day, 0, 0, 0),
+    }
+}
+
+/// Tells whether two modification times sent by a server stand for the same time.
+///
+/// Servers switch the LIST form of a file from `Jun 01 12:00` to `Jun 01 2025` once it
+/// is six months old, and MLSD sends `20250601120000`, so the times are compared at the
+/// precision of the least precise one: the day, the minute or the second. Times that
+/// cannot be parsed are compared as sent.
+///
+/// # Arguments
+///
+/// * `old` - The first modification time.
+/// * `new` - The second modification time.
+/// * `now` - The current time, to date the times missing their year.
+///
+/// # Returns
+///
+/// `true` if both times are the same at the precision they share.
+pub fn same_modified_time(old: &str, new: &str, now: SystemTime) -> bool {
+    let (Some(old_time), Some(new_time)) = (modified_time(old, now), modified_time(new, now))
+    else {
+        return old == new;
+    };
+    let precision: u64 = precision(old).max(precision(new));
+    let seconds = |time: SystemTime| {
+        time.duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs() / precision)
+    };
+
+    seconds(old_time) == seconds(new_time)
+}
+
+/// Formats a time the way MLSD sends it, such as `20250101120000`.
+///
+/// # Arguments
+///
+/// * `time` - The time.
+///
+/// # Returns
+///
+/// A `String` holding the UTC date and time as 14 digits.
+pub fn machine_time(time: SystemTime) -> String {
+    humantime::format_rfc3339_seconds(time)
+        .to_string()
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect()
+}
+
+/// Formats a time the way Unix-style LIST replies send it, such as `Jan 01 12:00`.
+///
+/// Like `ls -l`, times within the last six months are printed with their time of day and
+/// older or future ones with their year, such as `Jan 01  2024`, so that `modified_time`
+/// reads them back.
+///
+/// # Arguments
+///
+/// * `time` - The time.
+/// * `now` - The current time.
+///
+/// # Returns
+///
+/// A `String` holding the UTC date and time as three columns.
+pub fn list_time(time: SystemTime, now: SystemTime) -> String {
+    let rfc3339: String = humantime::format_rfc3339_seconds(time).to_string();
+    let month: &str = MONTHS[rfc3339[5..7].parse::<usize>().unwrap_or(1) - 1];
+    let recent: bool = time <= now + Duration::from_secs(DAY)
+        && now
+            .duration_since(time)
+            .map_or(true, |age| age.as_secs() < HALF_YEAR);
+    let year_or_time: String = if recent {
+        rfc3339[11..16].to_string()
+    } else {
+        format!(" {}", &rfc3339[..4])
+    };
+
+    format!(
+        "{}{} {} {}",
+        month[..1].to_ascii_uppercase(),
+        &month[1..],
+        &rfc3339[8..10],
+        year_or_time
+    )
+}
+
+/// Returns the precision of a modification time parsed by `modified_time`.
+///
+/// # Arguments
+///
+/// * `modified` - The modification time.
+///
+/// # Returns
+///
+/// The number of seconds the time is precise to: 1 for MLSD, 60 for LIST times of day and
+/// a day for LIST years.
+fn precision(modified: &str) -> u64 {
+    match modified.split_whitespace().nth(2) {
+        Some(year_or_time) if year_or_time.contains(':') => 60,
+        Some(_) => DAY,
+        None => 1,
+    }
+}
+
+/// Builds a UTC time out of its calendar fields.
+///
+/// # Arguments
+///
+/// * `year`, `month`, `day` - The date, months and days starting at 1.
+/// * `hour`, `minute`, `second` - The time of day.
+///
+/// # Returns
+///
+/// The `SystemTime`, or `None` when a field is out of range.
+fn utc(year: u32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> Option<SystemTime> {
+    humantime::parse_rfc3339(&format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, hour, minute, second
+    ))
+    .ok()
+}
@@ -0,0 +1,360 @@
+use std::{cmp::Ordering, collections::VecDeque, time::SystemTime};
+
+use super::{
+    directory::Directory,
+    metadata::{permission_mode, Metadata},
+    node::{Node, NodeEnum, TraversalType},
+    timestamp::modified_time,
+    usage::Usage,
+};
+
+/// The characters the branches of the tree are drawn with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Charset {
+    /// Box-drawing characters, such as `├──`.
+    #[default]
+    #[value(alias = "utf-8")]
+    Utf8,
+
+    /// Plain ASCII characters, such as `|--`, for terminals and files without UTF-8.
+    Ascii,
+}
+
+impl Charset {
+    /// Returns the prefixes of an entry and of the entries below it.
+    ///
+    /// # Arguments
+    ///
+    /// * `is_last` - Whether the entry is the last one of its directory.
+    ///
+    /// # Returns
+    ///
+    /// The prefix of the line of the entry and the indentation added for its children.
+    fn branches(&self, is_last: bool) -> (&'static str, &'static str) {
+        match (self, is_last) {
+            (Charset::Utf8, false) => ("├── ", "│   "),
+            (Charset::Utf8, true) => ("└── ", "    "),
+            (Charset::Ascii, false) => ("|-- ", "|   "),
+            (Charset::Ascii, true) => ("`-- ", "    "),
+        }
+    }
+}
+
+/// The orders the entries of a directory can be sorted in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// By name, in byte order.
+    Name,
+
+    /// By size, largest first, entries of unknown size last.
+    Size,
+
+    /// By modification time, oldest first, entries of unknown time first.
+    #[value(alias = "time")]
+    Mtime,
+}
+
+/// The options of the indented tree, mirroring those of tree(1).
+#[derive(Clone, Debug, Default)]
+pub struct TreeOptions {
+    /// The characters the branches are drawn with.
+    pub charset: Charset,
+
+    /// Whether to list the directories of every directory before its files.
+    pub dirs_first: bool,
+
+    /// The order the entries of every directory are listed in, the order of the listing
+    /// when `None`.
+    pub sort: Option<SortKey>,
+
+    /// Whether to print the size of every entry, in bytes.
+    pub sizes: bool,
+
+    /// Whether to print sizes with units, such as `1.5K`. Implies `sizes`.
+    pub human: bool,
+
+    /// Whether to print the modification time of every entry.
+    pub dates: bool,
+
+    /// Whether to print the permissions of every entry.
+    pub permissions: bool,
+
+    /// Whether to append `/` to directories and `*` to executable files.
+    pub classify: bool,
+
+    /// Whether to leave out the line counting the directories and files.
+    pub no_report: bool,
+}
+
+impl TreeOptions {
+    /// Draws a tree the way tree(1) does: the path of the root, its entries and a line
+    /// counting the directories and files.
+    ///
+    /// Every directory is drawn right below its line, whatever order the tree was crawled
+    /// in, so the branches stay readable.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The root of the tree.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the tree.
+    pub fn render(&self, root: &Directory) -> String {
+        let mut text: String = format!("{}\n", root.path);
+
+        text.push_str(&self.entries(root, TraversalType::DFS, ""));
+
+        if !self.no_report {
+            let usage: Usage = root.usage();
+
+            text.push_str(&format!(
+                "\n{} {}, {} {}\n",
+                usage.dirs,
+                if usage.dirs == 1 {
+                    "directory"
+                } else {
+                    "directories"
+                },
+                usage.files,
+                if usage.files == 1 { "file" } else { "files" }
+            ));
+        }
+
+        text
+    }
+
+    /// Draws the entries of a directory, without the directory itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory.
+    /// * `traversal_type` - The order the directories are drawn in.
+    /// * `indent` - The indentation of the entries of the directory.
+    ///
+    /// # Returns
+    ///
+    /// A `String` holding one line per entry.
+    pub fn entries(&self, dir: &Directory, traversal_type: TraversalType, indent: &str) -> String {
+        let now: SystemTime = SystemTime::now();
+        let mut text: String = String::new();
+        let mut queue: VecDeque<(&Directory, String)> = VecDeque::from([(dir, indent.to_string())]);
+
+        while let Some((current_dir, current_indent)) = queue.pop_front() {
+            let nodes: Vec<&NodeEnum> = self.sorted(current_dir, now);
+
+            for (i, node) in nodes.iter().enumerate() {
+                let (prefix, child_indent): (&str, &str) =
+                    self.charset.branches(i == nodes.len() - 1);
+
+                text.push_str(&format!(
+                    "{}{}{}\n",
+                    current_indent,
+                    prefix,
+                    self.label(node, now)
+                ));
+
+                if let NodeEnum::Directory(subdir) = node {
+                    let indent: String = format!("{}{}", current_indent, child_indent);
+
+                    match traversal_type {
+                        TraversalType::BFS => queue.push_back((subdir, indent)),
+                        TraversalType::DFS => {
+                            text.push_str(&self.entries(subdir, TraversalType::DFS, &indent))
+                        }
+                    }
+                }
+            }
+        }
+
+        text
+    }
+
+    /// Returns the entries of a directory in the order they are drawn in.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory.
+    /// * `now` - The current time, to date the modification times missing their year.
+    ///
+    /// # Returns
+    ///
+    /// The sorted entries.
+    fn sorted<'a>(&self, dir: &'a Directory, now: SystemTime) -> Vec<&'a NodeEnum> {
+        let mut nodes: Vec<&NodeEnum> = dir.nodes.iter().collect();
+
+        if let Some(key) = self.sort {
+            nodes.sort_by(|a, b| {
+                let (a_metadata, b_metadata): (&Metadata, &Metadata) = (metadata(a), metadata(b));
+                let order: Ordering = match key {
+                    SortKey::Name => Ordering::Equal,
+                    SortKey::Size => b_metadata.size.cmp(&a_metadata.size),
+                    SortKey::Mtime => {
+                        let time = |metadata: &Metadata| {
+                            metadata
+                                .modified
+                                .as_deref()
+                                .and_then(|modified| modified_time(modified, now))
+                        };
+
+                        time(a_metadata).cmp(&time(b_metadata))
+                    }
+                };
+
+                order.then_with(|| a.name().cmp(b.name()))
+            });
+        }
+
+        if self.dirs_first {
+            nodes.sort_by_key(|node| !matches!(node, NodeEnum::Directory(_)));
+        }
+
+        nodes
+    }
+
+    /// Builds the line of an entry: its bracketed details, its name and its suffix.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The entry.
+    /// * `now` - The current time, to date the modification times missing their year.
+    ///
+    /// # Returns
+    ///
+    /// The line, without its prefix.
+    fn label(&self, node: &NodeEnum, now: SystemTime) -> String {
+        let metadata: &Metadata = metadata(node);
+        let mut details: Vec<String> = Vec::new();
+
+        if self.permissions {
+            details.push(format!(
+                "{:<10}",
+                metadata.permissions.as_deref().unwrap_or("?")
+            ));
+        }
+
+        if self.human {
+            details.push(format!(
+                "{:>5}",
+                metadata.size.map_or_else(|| "?".to_string(), compact_size)
+            ));
+        } else if self.sizes {
+            details.push(format!(
+                "{:>11}",
+                metadata
+                    .size
+                    .map_or_else(|| "?".to_string(), |size| size.to_string())
+            ));
+        }
+
+        if self.dates {
+            details.push(format!("{:<16}", date(metadata.modified.as_deref(), now)));
+        }
+
+        let mut label: String = if details.is_empty() {
+            String::new()
+        } else {
+            format!("[{}]  ", details.join(" "))
+        };
+
+        label.push_str(node.name());
+
+        if self.classify {
+            match node {
+                NodeEnum::Directory(_) => label.push('/'),
+                NodeEnum::File(_) if is_executable(metadata) => label.push('*'),
+                NodeEnum::File(_) => {}
+            }
+        }
+
+        label
+    }
+}
+
+/// Returns the metadata of an entry.
+///
+/// # Arguments
+///
+/// * `node` - The entry.
+///
+/// # Returns
+///
+/// A reference to the `Metadata` of the entry.
+fn metadata(node: &NodeEnum) -> &Metadata {
+    match node {
+        NodeEnum::Directory(dir) => &dir.metadata,
+        NodeEnum::File(file) => &file.metadata,
+    }
+}
+
+/// Formats a size the way `tree -h` does, with one-letter binary units.
+///
+/// # Arguments
+///
+/// * `bytes` - The size, in bytes.
+///
+/// # Returns
+///
+/// A `String` such as `512`, `1.5K` or `20M`.
+fn compact_size(bytes: u64) -> String {
+    const UNITS: [char; 6] = ['K', 'M', 'G', 'T', 'P', 'E'];
+
+    if bytes < 1024 {
+        return bytes.to_string();
+    }
+
+    let mut size: f64 = bytes as f64 / 1024.0;
+    let mut unit: usize = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if size < 9.95 {
+        format!("{:.1}{}", size, UNITS[unit])
+    } else {
+        format!("{:.0}{}", size, UNITS[unit])
+    }
+}
+
+/// Formats a modification time as `YYYY-MM-DD HH:MM`, in UTC.
+///
+/// # Arguments
+///
+/// * `modified` - The modification time, as formatted by the server.
+/// * `now` - The current time, to date the modification times missing their year.
+///
+/// # Returns
+///
+/// The formatted time, the time as sent when it cannot be understood, or `?` when unknown.
+fn date(modified: Option<&str>, now: SystemTime) -> String {
+    let Some(modified) = modified else {
+        return "?".to_string();
+    };
+
+    match modified_time(modified, now) {
+        Some(time) => {
+            humantime::format_rfc3339_seconds(time).to_string()[..16].replacen('T', " ", 1)
+        }
+        None => modified.to_string(),
+    }
+}
+
+/// Tells whether the permissions of a file let anyone execute it.
+///
+/// # Arguments
+///
+/// * `metadata` - The metadata of the file.
+///
+/// # Returns
+///
+/// `true` when the permissions, such as `-rwxr-xr-x` or `-rwxr-x---+`, hold an execute
+/// bit.
+fn is_executable(metadata: &Metadata) -> bool {
+    metadata
+        .permissions
+        .as_deref()
+        .and_then(permission_mode)
+        .is_some_and(|mode| mode & 0o111 != 0)
+}
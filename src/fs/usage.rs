@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{directory::Directory, node::NodeEnum};
+
+/// The units used to print sizes, each 1024 times the previous one.
+const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+/// The recursive totals of a directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    /// The sum of the sizes of the files, in bytes. Files of unknown size count as empty.
+    pub bytes: u64,
+
+    /// The number of files.
+    pub files: usize,
+
+    /// The number of directories, not counting the directory itself.
+    pub dirs: usize,
+
+    /// The deepest level holding an entry, 1 for the children of the directory and 0
+    /// when it is empty.
+    pub depth: usize,
+}
+
+/// The totals of a directory, as reported by `du`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DuEntry {
+    /// The full remote path of the directory.
+    pub path: String,
+
+    /// The recursive totals of the directory.
+    pub usage: Usage,
+}
+
+/// Computes the totals of the directories below the root, up to the given depth.
+///
+/// Directories deeper than `max_depth` are not reported on their own, but rolled up into
+/// the totals of their ancestors.
+///
+/// # Arguments
+///
+/// * `root` - The root of the tree.
+/// * `max_depth` - The deepest level of directories to report, 1 for the children of the
+///   root.
+///
+/// # Returns
+///
+/// The entries, largest first, and the totals of the root.
+pub fn du(root: &Directory, max_depth: usize) -> (Vec<DuEntry>, Usage) {
+    let mut entries: Vec<DuEntry> = Vec::new();
+    let total: Usage = collect(root, 0, max_depth, &mut entries);
+
+    entries.sort_by(|a, b| {
+        b.usage
+            .bytes
+            .cmp(&a.usage.bytes)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    (entries, total)
+}
+
+/// Computes the totals of every directory of a tree in a single walk, for the formats
+/// labelling every directory with its totals.
+///
+/// # Arguments
+///
+/// * `root` - The root of the tree.
+///
+/// # Returns
+///
+/// The `Usage` of the root and of every directory below it, by full remote path.
+pub fn usage_by_path(root: &Directory) -> HashMap<String, Usage> {
+    let mut entries: Vec<DuEntry> = Vec::new();
+    let total: Usage = collect(root, 0, usize::MAX, &mut entries);
+
+    entries
+        .into_iter()
+        .map(|entry| (entry.path, entry.usage))
+        .chain([(root.path.clone(), total)])
+        .collect()
+}
+
+/// Computes the totals of a directory, recording the entries of its subdirectories.
+///
+/// # Arguments
+///
+/// * `dir` - The directory.
+/// * `level` - The level of the directory, 0 for the root.
+/// * `max_depth` - The deepest level of directories to report.
+/// * `entries` - The entries found so far.
+///
+/// # Returns
+///
+/// The `Usage` of the directory.
+fn collect(dir: &Directory, level: usize, max_depth: usize, entries: &mut Vec<DuEntry>) -> Usage {
+    let mut usage: Usage = Usage::default();
+
+    for node in &dir.nodes {
+        match node {
+            NodeEnum::File(file) => {
+                usage.bytes += file.metadata.size.unwrap_or(0);
+                usage.files += 1;
+                usage.depth = usage.depth.max(1);
+            }
+            NodeEnum::Directory(subdir) => {
+                let sub: Usage = collect(subdir, level + 1, max_depth, entries);
+
+                if level < max_depth {
+                    entries.push(DuEntry {
+                        path: subdir.path.clone(),
+                        usage: sub,
+                    });
+                }
+
+                usage.bytes += sub.bytes;
+                usage.files += sub.files;
+                usage.dirs += sub.dirs + 1;
+                usage.depth = usage.depth.max(sub.depth + 1);
+            }
+        }
+    }
+
+    usage
+}
+
+/// Formats a size with binary units, such as `1.5 MiB`.
+///
+/// # Arguments
+///
+/// * `bytes` - The size, in bytes.
+///
+/// # Returns
+///
+/// A `String` containing the size with one decimal, or the exact count below 1 KiB.
+pub fn human_size(bytes: u64) -> String {
+    let mut size: f64 = bytes as f64;
+    let mut unit: usize = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Formats a `du` report, one line per directory followed by the totals of the root.
+///
+/// # Arguments
+///
+/// * `entries` - The entries, in the order to print them.
+/// * `total` - The totals of the root.
+/// * `human` - Whether to print sizes with binary units rather than in bytes.
+///
+/// # Returns
+///
+/// A `String` containing the report.
+pub fn to_text(entries: &[DuEntry], total: &Usage, human: bool) -> String {
+    let size = |bytes: u64| {
+        if human {
+            human_size(bytes)
+        } else {
+            bytes.to_string()
+        }
+    };
+    let mut text: String = String::new();
+
+    for entry in entries {
+        text.push_str(&format!(
+            "{:>10}  {:>8} files  {:>6} dirs  {}\n",
+            size(entry.usage.bytes),
+            entry.usage.files,
+            entry.usage.dirs,
+            entry.path
+        ));
+    }
+
+    text.push_str(&format!(
+        "total {} in {} files and {} directories, {} levels deep",
+        size(total.bytes),
+        total.files,
+        total.dirs,
+        total.depth
+    ));
+
+    text
+}
@@ -1,34 +1,93 @@
 use std::{
-    cell::RefCell,
+    collections::VecDeque,
+    io,
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    rc::Rc,
+    thread::sleep,
+    time::Duration,
 };
 
-use log::{debug, info};
+use log::{debug, error, info, warn};
 
 use crate::{
-    fs::{directory::Directory, file::File, node::NodeEnum},
+    fs::{
+        directory::Directory,
+        file::File,
+        metadata::Metadata,
+        node::{Node, NodeEnum},
+    },
     ftp::{
         command::FtpCommand,
         command_stream::CommandStream,
         data_stream::DataStream,
         error::{Error, Result},
+        options::{FtpOptions, ReconnectPolicy},
+        reply::{Reply, ReplyClass},
+        transport::{TcpTransport, Transport},
     },
+    utils::path,
 };
 
-use super::stream::{Responses, Stream};
+use super::stream::Stream;
+
+/// The number of times a directory listing is retried after losing the connection.
+const MAX_LIST_ATTEMPTS: usize = 3;
+
+/// How directories are listed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ListMode {
+    /// A plain LIST, which hides dotfiles on most servers.
+    Plain,
+
+    /// A LIST with the `-a` flag, listing dotfiles on servers honouring it.
+    All,
+
+    /// An MLSD listing, which lists every entry with machine-readable facts.
+    Machine,
+}
+
+/// Why a directory could not be listed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Unlisted {
+    /// The directory could not be entered, whatever the listing command.
+    Unreachable,
+
+    /// The listing command was refused, which another listing command may not be.
+    Refused,
+}
+
+impl ListMode {
+    /// Returns the command listing the working directory in this mode.
+    ///
+    /// # Returns
+    ///
+    /// The `FtpCommand` to send.
+    fn command(&self) -> FtpCommand {
+        match self {
+            ListMode::Plain => FtpCommand::List(None),
+            ListMode::All => FtpCommand::List(Some("-a".to_string())),
+            ListMode::Machine => FtpCommand::Mlsd,
+        }
+    }
+}
 
 /// Represents an FTP client for communicating with an FTP server.
-pub struct FtpClient {
-    extended: bool,
+///
+/// The connections are opened by a `Transport`, plain TCP unless another one is given to
+/// `FtpClient::with_transport`.
+pub struct FtpClient<T: Transport = TcpTransport> {
+    transport: T,
+    options: FtpOptions,
     data_addr: Option<SocketAddr>,
-    ftp_stream: CommandStream,
-    ftp_data_stream: Option<DataStream>,
+    ftp_stream: CommandStream<T::Control>,
+    ftp_data_stream: Option<DataStream<T::Data>>,
     username: String,
     password: String,
+    root_path: Option<String>,
+    features: Vec<String>,
+    list_mode: Option<ListMode>,
 }
 
-impl FtpClient {
+impl FtpClient<TcpTransport> {
     /// Creates a new `FtpClient` and connects to the given address.
     ///
     /// # Arguments
@@ -47,18 +106,87 @@ impl FtpClient {
         password: &String,
         extended: bool,
     ) -> Result<Self> {
-        let mut ftp_stream: CommandStream = CommandStream::new(addr)?;
-        let response: Responses = ftp_stream.read_responses()?;
+        let options: FtpOptions = FtpOptions {
+            extended,
+            ..FtpOptions::default()
+        };
+
+        Self::with_options(addr, username, password, options)
+    }
+
+    /// Creates a new `FtpClient` with the given options and connects to the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The socket address of the FTP server.
+    /// * `username` - The username for authentication.
+    /// * `password` - The password for authentication.
+    /// * `options` - The passive mode, timeouts and reconnect policy to use.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new `FtpClient` or an `Error`.
+    pub fn with_options(
+        addr: SocketAddr,
+        username: &String,
+        password: &String,
+        options: FtpOptions,
+    ) -> Result<Self> {
+        let transport: TcpTransport = TcpTransport::new(addr, &options);
+
+        Self::with_transport(transport, username, password, options)
+    }
+}
+
+impl<T: Transport> FtpClient<T> {
+    /// Creates a new `FtpClient` opening its connections through the given transport.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - The transport opening the control and data connections.
+    /// * `username` - The username for authentication.
+    /// * `password` - The password for authentication.
+    /// * `options` - The passive mode, timeouts and reconnect policy to use.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new `FtpClient` or an `Error`.
+    pub fn with_transport(
+        mut transport: T,
+        username: &String,
+        password: &String,
+        options: FtpOptions,
+    ) -> Result<Self> {
+        let addr: SocketAddr = transport.addr();
+        let control: T::Control = transport
+            .connect_control()
+            .map_err(|e| Error::ConnectionError { addr, source: e })?;
+
+        info!("Connected to the server");
+
+        let mut ftp_stream: CommandStream<T::Control> = CommandStream::new(control);
+        let reply: Reply = ftp_stream.read_reply()?;
 
-        info!("Server response: {:?}", response);
+        info!("Server response: {}", reply.message());
+
+        if reply.is_failure() {
+            return Err(Error::ReplyError {
+                command: "connect".to_string(),
+                reply,
+            });
+        }
 
         Ok(FtpClient {
+            transport,
             username: username.to_string(),
             password: password.to_string(),
-            extended,
+            options,
             data_addr: None,
-            ftp_stream: ftp_stream,
+            ftp_stream,
             ftp_data_stream: None,
+            root_path: None,
+            features: Vec::new(),
+            list_mode: None,
         })
     }
 
@@ -75,15 +203,26 @@ impl FtpClient {
     pub fn authenticate(&mut self, username: &String, password: &String) -> Result<()> {
         info!("Starting authentication");
 
-        self.ftp_stream
-            .send_command(FtpCommand::User(username.to_string()))?;
-
-        let responses: Responses = self
+        let mut reply: Reply = self
             .ftp_stream
-            .send_command(FtpCommand::Pass(password.to_string()))?;
+            .send_command_unchecked(FtpCommand::User(username.to_string()))?;
+
+        if reply.class == ReplyClass::Intermediate {
+            reply = self
+                .ftp_stream
+                .send_command_unchecked(FtpCommand::Pass(password.to_string()))?;
+        }
 
-        if responses.iter().any(|response| response.0 == 530) {
-            return Err(Error::BadLogin);
+        if reply.code == 530 {
+            return Err(Error::BadLogin {
+                username: username.to_string(),
+                reply,
+            });
+        } else if reply.is_failure() {
+            return Err(Error::ReplyError {
+                command: "login".to_string(),
+                reply,
+            });
         }
 
         info!("Authentication successful");
@@ -99,9 +238,17 @@ impl FtpClient {
     pub fn retrieve_server_info(&mut self) -> Result<()> {
         info!("Retrieving server information");
 
-        self.ftp_stream.send_command(FtpCommand::Syst)?;
-        self.ftp_stream.send_command(FtpCommand::Feat)?;
-        self.ftp_stream.send_command(FtpCommand::Pwd)?;
+        self.ftp_stream.send_command_unchecked(FtpCommand::Syst)?;
+        let reply: Reply = self.ftp_stream.send_command_unchecked(FtpCommand::Feat)?;
+
+        if reply.code == 211 && reply.lines.len() > 2 {
+            self.features = reply.lines[1..reply.lines.len() - 1]
+                .iter()
+                .map(|line| line.trim().to_string())
+                .collect();
+        }
+
+        debug!("Server features: {:?}", self.features);
         self.ftp_stream
             .send_command(FtpCommand::Type("I".to_string()))?;
 
@@ -110,13 +257,30 @@ impl FtpClient {
         Ok(())
     }
 
+    /// Returns whether the server advertised the given feature in its FEAT reply.
+    ///
+    /// # Arguments
+    ///
+    /// * `feature` - The name of the feature, such as `MLSD`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the feature is supported.
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|line| {
+            line.split_whitespace()
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case(feature))
+        })
+    }
+
     /// Enters passive mode or extended passive mode based on the `extended` flag.
     ///
     /// # Returns
     ///
     /// A `Result` indicating success or failure.
     pub fn passive_mode(&mut self) -> Result<()> {
-        let command: FtpCommand = if self.extended {
+        let command: FtpCommand = if self.options.extended {
             debug!("Entering in extended passive mode");
 
             FtpCommand::Epsv
@@ -125,46 +289,22 @@ impl FtpClient {
 
             FtpCommand::Pasv
         };
-        let mut responses: Responses = self.ftp_stream.send_command(command.clone())?;
+        let reply: Reply = self.ftp_stream.send_command(command)?;
 
         debug!("Passive mode entered");
 
-        let response: String = loop {
-            let response = responses.pop().ok_or_else(|| {
-                self.ftp_stream
-                    .send_command(command.clone())?
-                    .pop()
-                    .ok_or(Error::NoResponseReceived)
-            });
-
-            match response {
-                Ok((_, response)) => {
-                    if !response.is_empty() {
-                        break response;
-                    } else {
-                        info!("Empty response received, retrying...");
-                        responses = self.ftp_stream.send_command(command.clone())?;
-                    }
-                }
-                Err(e) => {
-                    if let Err(Error::NoResponseReceived) = e {
-                        info!("No response received, attempting to reconnect...");
-                        self.ftp_stream.reconnect()?;
-                        responses = self.ftp_stream.send_command(command.clone())?;
-                    } else {
-                        return Err(e.unwrap_err());
-                    }
-                }
-            }
-        };
+        let addr: SocketAddr = FtpClient::parse_passive_mode_response(self, reply.message())?;
 
-        let addr: SocketAddr = FtpClient::parse_passive_mode_response(self, response)?;
-
-        self.data_addr = Some(addr.clone());
+        self.data_addr = Some(addr);
 
         debug!("Connecting to data client at {}", addr);
 
-        self.ftp_data_stream = Some(DataStream::new(addr)?);
+        let data: T::Data = self
+            .transport
+            .connect_data(addr)
+            .map_err(|e| Error::ConnectionError { addr, source: e })?;
+
+        self.ftp_data_stream = Some(DataStream::new(data));
 
         Ok(())
     }
@@ -181,24 +321,32 @@ impl FtpClient {
     fn parse_passive_mode_response(&mut self, res: String) -> Result<SocketAddr> {
         debug!("Parsing passive mode response: {}", res);
 
+        let invalid_data = || Error::InvalidParsedData {
+            context: "passive mode reply",
+            data: res.clone(),
+        };
+
         if let Some(start) = res.find('(') {
             if let Some(end) = res.find(')') {
                 let content: &str = &res[start + 1..end];
                 let parts: Vec<&str>;
 
-                if self.extended {
+                if self.options.extended {
                     debug!("Parsing extended passive mode");
 
                     parts = content.split('|').collect();
 
                     if parts.len() != 5 {
-                        return Err(Error::InvalidParsedData);
+                        return Err(invalid_data());
                     }
 
-                    let ip: IpAddr = self.ftp_stream.get_addr().ip();
-                    let port: u16 = parts[3]
-                        .parse::<u16>()
-                        .map_err(|_| Error::InvalidParsedPort)?;
+                    let ip: IpAddr = self.transport.addr().ip();
+                    let port: u16 =
+                        parts[3]
+                            .parse::<u16>()
+                            .map_err(|_| Error::InvalidParsedPort {
+                                data: content.to_string(),
+                            })?;
 
                     return Ok(SocketAddr::new(ip, port));
                 } else {
@@ -207,27 +355,29 @@ impl FtpClient {
                     parts = content.split(',').collect();
 
                     if parts.len() < 6 {
-                        return Err(Error::InvalidParsedData);
+                        return Err(invalid_data());
                     }
 
+                    let invalid_ip = |_| Error::InvalidParsedIp {
+                        data: content.to_string(),
+                    };
+                    let invalid_port = |_| Error::InvalidParsedPort {
+                        data: content.to_string(),
+                    };
                     let ip: Ipv4Addr = Ipv4Addr::new(
-                        parts[0].parse().map_err(|_| Error::InvalidParsedIp)?,
-                        parts[1].parse().map_err(|_| Error::InvalidParsedIp)?,
-                        parts[2].parse().map_err(|_| Error::InvalidParsedIp)?,
-                        parts[3].parse().map_err(|_| Error::InvalidParsedIp)?,
+                        parts[0].trim().parse().map_err(invalid_ip)?,
+                        parts[1].trim().parse().map_err(invalid_ip)?,
+                        parts[2].trim().parse().map_err(invalid_ip)?,
+                        parts[3].trim().parse().map_err(invalid_ip)?,
                     );
-                    let port: u16 = parts[4]
-                        .parse::<u16>()
-                        .map_err(|_| Error::InvalidParsedPort)?
-                        * 256
-                        + parts[5]
-                            .parse::<u16>()
-                            .map_err(|_| Error::InvalidParsedPort)?;
+                    let high: u8 = parts[4].trim().parse().map_err(invalid_port)?;
+                    let low: u8 = parts[5].trim().parse().map_err(invalid_port)?;
+                    let port: u16 = u16::from(high) * 256 + u16::from(low);
 
                     return Ok(SocketAddr::new(IpAddr::V4(ip), port));
                 }
             } else {
-                return Err(Error::InvalidParsedData);
+                return Err(invalid_data());
             }
         }
 
@@ -235,11 +385,15 @@ impl FtpClient {
             return Ok(addr);
         }
 
-        Err(Error::InvalidParsedData)
+        Err(invalid_data())
     }
 
     /// Lists the directory contents up to a specified depth using either BFS or DFS.
     ///
+    /// Every directory is listed by its absolute remote path, starting from the working
+    /// directory reported by the server after login. The first call logs in, later calls
+    /// reuse the session and list the same root again.
+    ///
     /// # Arguments
     ///
     /// * `depth` - The depth to which the directory contents should be listed.
@@ -249,205 +403,718 @@ impl FtpClient {
     ///
     /// A `Result` containing the root `NodeEnum` or an `Error`.
     pub fn list_dir(&mut self, depth: usize, bfs: bool) -> Result<NodeEnum> {
-        let username = self.username.clone();
-        let password = self.password.clone();
-
-        self.authenticate(&username, &password)?;
-        self.retrieve_server_info()?;
-        self.passive_mode()?;
-
-        self.ftp_stream.send_command(FtpCommand::List)?;
+        self.list_dir_with(depth, bfs, |_, _| Ok::<(), Error>(()))
+    }
 
-        let responses = self.ftp_data_stream.as_mut().unwrap().read_responses()?;
-        let mut root = Directory::new(String::from("."));
+    /// Lists the directory contents like `list_dir`, calling back for every entry as soon
+    /// as it is listed.
+    ///
+    /// Directories are passed to the callback before their own contents are listed, so
+    /// they hold no children yet. An error returned by the callback stops the crawl. The
+    /// callback may fail with its own error type, which the errors of the crawl are
+    /// converted into, such as the crate-level `error::Error` when writing output.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - The depth to which the directory contents should be listed.
+    /// * `bfs` - A boolean indicating whether to use BFS (true) or DFS (false).
+    /// * `on_entry` - The callback, receiving the entry and its depth, 1 for the entries of
+    ///   the root.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the root `NodeEnum`, or the error of the crawl or of the
+    /// callback.
+    pub fn list_dir_with<E, F>(
+        &mut self,
+        depth: usize,
+        bfs: bool,
+        mut on_entry: F,
+    ) -> std::result::Result<NodeEnum, E>
+    where
+        E: From<Error>,
+        F: FnMut(&NodeEnum, usize) -> std::result::Result<(), E>,
+    {
+        let root_path: String = match &self.root_path {
+            Some(root_path) => root_path.clone(),
+            None => {
+                let username: String = self.username.clone();
+                let password: String = self.password.clone();
+
+                self.authenticate(&username, &password)?;
+                self.retrieve_server_info()?;
+
+                let root_path: String = self.working_directory()?;
+
+                self.root_path = Some(root_path.clone());
+
+                root_path
+            }
+        };
+        let mut root: Directory = Directory::with_path(String::from("."), root_path);
 
         if bfs {
             debug!("BFS enabled");
 
-            self.process_responses_bfs(responses, &mut root, depth)?;
+            self.populate_dir_bfs(&mut root, depth, &mut on_entry)?;
         } else {
-            self.process_responses_dfs(responses, &mut root, depth)?;
+            self.populate_dir_dfs(&mut root, depth, 1, &mut on_entry)?;
         }
 
-        if self.ftp_stream.is_reconnected() {
-            self.ftp_stream.set_reconnected(false);
-            self.list_dir(depth, bfs)
-        } else {
-            Ok(NodeEnum::Directory(root))
+        Ok(NodeEnum::Directory(root))
+    }
+
+    /// Sends a NOOP command to keep the control connection alive.
+    ///
+    /// A lost connection is re-established and the session restored, so the next listing
+    /// starts on a working connection.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn noop(&mut self) -> Result<()> {
+        match self.ftp_stream.send_command(FtpCommand::Noop) {
+            Err(e) if e.is_connection_lost() => {
+                info!("Connection lost while idle, reconnecting");
+
+                self.reconnect()
+            }
+            result => result.map(|_| ()),
         }
     }
 
-    /// Processes the responses using DFS and populates the directory up to a specified depth.
+    /// Sends a NOOP command if the control connection has been unused for longer than the
+    /// keepalive interval of the options.
+    ///
+    /// Callers holding a client between two operations call it periodically, so servers
+    /// with short idle timeouts do not close the connection.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn keepalive(&mut self) -> Result<()> {
+        match self.options.keepalive {
+            Some(keepalive) if self.ftp_stream.idle_time() >= keepalive => self.noop(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Retrieves the current working directory of the session with the PWD command.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the absolute path of the working directory or an `Error`.
+    pub fn working_directory(&mut self) -> Result<String> {
+        let reply: Reply = self.ftp_stream.send_command(FtpCommand::Pwd)?;
+
+        if reply.code != 257 {
+            return Err(Error::InvalidParsedData {
+                context: "PWD reply",
+                data: reply.message(),
+            });
+        }
+
+        Self::parse_pwd_response(&reply.message())
+    }
+
+    /// Parses the quoted path out of the message of a `257` reply to the PWD command.
+    ///
+    /// Quotes inside the path are doubled by the server, as described in RFC 959.
     ///
     /// # Arguments
     ///
-    /// * `responses` - The responses from the server.
-    /// * `dir` - The directory to populate.
-    /// * `depth` - The depth to which the directory should be populated.
+    /// * `line` - The reply message sent by the server.
     ///
     /// # Returns
     ///
-    /// A `Result` indicating success or failure.
-    fn process_responses_dfs(
-        &mut self,
-        responses: Responses,
-        dir: &mut Directory,
-        depth: usize,
-    ) -> Result<()> {
-        for response in responses {
-            if self.ftp_stream.is_reconnected() {
-                return Ok(());
+    /// A `Result` containing the unquoted path or an `Error`.
+    fn parse_pwd_response(line: &str) -> Result<String> {
+        let invalid_data = || Error::InvalidParsedData {
+            context: "PWD reply",
+            data: line.to_string(),
+        };
+        let start: usize = line.find('"').ok_or_else(invalid_data)?;
+        let mut chars = line[start + 1..].chars().peekable();
+        let mut path: String = String::new();
+
+        while let Some(c) = chars.next() {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                } else {
+                    return Ok(path);
+                }
             }
 
-            let (code, line) = response;
-            let node_name = Self::parse_filename(&line);
+            path.push(c);
+        }
 
-            if line.chars().next() == Some('d') {
-                let mut subdir = Directory::new(node_name.clone());
+        Err(invalid_data())
+    }
 
-                if code < 500 && depth > 0 {
-                    self.populate_dir_dfs(node_name.clone(), &mut subdir, depth - 1)?;
+    /// Re-establishes the control connection and restores the session on it.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn reconnect(&mut self) -> Result<()> {
+        self.ftp_data_stream = None;
+        self.reconnect_control()?;
+        self.restore_session()
+    }
+
+    /// Opens a new control connection, following the reconnect policy of the options.
+    ///
+    /// An attempt only succeeds once the server has sent a positive welcome reply, so a
+    /// server answering `421` while it recovers is retried as well.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the welcome reply of the server once reconnected.
+    fn reconnect_control(&mut self) -> Result<Reply> {
+        let addr: SocketAddr = self.transport.addr();
+        let policy: ReconnectPolicy = self.options.reconnect.clone();
+        let mut last_error: Option<io::Error> = None;
+
+        for attempt in 0..policy.max_attempts {
+            let delay: Duration = policy.delay(attempt);
+
+            info!(
+                "Reconnecting to {} in {} (attempt {}/{})",
+                addr,
+                humantime::format_duration(delay),
+                attempt + 1,
+                policy.max_attempts
+            );
+
+            sleep(delay);
+
+            match self.transport.connect_control() {
+                Ok(control) => {
+                    self.ftp_stream.set_stream(control);
+
+                    match self.ftp_stream.read_reply() {
+                        Ok(reply) if !reply.is_failure() => {
+                            info!("Reconnected to the server at {}", addr);
+
+                            return Ok(reply);
+                        }
+                        Ok(reply) => {
+                            error!(
+                                "Server at {} is not ready: {} {}",
+                                addr,
+                                reply.code,
+                                reply.message()
+                            );
+                        }
+                        Err(e) => error!("Failed to read the welcome of {}: {}", addr, e),
+                    }
                 }
+                Err(e) => {
+                    error!("Failed to reconnect to the server at {}: {}", addr, e);
 
-                dir.add(subdir);
-            } else {
-                dir.add(File::new(node_name));
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        error!(
+            "Failed to reconnect to the server at {} after {} attempts",
+            addr, policy.max_attempts
+        );
+
+        Err(Error::ReconnectError {
+            addr,
+            source: last_error,
+        })
+    }
+
+    /// Restores the session after the control connection has been re-established.
+    ///
+    /// The user is logged in again and the working directory is verified with PWD, so the
+    /// absolute paths recorded before the reconnection still point at the same tree.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn restore_session(&mut self) -> Result<()> {
+        let username: String = self.username.clone();
+        let password: String = self.password.clone();
+
+        self.authenticate(&username, &password)?;
+        self.ftp_stream
+            .send_command(FtpCommand::Type("I".to_string()))?;
+
+        let expected: String = match &self.root_path {
+            Some(root_path) => root_path.clone(),
+            None => return Ok(()),
+        };
+        let current: String = self.working_directory()?;
+
+        if current != expected {
+            warn!(
+                "Working directory is {} after reconnecting, expected {}",
+                current, expected
+            );
+
+            self.ftp_stream
+                .send_command(FtpCommand::Cwd(expected.clone()))?;
+
+            let actual: String = self.working_directory()?;
+
+            if actual != expected {
+                return Err(Error::WorkingDirectoryMismatch { expected, actual });
             }
         }
 
+        info!("Session restored in {}", expected);
+
         Ok(())
     }
 
-    /// Processes the responses using BFS and populates the directory up to a specified depth.
+    /// Lists the directory at the given absolute path, reconnecting if the control
+    /// connection is lost during the listing.
+    ///
+    /// When hidden entries are requested, the first listing detects how the server lists
+    /// them: MLSD if advertised, then `LIST -a` if the listing holds the `.` and `..`
+    /// entries of `ls -a`, then a plain LIST. The mode found is used for every later
+    /// listing. Directories that cannot be entered tell nothing about the server, so the
+    /// detection waits for the first directory that can.
     ///
     /// # Arguments
     ///
-    /// * `responses` - The responses from the server.
-    /// * `dir` - The directory to populate.
-    /// * `depth` - The depth to which the directory should be populated.
+    /// * `path` - The absolute remote path of the directory.
     ///
     /// # Returns
     ///
-    /// A `Result` indicating success or failure.
-    fn process_responses_bfs(
+    /// A `Result` containing the listing lines, `None` if the directory cannot be entered,
+    /// or an `Error`.
+    fn list_path(&mut self, path: &str) -> Result<Option<Vec<String>>> {
+        if let Some(idle_timeout) = self.options.idle_timeout {
+            if self.ftp_stream.idle_time() > idle_timeout {
+                info!("Connection idle for too long, reconnecting");
+
+                self.reconnect()?;
+            }
+        }
+
+        if let Some(mode) = self.list_mode {
+            return Ok(self.list_path_with(path, mode)?.ok());
+        }
+
+        let mut mode: ListMode = if !self.options.hidden {
+            ListMode::Plain
+        } else if self.supports("MLSD") {
+            ListMode::Machine
+        } else {
+            ListMode::All
+        };
+
+        loop {
+            let result: std::result::Result<Vec<String>, Unlisted> =
+                self.list_path_with(path, mode)?;
+            let honoured: bool = match (&result, mode) {
+                (Err(Unlisted::Unreachable), _) => return Ok(None),
+                (_, ListMode::Plain) => true,
+                (Err(Unlisted::Refused), _) => false,
+                (Ok(_), ListMode::Machine) => true,
+                (Ok(listing), ListMode::All) => listing
+                    .iter()
+                    .any(|line| matches!(Self::parse_filename(line).as_str(), "." | "..")),
+            };
+
+            if honoured {
+                debug!("Listing directories with {}", mode.command());
+
+                self.list_mode = Some(mode);
+
+                return Ok(result.ok());
+            }
+
+            let fallback: ListMode = match mode {
+                ListMode::Machine => ListMode::All,
+                _ => ListMode::Plain,
+            };
+
+            warn!(
+                "The server does not honour {}, falling back to {}",
+                mode.command(),
+                fallback.command()
+            );
+
+            mode = fallback;
+        }
+    }
+
+    /// Lists the directory at the given absolute path in the given mode, reconnecting if
+    /// the control connection is lost during the listing.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The absolute remote path of the directory.
+    /// * `mode` - How to list the directory.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the listing lines, why the directory could not be listed, or
+    /// an `Error`.
+    fn list_path_with(
         &mut self,
-        responses: Responses,
-        dir: &mut Directory,
-        depth: usize,
-    ) -> Result<()> {
-        let root: Rc<RefCell<Directory>> = Rc::new(RefCell::new(std::mem::take(dir)));
-        let mut queue: Vec<(Rc<RefCell<Directory>>, Vec<(u16, String)>, usize)> =
-            vec![(root.clone(), responses, depth)];
-
-        while let Some((current_dir, current_responses, current_depth)) = queue.pop() {
-            for response in current_responses {
-                if self.ftp_stream.is_reconnected() {
-                    return Ok(());
+        path: &str,
+        mode: ListMode,
+    ) -> Result<std::result::Result<Vec<String>, Unlisted>> {
+        for _ in 0..MAX_LIST_ATTEMPTS {
+            match self.try_list_path(path, mode) {
+                Err(e) if e.is_connection_lost() => {
+                    info!("Connection lost while listing {}, reconnecting", path);
+
+                    self.reconnect()?;
                 }
+                Err(e) => {
+                    return Err(Error::ListError {
+                        path: path.to_string(),
+                        source: Box::new(e),
+                    })
+                }
+                result => return result,
+            }
+        }
 
-                let (code, line) = response;
-                let node_name: String = Self::parse_filename(&line);
+        Err(Error::ListError {
+            path: path.to_string(),
+            source: Box::new(Error::ConnectionLost {
+                reason: format!("gave up after {} attempts", MAX_LIST_ATTEMPTS),
+                source: None,
+            }),
+        })
+    }
 
-                if line.chars().next() == Some('d') {
-                    let subdir: Rc<RefCell<Directory>> =
-                        Rc::new(RefCell::new(Directory::new(node_name.clone())));
+    /// Makes a single attempt at listing the directory at the given absolute path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The absolute remote path of the directory.
+    /// * `mode` - How to list the directory.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the listing lines, why the directory could not be listed, or
+    /// an `Error`.
+    fn try_list_path(
+        &mut self,
+        path: &str,
+        mode: ListMode,
+    ) -> Result<std::result::Result<Vec<String>, Unlisted>> {
+        let reply: Reply = self
+            .ftp_stream
+            .send_command_unchecked(FtpCommand::Cwd(path.to_string()))?;
 
-                    if code < 500 && current_depth > 0 {
-                        let subdir_responses = self.populate_dir_bfs(
-                            node_name.clone(),
-                            &mut subdir.borrow_mut(),
-                            current_depth - 1,
-                        )?;
+        if reply.is_failure() {
+            warn!("Cannot enter {}: {} {}", path, reply.code, reply.message());
 
-                        queue.push((subdir.clone(), subdir_responses, current_depth - 1));
-                    }
+            return Ok(Err(Unlisted::Unreachable));
+        }
 
-                    current_dir
-                        .borrow_mut()
-                        .add(NodeEnum::Directory((*subdir.borrow()).clone()));
-                } else {
-                    current_dir.borrow_mut().add(File::new(node_name));
-                }
+        self.passive_mode()?;
+
+        let reply: Reply = self.ftp_stream.send_command_unchecked(mode.command())?;
+        let mut data_stream: DataStream<T::Data> = match self.ftp_data_stream.take() {
+            Some(data_stream) => data_stream,
+            None => unreachable!("passive mode always opens a data stream"),
+        };
+
+        if reply.class != ReplyClass::Preliminary {
+            warn!("Cannot list {}: {} {}", path, reply.code, reply.message());
+
+            return Ok(Err(Unlisted::Refused));
+        }
+
+        let keepalive: Option<Duration> = self.options.keepalive;
+        let ftp_stream: &mut CommandStream<T::Control> = &mut self.ftp_stream;
+        let mut pending_noops: usize = 0;
+        let listing: Vec<String> = data_stream.read_lines_with(|| {
+            if keepalive.is_some_and(|keepalive| ftp_stream.idle_time() >= keepalive) {
+                debug!("Sending a keepalive during the transfer");
+
+                ftp_stream.send_command_nowait(FtpCommand::Noop)?;
+                pending_noops += 1;
             }
+
+            Ok(())
+        })?;
+
+        drop(data_stream);
+
+        let reply: Reply = self.read_transfer_completion(pending_noops)?;
+
+        if reply.is_failure() {
+            warn!(
+                "Listing of {} did not complete: {} {}",
+                path,
+                reply.code,
+                reply.message()
+            );
         }
 
-        *dir = Rc::try_unwrap(root).unwrap().into_inner();
+        Ok(Ok(listing))
+    }
 
-        Ok(())
+    /// Reads the reply completing a data transfer, along with the replies to the NOOP
+    /// commands sent during the transfer.
+    ///
+    /// Servers answer the NOOP commands either before or after the completion reply, so
+    /// the completion is the first reply that is not a `200`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pending_noops` - The number of NOOP commands sent during the transfer.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the completion reply, or an `Error` when every reply read is a
+    /// `200`.
+    fn read_transfer_completion(&mut self, pending_noops: usize) -> Result<Reply> {
+        let mut completion: Option<Reply> = None;
+        let mut last: Option<Reply> = None;
+
+        for _ in 0..=pending_noops {
+            let reply: Reply = self.ftp_stream.read_reply()?;
+
+            if completion.is_none() && reply.code != 200 {
+                completion = Some(reply);
+            } else {
+                last = Some(reply);
+            }
+        }
+
+        completion.ok_or_else(|| Error::InvalidParsedData {
+            context: "transfer completion reply",
+            data: last.map(|reply| reply.message()).unwrap_or_default(),
+        })
     }
 
     /// Populates the directory using DFS up to a specified depth.
     ///
     /// # Arguments
     ///
-    /// * `dir_name` - The name of the directory to populate.
-    /// * `dir` - The directory to populate.
+    /// * `dir` - The directory to populate, holding its absolute remote path.
     /// * `depth` - The depth to which the directory should be populated.
+    /// * `level` - The depth of the entries of the directory, 1 for the root.
+    /// * `on_entry` - The callback receiving every entry as it is listed.
     ///
     /// # Returns
     ///
     /// A `Result` indicating success or failure.
-    fn populate_dir_dfs(
+    fn populate_dir_dfs<E: From<Error>>(
         &mut self,
-        dir_name: String,
         dir: &mut Directory,
         depth: usize,
-    ) -> Result<()> {
-        if depth == 0 || self.ftp_stream.is_reconnected() {
-            return Ok(());
-        }
-
-        self.ftp_stream.send_command(FtpCommand::Cwd(dir_name))?;
-        self.passive_mode()?;
-        self.ftp_stream.send_command(FtpCommand::List)?;
+        level: usize,
+        on_entry: &mut dyn FnMut(&NodeEnum, usize) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        let listing: Vec<String> = match self.list_path(&dir.path.clone())? {
+            Some(listing) => listing,
+            None => return Ok(()),
+        };
 
-        if self.ftp_stream.is_reconnected() {
-            return Ok(());
-        }
+        for mut node in self.filtered_listing(&dir.path, listing) {
+            on_entry(&node, level)?;
 
-        let responses: Responses = self.ftp_data_stream.as_mut().unwrap().read_responses()?;
-        self.process_responses_dfs(responses, dir, depth)?;
+            if let NodeEnum::Directory(subdir) = &mut node {
+                if depth > 1 {
+                    self.populate_dir_dfs(subdir, depth - 1, level + 1, on_entry)?;
+                }
+            }
 
-        self.ftp_stream.send_command(FtpCommand::Cdup)?;
+            dir.add(node);
+        }
 
         Ok(())
     }
 
     /// Populates the directory using BFS up to a specified depth.
     ///
+    /// Directories waiting to be listed are addressed by their position in the tree, so
+    /// the tree can be filled in place level by level.
+    ///
     /// # Arguments
     ///
-    /// * `dir_name` - The name of the directory to populate.
-    /// * `dir` - The directory to populate.
+    /// * `root` - The directory to populate, holding its absolute remote path.
     /// * `depth` - The depth to which the directory should be populated.
+    /// * `on_entry` - The callback receiving every entry as it is listed.
     ///
     /// # Returns
     ///
-    /// A `Result` containing the server's responses or an `Error`.
-    fn populate_dir_bfs(
+    /// A `Result` indicating success or failure.
+    fn populate_dir_bfs<E: From<Error>>(
         &mut self,
-        dir_name: String,
-        dir: &mut Directory,
+        root: &mut Directory,
         depth: usize,
-    ) -> Result<Responses> {
-        if depth == 0 || self.ftp_stream.is_reconnected() {
-            return Ok(vec![]);
+        on_entry: &mut dyn FnMut(&NodeEnum, usize) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        let mut queue: VecDeque<(Vec<usize>, usize)> = VecDeque::from([(Vec::new(), depth)]);
+
+        while let Some((position, current_depth)) = queue.pop_front() {
+            let dir: &mut Directory = Self::dir_at(root, &position);
+            let listing: Vec<String> = match self.list_path(&dir.path.clone())? {
+                Some(listing) => listing,
+                None => continue,
+            };
+
+            for node in self.filtered_listing(&dir.path, listing) {
+                let is_dir: bool = matches!(node, NodeEnum::Directory(_));
+
+                on_entry(&node, depth - current_depth + 1)?;
+
+                dir.add(node);
+
+                if is_dir && current_depth > 1 {
+                    let mut child_position: Vec<usize> = position.clone();
+
+                    child_position.push(dir.nodes.len() - 1);
+                    queue.push_back((child_position, current_depth - 1));
+                }
+            }
         }
 
-        self.ftp_stream.send_command(FtpCommand::Cwd(dir_name))?;
-        self.passive_mode()?;
-        self.ftp_stream.send_command(FtpCommand::List)?;
+        Ok(())
+    }
+
+    /// Returns the directory found by following the given child indices from the root.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The root directory.
+    /// * `position` - The indices of the nodes leading to the directory.
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the directory.
+    fn dir_at<'a>(root: &'a mut Directory, position: &[usize]) -> &'a mut Directory {
+        position
+            .iter()
+            .fold(root, |dir, &index| match &mut dir.nodes[index] {
+                NodeEnum::Directory(subdir) => subdir,
+                NodeEnum::File(_) => unreachable!("queued positions only point at directories"),
+            })
+    }
+
+    /// Parses the lines of a LIST reply and drops the nodes rejected by the filter of the
+    /// options, so excluded directories are never listed.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_path` - The absolute remote path of the listed directory.
+    /// * `listing` - The lines read from the data connection.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the kept nodes.
+    fn filtered_listing(&self, parent_path: &str, listing: Vec<String>) -> Vec<NodeEnum> {
+        let mut nodes: Vec<NodeEnum> = match self.list_mode {
+            Some(ListMode::Machine) => Self::parse_machine_listing(parent_path, listing),
+            _ => Self::parse_listing(parent_path, listing),
+        };
 
-        if self.ftp_stream.is_reconnected() {
-            return Ok(vec![]);
+        nodes.retain(|node| {
+            let kept: bool = self.options.filter.keeps(node);
+
+            if !kept {
+                debug!("Skipping {}", node.path());
+            }
+
+            kept
+        });
+
+        nodes
+    }
+
+    /// Parses the lines of a LIST reply into nodes located under the given parent path.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_path` - The absolute remote path of the listed directory.
+    /// * `listing` - The lines read from the data connection.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the parsed nodes.
+    fn parse_listing(parent_path: &str, listing: Vec<String>) -> Vec<NodeEnum> {
+        let mut nodes: Vec<NodeEnum> = Vec::new();
+
+        for line in listing {
+            let node_name: String = Self::parse_filename(&line);
+
+            if node_name.is_empty() || node_name == "." || node_name == ".." {
+                continue;
+            }
+
+            let node_path: String = path::join(parent_path, &node_name);
+            let metadata: Metadata = Metadata::from_list_line(&line);
+
+            if line.starts_with('d') {
+                let mut dir: Directory = Directory::with_path(node_name, node_path);
+
+                dir.metadata = metadata;
+                nodes.push(dir.into());
+            } else {
+                let mut file: File = File::with_path(node_name, node_path);
+
+                file.metadata = metadata;
+                nodes.push(file.into());
+            }
         }
 
-        let responses = self.ftp_data_stream.as_mut().unwrap().read_responses()?;
+        nodes
+    }
+
+    /// Parses the lines of an MLSD reply into nodes located under the given parent path.
+    ///
+    /// Every line holds facts separated by `;`, a space and the name of the entry. The
+    /// entries standing for the listed directory and its parent are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_path` - The absolute remote path of the listed directory.
+    /// * `listing` - The lines read from the data connection.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the parsed nodes.
+    fn parse_machine_listing(parent_path: &str, listing: Vec<String>) -> Vec<NodeEnum> {
+        let mut nodes: Vec<NodeEnum> = Vec::new();
+
+        for line in listing {
+            let Some((facts, name)) = line.split_once(' ') else {
+                continue;
+            };
+            let kind: String = facts
+                .split(';')
+                .filter_map(|fact| fact.split_once('='))
+                .find(|(key, _)| key.eq_ignore_ascii_case("type"))
+                .map_or(String::new(), |(_, value)| value.to_ascii_lowercase());
+
+            if name.is_empty() || name == "." || name == ".." || kind == "cdir" || kind == "pdir" {
+                continue;
+            }
 
-        self.process_responses_bfs(responses.clone(), dir, depth)?;
-        self.ftp_stream.send_command(FtpCommand::Cdup)?;
+            let node_path: String = path::join(parent_path, name);
+            let metadata: Metadata = Metadata::from_mlsd_facts(facts);
 
-        Ok(responses)
+            if kind == "dir" {
+                let mut dir: Directory = Directory::with_path(name.to_string(), node_path);
+
+                dir.metadata = metadata;
+                nodes.push(dir.into());
+            } else {
+                let mut file: File = File::with_path(name.to_string(), node_path);
+
+                file.metadata = metadata;
+                nodes.push(file.into());
+            }
+        }
+
+        nodes
     }
 
     /// Parses the filename from a response line.
@@ -469,3 +1136,15 @@ impl FtpClient {
         }
     }
 }
+
+/// Ends the session with a QUIT command when the client is dropped.
+///
+/// The reply is not awaited, so a server that went away never delays the drop.
+impl<T: Transport> Drop for FtpClient<T> {
+    fn drop(&mut self) {
+        match self.ftp_stream.send_command_nowait(FtpCommand::Quit) {
+            Ok(()) => debug!("Session closed"),
+            Err(e) => debug!("Cannot close the session: {}", e),
+        }
+    }
+}
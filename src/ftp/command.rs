@@ -25,12 +25,60 @@ pub enum FtpCommand {
     /// The TYPE command is used to specify the type of file to be transferred.
     Epsv,
 
-    /// The LIST command is used to list files in a directory.
-    List,
+    /// The LIST command is used to list files in a directory, with optional flags such as
+    /// `-a` or a path as argument.
+    List(Option<String>),
+
+    /// The MLSD command is used to list a directory in a machine-readable format.
+    Mlsd,
 
     /// The CWD command is used to change the working directory.
     Cwd(String),
 
     /// The CDUP command is used to change to the parent directory.
     Cdup,
+
+    /// The NOOP command does nothing but keeps the connection alive.
+    Noop,
+
+    /// The QUIT command ends the session.
+    Quit,
+}
+
+impl FtpCommand {
+    /// Returns the command line sent to the server, without the trailing CRLF.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the command and its argument.
+    pub fn to_line(&self) -> String {
+        match self {
+            FtpCommand::User(username) => format!("USER {}", username),
+            FtpCommand::Pass(password) => format!("PASS {}", password),
+            FtpCommand::Syst => "SYST".to_string(),
+            FtpCommand::Feat => "FEAT".to_string(),
+            FtpCommand::Pwd => "PWD".to_string(),
+            FtpCommand::Type(t) => format!("TYPE {}", t),
+            FtpCommand::Pasv => "PASV".to_string(),
+            FtpCommand::Epsv => "EPSV".to_string(),
+            FtpCommand::List(None) => "LIST".to_string(),
+            FtpCommand::List(Some(argument)) => format!("LIST {}", argument),
+            FtpCommand::Mlsd => "MLSD".to_string(),
+            FtpCommand::Cwd(path) => format!("CWD {}", path),
+            FtpCommand::Cdup => "CDUP".to_string(),
+            FtpCommand::Noop => "NOOP".to_string(),
+            FtpCommand::Quit => "QUIT".to_string(),
+        }
+    }
+}
+
+/// Displays the command as sent to the server, with the password redacted so the command
+/// can be logged or reported in errors.
+impl core::fmt::Display for FtpCommand {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
+        match self {
+            FtpCommand::Pass(_) => write!(fmt, "PASS ****"),
+            command => write!(fmt, "{}", command.to_line()),
+        }
+    }
 }
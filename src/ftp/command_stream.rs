@@ -1,40 +1,44 @@
 use std::{
-    io::Write,
-    net::{SocketAddr, TcpStream},
+    io::{BufReader, Read, Write},
+    time::{Duration, Instant},
 };
 
-use super::stream::{Responses, Stream};
+use super::reply::Reply;
+use super::stream::{is_connection_lost, is_timeout, Stream};
 use crate::ftp::error::Result;
 use crate::ftp::{command::FtpCommand, error::Error};
-use log::{debug, error, info};
+use log::{debug, error};
 
 /// Represents a command stream for FTP communication.
-pub struct CommandStream {
-    addr: SocketAddr,
-    stream: TcpStream,
-    reconnected: bool,
+pub struct CommandStream<C: Read + Write> {
+    reader: BufReader<C>,
+    last_activity: Instant,
 }
 
-impl CommandStream {
-    /// Creates a new `CommandStream` and connects to the given address.
+impl<C: Read + Write> CommandStream<C> {
+    /// Creates a new `CommandStream` over an open control connection.
     ///
     /// # Arguments
     ///
-    /// * `addr` - The socket address to connect to.
+    /// * `stream` - The control connection opened by the transport.
     ///
     /// # Returns
     ///
-    /// A `Result` containing the new `CommandStream` or an `Error`.
-    pub fn new(addr: SocketAddr) -> Result<Self> {
-        let stream: TcpStream = TcpStream::connect(addr).map_err(|_| Error::ConnectionError)?;
-
-        info!("Connected to the server");
+    /// A new `CommandStream` instance.
+    pub fn new(stream: C) -> Self {
+        CommandStream {
+            reader: BufReader::new(stream),
+            last_activity: Instant::now(),
+        }
+    }
 
-        Ok(CommandStream {
-            addr,
-            stream,
-            reconnected: false,
-        })
+    /// Returns how long the connection has been unused.
+    ///
+    /// # Returns
+    ///
+    /// The `Duration` elapsed since the last command was answered.
+    pub fn idle_time(&self) -> Duration {
+        self.last_activity.elapsed()
     }
 
     /// Formats an FTP command into a string.
@@ -46,23 +50,36 @@ impl CommandStream {
     /// # Returns
     ///
     /// A formatted string representing the FTP command.
-    fn format_command(cmd: FtpCommand) -> String {
-        match cmd {
-            FtpCommand::User(username) => format!("USER {}\r\n", username),
-            FtpCommand::Pass(password) => format!("PASS {}\r\n", password),
-            FtpCommand::Syst => "SYST\r\n".to_string(),
-            FtpCommand::Feat => "FEAT\r\n".to_string(),
-            FtpCommand::Pwd => "PWD\r\n".to_string(),
-            FtpCommand::Type(t) => format!("TYPE {}\r\n", t),
-            FtpCommand::Pasv => "PASV\r\n".to_string(),
-            FtpCommand::Epsv => "EPSV\r\n".to_string(),
-            FtpCommand::List => "LIST\r\n".to_string(),
-            FtpCommand::Cwd(path) => format!("CWD {}\r\n", path),
-            FtpCommand::Cdup => "CDUP\r\n".to_string(),
+    fn format_command(cmd: &FtpCommand) -> String {
+        format!("{}\r\n", cmd.to_line())
+    }
+
+    /// Sends an FTP command to the server, failing on negative replies.
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The FTP command to send.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the server's reply, or `Error::ReplyError` if the reply is a
+    /// transient or permanent failure.
+    pub fn send_command(&mut self, cmd: FtpCommand) -> Result<Reply> {
+        let command: String = cmd.to_string();
+        let reply: Reply = self.send_command_unchecked(cmd)?;
+
+        if reply.is_failure() {
+            return Err(Error::ReplyError { command, reply });
         }
+
+        Ok(reply)
     }
 
-    /// Sends an FTP command to the server.
+    /// Sends an FTP command to the server and returns its reply whatever its class, for
+    /// callers that handle negative replies themselves.
+    ///
+    /// Only the first reply is read. For commands opening a data transfer, such as LIST,
+    /// the caller reads the completion reply once the data connection has been drained.
     ///
     /// # Arguments
     ///
@@ -70,68 +87,102 @@ impl CommandStream {
     ///
     /// # Returns
     ///
-    /// A `Result` containing the server's responses or an `Error`.
-    pub fn send_command(&mut self, cmd: FtpCommand) -> Result<Responses> {
-        let command_str: String = CommandStream::format_command(cmd.clone());
+    /// A `Result` containing the server's reply, `Error::ConnectionLost` if the server
+    /// closed the connection or replied `421`, or another `Error`.
+    pub fn send_command_unchecked(&mut self, cmd: FtpCommand) -> Result<Reply> {
+        self.write_command(&cmd)?;
 
-        debug!("Sending command: {}", command_str.trim_end());
+        let reply: Reply = self.read_reply()?;
 
-        match self.stream.write(command_str.as_bytes()) {
-            Ok(_) => {
-                self.stream.flush().map_err(|_| Error::CommandFlushError)?;
+        self.last_activity = Instant::now();
 
-                debug!("Command flushed: {}", command_str.trim_end());
+        if reply.code == 421 {
+            error!("Service not available: {}", reply.message());
 
-                match cmd {
-                    FtpCommand::List => {
-                        let mut responses: Responses = self.read_responses()?;
+            return Err(Error::ConnectionLost {
+                reason: format!("{} {}", reply.code, reply.message()),
+                source: None,
+            });
+        }
+
+        Ok(reply)
+    }
 
-                        if !self.is_reconnected() {
-                            let mut additional_responses: Responses = self.read_responses()?;
+    /// Sends an FTP command to the server without waiting for its reply.
+    ///
+    /// Used for NOOP keepalives sent while a data transfer is in progress, whose replies
+    /// are read once the transfer completes, and for QUIT when the client is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The FTP command to send.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn send_command_nowait(&mut self, cmd: FtpCommand) -> Result<()> {
+        self.write_command(&cmd)?;
+        self.last_activity = Instant::now();
 
-                            responses.append(&mut additional_responses);
-                        }
+        Ok(())
+    }
 
-                        Ok(responses)
-                    }
-                    _ => Ok(self.read_responses()?),
-                }
-            }
-            Err(e) => {
-                if let Some(10053) | Some(libc::ECONNABORTED) = e.raw_os_error() {
-                    error!("Connection was aborted by the software in your host machine. Attempting to reconnect...");
+    /// Writes an FTP command to the server and flushes it.
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The FTP command to write.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success, `Error::ConnectionLost` if the server closed the
+    /// connection, or another `Error`.
+    fn write_command(&mut self, cmd: &FtpCommand) -> Result<()> {
+        let command_str: String = Self::format_command(cmd);
 
-                    self.reconnect()?;
+        debug!("Sending command: {}", cmd);
 
-                    return Ok(Vec::new());
-                } else {
-                    error!("Error writing command");
-                }
+        if let Err(e) = self.reader.get_mut().write_all(command_str.as_bytes()) {
+            if is_connection_lost(&e) || is_timeout(&e) {
+                error!("Connection was aborted by the software in your host machine");
 
-                return Err(Error::CommandWriteError);
+                return Err(Error::ConnectionLost {
+                    reason: format!("could not send {}", cmd),
+                    source: Some(e),
+                });
             }
+
+            error!("Error writing command");
+
+            return Err(Error::CommandWriteError {
+                command: cmd.to_string(),
+                source: e,
+            });
         }
-    }
-}
 
-impl Stream for CommandStream {
-    fn get_stream(&self) -> &TcpStream {
-        &self.stream
-    }
+        self.reader
+            .get_mut()
+            .flush()
+            .map_err(|e| Error::CommandFlushError {
+                command: cmd.to_string(),
+                source: e,
+            })?;
 
-    fn get_addr(&self) -> SocketAddr {
-        self.addr
-    }
+        debug!("Command flushed: {}", cmd);
 
-    fn set_stream(&mut self, stream: TcpStream) {
-        self.stream = stream;
+        Ok(())
     }
+}
+
+impl<C: Read + Write> Stream for CommandStream<C> {
+    type Inner = C;
 
-    fn set_reconnected(&mut self, reconnected: bool) {
-        self.reconnected = reconnected;
+    fn get_reader(&mut self) -> &mut BufReader<C> {
+        &mut self.reader
     }
 
-    fn is_reconnected(&mut self) -> bool {
-        self.reconnected
+    fn set_stream(&mut self, stream: C) {
+        self.reader = BufReader::new(stream);
+        self.last_activity = Instant::now();
     }
 }
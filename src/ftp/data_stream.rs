@@ -1,57 +1,90 @@
-use crate::ftp::error::{Error, Result};
-use crate::ftp::stream::Stream;
+use crate::ftp::error::Result;
+use crate::ftp::stream::{map_read_error, Stream};
 use log::debug;
-use std::net::{SocketAddr, TcpStream};
+use std::io::{BufRead, BufReader, Read};
 
 /// Represents a data stream for FTP communication.
-pub struct DataStream {
-    addr: SocketAddr,
-    stream: TcpStream,
-    reconnected: bool,
+pub struct DataStream<D: Read> {
+    reader: BufReader<D>,
 }
 
-impl DataStream {
-    /// Creates a new `DataStream`.
+impl<D: Read> DataStream<D> {
+    /// Creates a new `DataStream` over an open data connection.
     ///
     /// # Arguments
     ///
-    /// * `addr` - The address of the data stream.
-    /// * `stream` - The TCP stream used for communication.
+    /// * `stream` - The data connection opened by the transport.
     ///
     /// # Returns
     ///
     /// A new `DataStream` instance.
-    pub fn new(addr: SocketAddr) -> Result<Self> {
-        let stream: TcpStream = TcpStream::connect(addr).map_err(|_| Error::ConnectionError)?;
-
-        debug!("Connected to the data server");
-
-        Ok(DataStream {
-            addr,
-            stream,
-            reconnected: false,
-        })
+    pub fn new(stream: D) -> Self {
+        DataStream {
+            reader: BufReader::new(stream),
+        }
     }
-}
 
-impl Stream for DataStream {
-    fn get_stream(&self) -> &TcpStream {
-        &self.stream
+    /// Reads every line sent on the data connection until the server closes it.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the lines, without their line endings, or an `Error`. Bytes that
+    /// are not valid UTF-8 are replaced rather than failing the whole listing.
+    pub fn read_lines(&mut self) -> Result<Vec<String>> {
+        self.read_lines_with(|| Ok(()))
     }
 
-    fn get_addr(&self) -> SocketAddr {
-        self.addr
-    }
+    /// Reads every line sent on the data connection until the server closes it, calling
+    /// the given function after every line.
+    ///
+    /// # Arguments
+    ///
+    /// * `on_line` - The function called after every line, such as a keepalive sending
+    ///   NOOP commands on the control connection during long transfers.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the lines, without their line endings, or the first `Error`
+    /// returned by the connection or by `on_line`.
+    pub fn read_lines_with<F: FnMut() -> Result<()>>(
+        &mut self,
+        mut on_line: F,
+    ) -> Result<Vec<String>> {
+        let mut lines: Vec<String> = Vec::new();
+        let mut buffer: Vec<u8> = Vec::new();
+
+        loop {
+            buffer.clear();
+
+            if self
+                .reader
+                .read_until(b'\n', &mut buffer)
+                .map_err(|e| map_read_error(e, "data connection"))?
+                == 0
+            {
+                break;
+            }
 
-    fn set_stream(&mut self, stream: TcpStream) {
-        self.stream = stream;
+            let line: String = String::from_utf8_lossy(&buffer).trim_end().to_string();
+
+            debug!("Read data line: {}", line);
+
+            lines.push(line);
+            on_line()?;
+        }
+
+        Ok(lines)
     }
+}
+
+impl<D: Read> Stream for DataStream<D> {
+    type Inner = D;
 
-    fn set_reconnected(&mut self, reconnected: bool) {
-        self.reconnected = reconnected;
+    fn get_reader(&mut self) -> &mut BufReader<D> {
+        &mut self.reader
     }
 
-    fn is_reconnected(&mut self) -> bool {
-        self.reconnected
+    fn set_stream(&mut self, stream: D) {
+        self.reader = BufReader::new(stream);
     }
 }
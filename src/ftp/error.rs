@@ -1,48 +1,202 @@
-use derive_more::From;
+use std::{io, net::SocketAddr};
+
+use crate::error::ErrorClass;
+
+use super::reply::{Reply, ReplyClass};
 
 pub type Result<T> = core::result::Result<T, Error>;
 
 /// Represents various errors that can occur during FTP operations.
-#[derive(Debug, From)]
+#[derive(Debug)]
 pub enum Error {
-    /// Error occurred while reading data.
-    ReadError,
+    /// The address given on the command line is not a valid domain or IP address.
+    InvalidAddress { address: String },
 
-    /// Error occurred while attempting to reconnect.
-    ReconnectError,
+    /// Error occurred while resolving the domain.
+    DomainResolutionError {
+        domain: String,
+        source: Option<io::Error>,
+    },
 
     /// Error occurred while establishing a connection.
-    ConnectionError,
+    ConnectionError { addr: SocketAddr, source: io::Error },
 
-    /// Error occurred while resolving the domain.
-    DomainResolutionError,
+    /// Error occurred while attempting to reconnect.
+    ReconnectError {
+        addr: SocketAddr,
+        source: Option<io::Error>,
+    },
+
+    /// The control connection was closed, reset, or the server announced it is closing it.
+    ConnectionLost {
+        reason: String,
+        source: Option<io::Error>,
+    },
+
+    /// No data arrived on a connection before its read timeout expired.
+    TimedOut {
+        context: &'static str,
+        source: io::Error,
+    },
+
+    /// Error occurred while reading data.
+    ReadError {
+        context: &'static str,
+        source: io::Error,
+    },
 
     /// Error occurred while writing a command.
-    CommandWriteError,
+    CommandWriteError { command: String, source: io::Error },
 
     /// Error occurred while flushing a command.
-    CommandFlushError,
+    CommandFlushError { command: String, source: io::Error },
 
     /// Error occurred due to invalid parsed data.
-    InvalidParsedData,
+    InvalidParsedData { context: &'static str, data: String },
 
     /// Error occurred due to invalid parsed IP address.
-    InvalidParsedIp,
+    InvalidParsedIp { data: String },
 
     /// Error occurred due to invalid parsed port.
-    InvalidParsedPort,
-
-    /// No response was received.
-    NoResponseReceived,
+    InvalidParsedPort { data: String },
 
     /// Error indicating that the login attempt was unsuccessful.
-    BadLogin,
+    BadLogin { username: String, reply: Reply },
+
+    /// The server answered a command with a transient or permanent failure reply.
+    ReplyError { command: String, reply: Reply },
+
+    /// Error indicating that the working directory could not be restored after reconnecting.
+    WorkingDirectoryMismatch { expected: String, actual: String },
+
+    /// An error occurred while listing the directory at the given remote path.
+    ListError { path: String, source: Box<Error> },
+}
+
+impl Error {
+    /// Returns the class of the error.
+    ///
+    /// # Returns
+    ///
+    /// The `ErrorClass` the error belongs to.
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            Error::InvalidAddress { .. } => ErrorClass::Usage,
+            Error::DomainResolutionError { .. } => ErrorClass::Resolution,
+            Error::ConnectionError { .. }
+            | Error::ReconnectError { .. }
+            | Error::ConnectionLost { .. }
+            | Error::TimedOut { .. } => ErrorClass::Connection,
+            Error::ReadError { .. }
+            | Error::CommandWriteError { .. }
+            | Error::CommandFlushError { .. } => ErrorClass::Io,
+            Error::InvalidParsedData { .. }
+            | Error::InvalidParsedIp { .. }
+            | Error::InvalidParsedPort { .. }
+            | Error::WorkingDirectoryMismatch { .. } => ErrorClass::Protocol,
+            Error::BadLogin { .. } => ErrorClass::Authentication,
+            Error::ReplyError { reply, .. } => match reply.class {
+                ReplyClass::TransientFailure => ErrorClass::TransientReply,
+                _ => ErrorClass::Protocol,
+            },
+            Error::ListError { source, .. } => source.class(),
+        }
+    }
+
+    /// Returns the process exit code matching the class of the error.
+    ///
+    /// # Returns
+    ///
+    /// The exit code to terminate the program with.
+    pub fn exit_code(&self) -> u8 {
+        self.class().exit_code()
+    }
+
+    /// Returns whether the error means the control connection has to be re-established.
+    ///
+    /// # Returns
+    ///
+    /// `true` for `Error::ConnectionLost` and `Error::TimedOut`, since a server that stopped
+    /// answering is treated like one that went away.
+    pub fn is_connection_lost(&self) -> bool {
+        matches!(self, Error::ConnectionLost { .. } | Error::TimedOut { .. })
+    }
 }
 
 impl core::fmt::Display for Error {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
-        write!(fmt, "{self:?}")
+        match self {
+            Error::InvalidAddress { address } => {
+                write!(fmt, "'{}' is not a valid domain or IP address", address)
+            }
+            Error::DomainResolutionError { domain, .. } => {
+                write!(fmt, "could not resolve {}", domain)
+            }
+            Error::ConnectionError { addr, .. } => write!(fmt, "could not connect to {}", addr),
+            Error::ReconnectError { addr, .. } => {
+                write!(fmt, "could not reconnect to {}", addr)
+            }
+            Error::ConnectionLost { reason, .. } => write!(fmt, "connection lost: {}", reason),
+            Error::TimedOut { context, .. } => write!(fmt, "the {} timed out", context),
+            Error::ReadError { context, .. } => write!(fmt, "could not read from the {}", context),
+            Error::CommandWriteError { command, .. } => {
+                write!(fmt, "could not send command {}", command)
+            }
+            Error::CommandFlushError { command, .. } => {
+                write!(fmt, "could not flush command {}", command)
+            }
+            Error::InvalidParsedData { context, data } => {
+                write!(
+                    fmt,
+                    "invalid {} received from the server: {:?}",
+                    context, data
+                )
+            }
+            Error::InvalidParsedIp { data } => {
+                write!(fmt, "invalid IP address in passive mode reply: {:?}", data)
+            }
+            Error::InvalidParsedPort { data } => {
+                write!(fmt, "invalid port in passive mode reply: {:?}", data)
+            }
+            Error::BadLogin { username, reply } => write!(
+                fmt,
+                "login as {} refused: {} {}",
+                username,
+                reply.code,
+                reply.message()
+            ),
+            Error::ReplyError { command, reply } => write!(
+                fmt,
+                "{} failed: {} {}",
+                command,
+                reply.code,
+                reply.message()
+            ),
+            Error::WorkingDirectoryMismatch { expected, actual } => write!(
+                fmt,
+                "working directory is {} after reconnecting, expected {}",
+                actual, expected
+            ),
+            Error::ListError { path, .. } => write!(fmt, "could not list {}", path),
+        }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::DomainResolutionError { source, .. }
+            | Error::ReconnectError { source, .. }
+            | Error::ConnectionLost { source, .. } => source
+                .as_ref()
+                .map(|e| e as &(dyn std::error::Error + 'static)),
+            Error::ConnectionError { source, .. }
+            | Error::TimedOut { source, .. }
+            | Error::ReadError { source, .. }
+            | Error::CommandWriteError { source, .. }
+            | Error::CommandFlushError { source, .. } => Some(source),
+            Error::ListError { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
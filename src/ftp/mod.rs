@@ -1,6 +1,12 @@
 pub mod client;
 pub mod command;
+pub mod command_stream;
 pub mod data_stream;
 pub mod error;
-pub mod command_stream;
-mod stream;
\ No newline at end of file
+pub mod options;
+pub mod record;
+pub mod replay;
+pub mod reply;
+mod stream;
+pub mod transport;
+pub mod watch;
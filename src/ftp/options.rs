@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use crate::fs::filter::Filter;
+
+/// Describes how a lost control connection is re-established.
+///
+/// Attempts are spaced with an exponential backoff: the delay before attempt `n` is
+/// `initial_delay * multiplier^n`, capped at `max_delay`.
+#[derive(Clone, Debug)]
+pub struct ReconnectPolicy {
+    /// The maximum number of connection attempts before giving up.
+    pub max_attempts: usize,
+
+    /// The delay before the first attempt.
+    pub initial_delay: Duration,
+
+    /// The upper bound of the delay between two attempts.
+    pub max_delay: Duration,
+
+    /// The factor applied to the delay after every failed attempt.
+    pub multiplier: u32,
+}
+
+impl ReconnectPolicy {
+    /// Returns the delay to wait before the given attempt.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - The zero-based index of the attempt.
+    ///
+    /// # Returns
+    ///
+    /// The `Duration` to sleep before attempting to connect.
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let factor: u32 = self
+            .multiplier
+            .saturating_pow(attempt.try_into().unwrap_or(u32::MAX));
+
+        self.initial_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            max_attempts: 8,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2,
+        }
+    }
+}
+
+/// Options controlling how an `FtpClient` talks to the server.
+///
+/// A timeout set to `None` waits forever.
+#[derive(Clone, Debug)]
+pub struct FtpOptions {
+    /// Whether to use extended passive mode (EPSV) for data connections.
+    pub extended: bool,
+
+    /// Whether to list hidden entries, such as `.htaccess`, with MLSD when the server
+    /// advertises it or with `LIST -a` otherwise.
+    pub hidden: bool,
+
+    /// The maximum time to wait for a control or data connection to be established.
+    pub connect_timeout: Option<Duration>,
+
+    /// The maximum time to wait for a reply on the control connection.
+    pub read_timeout: Option<Duration>,
+
+    /// The maximum time to wait for data on a data connection.
+    pub data_timeout: Option<Duration>,
+
+    /// The time after which an unused control connection is considered stale and is
+    /// re-established before sending the next command.
+    pub idle_timeout: Option<Duration>,
+
+    /// The time the control connection may stay unused before a NOOP command is sent to
+    /// keep it alive, during long data transfers or when `FtpClient::keepalive` is called.
+    ///
+    /// During a transfer the interval is checked after every line received, so a transfer
+    /// stalling without sending a line sends no NOOP until `data_timeout` ends it.
+    pub keepalive: Option<Duration>,
+
+    /// How a lost control connection is re-established.
+    pub reconnect: ReconnectPolicy,
+
+    /// The entries kept while walking the tree. Excluded directories are not listed.
+    pub filter: Filter,
+}
+
+impl Default for FtpOptions {
+    fn default() -> Self {
+        FtpOptions {
+            extended: false,
+            hidden: false,
+            connect_timeout: Some(Duration::from_secs(30)),
+            read_timeout: Some(Duration::from_secs(60)),
+            data_timeout: Some(Duration::from_secs(60)),
+            idle_timeout: Some(Duration::from_secs(300)),
+            keepalive: Some(Duration::from_secs(60)),
+            reconnect: ReconnectPolicy::default(),
+            filter: Filter::default(),
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use super::transport::Transport;
+
+/// The connection an event happened on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    /// The control connection, carrying commands and replies.
+    Control,
+
+    /// A data connection, carrying listings and file contents.
+    Data,
+}
+
+/// What happened on a connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// The connection was opened, the payload holds the address of the peer.
+    Connect,
+
+    /// The client sent bytes, one event per line on the control connection.
+    Sent,
+
+    /// The client received bytes.
+    Received,
+
+    /// Opening, reading or writing failed, the payload holds the `io::ErrorKind`.
+    Error,
+}
+
+/// The bytes carried by an event, kept as text when they are valid UTF-8.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Payload {
+    /// A payload that is valid UTF-8.
+    Text(String),
+
+    /// A binary payload.
+    Bytes(Vec<u8>),
+}
+
+impl Payload {
+    /// Returns the bytes of the payload.
+    ///
+    /// # Returns
+    ///
+    /// A byte slice holding the payload.
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Payload::Text(text) => text.as_bytes(),
+            Payload::Bytes(bytes) => bytes,
+        }
+    }
+}
+
+impl From<&[u8]> for Payload {
+    fn from(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Payload::Text(text.to_string()),
+            Err(_) => Payload::Bytes(bytes.to_vec()),
+        }
+    }
+}
+
+/// A single entry of a session recording, written as one JSON line.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    /// The time of the event, in milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+
+    /// The number of the connection, counted from 0 in the order they were opened.
+    pub connection: usize,
+
+    /// The kind of connection.
+    pub channel: Channel,
+
+    /// What happened.
+    pub direction: Direction,
+
+    /// The bytes sent or received, the peer address or the error kind.
+    pub payload: Payload,
+}
+
+/// Replaces the password of a PASS command line, so recordings can be shared.
+///
+/// # Arguments
+///
+/// * `line` - A line sent on the control connection, with its line ending.
+///
+/// # Returns
+///
+/// The line, with the password replaced by `****` if it is a PASS command.
+pub(crate) fn redact(line: &[u8]) -> Vec<u8> {
+    if line.len() >= 5 && line[..5].eq_ignore_ascii_case(b"PASS ") {
+        b"PASS ****\r\n".to_vec()
+    } else {
+        line.to_vec()
+    }
+}
+
+/// Writes the events of a session as JSON Lines.
+struct Recorder {
+    output: Box<dyn Write + Send>,
+    connections: usize,
+}
+
+impl Recorder {
+    /// Returns the number of the next connection.
+    ///
+    /// # Returns
+    ///
+    /// The number identifying the connection in the recording.
+    fn next_connection(&mut self) -> usize {
+        self.connections += 1;
+        self.connections - 1
+    }
+
+    /// Appends an event to the recording.
+    ///
+    /// A recording that cannot be written does not fail the crawl, the error is logged.
+    ///
+    /// # Arguments
+    ///
+    /// * `connection` - The number of the connection.
+    /// * `channel` - The kind of connection.
+    /// * `direction` - What happened.
+    /// * `payload` - The bytes of the event.
+    fn record(
+        &mut self,
+        connection: usize,
+        channel: Channel,
+        direction: Direction,
+        payload: &[u8],
+    ) {
+        let event: Event = Event {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_millis() as u64),
+            connection,
+            channel,
+            direction,
+            payload: Payload::from(payload),
+        };
+        let line: String =
+            serde_json::to_string(&event).expect("events only hold serializable values");
+
+        if let Err(e) = writeln!(self.output, "{}", line) {
+            warn!("Could not write to the session recording: {}", e);
+        }
+    }
+}
+
+/// A `Transport` recording everything sent and received through another transport.
+///
+/// Every connection, command, reply and data payload is written with a timestamp as a
+/// JSON line, passwords being redacted. The recording can be served back with a
+/// `ReplayTransport`.
+pub struct RecordingTransport<T: Transport> {
+    inner: T,
+    recorder: Arc<Mutex<Recorder>>,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    /// Creates a new `RecordingTransport` writing the recording to the given output.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The transport opening the connections.
+    /// * `output` - Where the recording is written.
+    ///
+    /// # Returns
+    ///
+    /// A new `RecordingTransport` instance.
+    pub fn new(inner: T, output: impl Write + Send + 'static) -> Self {
+        RecordingTransport {
+            inner,
+            recorder: Arc::new(Mutex::new(Recorder {
+                output: Box::new(output),
+                connections: 0,
+            })),
+        }
+    }
+
+    /// Creates a new `RecordingTransport` writing the recording to the given file.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The transport opening the connections.
+    /// * `path` - The path of the recording, truncated if it exists.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the new `RecordingTransport`.
+    pub fn create(inner: T, path: &Path) -> io::Result<Self> {
+        Ok(Self::new(inner, File::create(path)?))
+    }
+
+    /// Records the outcome of opening a connection and wraps the opened stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The kind of connection.
+    /// * `addr` - The address the connection was opened to.
+    /// * `result` - The outcome of opening the connection.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the recorded stream.
+    fn wrap<S>(
+        &self,
+        channel: Channel,
+        addr: SocketAddr,
+        result: io::Result<S>,
+    ) -> io::Result<Recorded<S>> {
+        let mut recorder: MutexGuard<Recorder> =
+            self.recorder.lock().expect("recorder lock poisoned");
+        let connection: usize = recorder.next_connection();
+
+        match result {
+            Ok(inner) => {
+                recorder.record(
+                    connection,
+                    channel,
+                    Direction::Connect,
+                    addr.to_string().as_bytes(),
+                );
+
+                Ok(Recorded {
+                    inner,
+                    connection,
+                    channel,
+                    recorder: Arc::clone(&self.recorder),
+                    pending: Vec::new(),
+                })
+            }
+            Err(e) => {
+                recorder.record(
+                    connection,
+                    channel,
+                    Direction::Error,
+                    format!("{:?}", e.kind()).as_bytes(),
+                );
+
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    type Control = Recorded<T::Control>;
+    type Data = Recorded<T::Data>;
+
+    fn addr(&self) -> SocketAddr {
+        self.inner.addr()
+    }
+
+    fn connect_control(&mut self) -> io::Result<Self::Control> {
+        let result: io::Result<T::Control> = self.inner.connect_control();
+
+        self.wrap(Channel::Control, self.inner.addr(), result)
+    }
+
+    fn connect_data(&mut self, addr: SocketAddr) -> io::Result<Self::Data> {
+        let result: io::Result<T::Data> = self.inner.connect_data(addr);
+
+        self.wrap(Channel::Data, addr, result)
+    }
+}
+
+/// A stream whose traffic is written to a session recording.
+pub struct Recorded<S> {
+    inner: S,
+    connection: usize,
+    channel: Channel,
+    recorder: Arc<Mutex<Recorder>>,
+    pending: Vec<u8>,
+}
+
+impl<S> Recorded<S> {
+    /// Appends an event about this connection to the recording.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - What happened.
+    /// * `payload` - The bytes of the event.
+    fn record(&self, direction: Direction, payload: &[u8]) {
+        self.recorder
+            .lock()
+            .expect("recorder lock poisoned")
+            .record(self.connection, self.channel, direction, payload);
+    }
+}
+
+impl<S: Read> Read for Recorded<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner.read(buf) {
+            Ok(0) => Ok(0),
+            Ok(n) => {
+                self.record(Direction::Received, &buf[..n]);
+
+                Ok(n)
+            }
+            Err(e) => {
+                if e.kind() != io::ErrorKind::Interrupted {
+                    self.record(Direction::Error, format!("{:?}", e.kind()).as_bytes());
+                }
+
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<S: Write> Write for Recorded<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n: usize = match self.inner.write(buf) {
+            Ok(n) => n,
+            Err(e) => {
+                self.record(Direction::Error, format!("{:?}", e.kind()).as_bytes());
+
+                return Err(e);
+            }
+        };
+
+        // Lines are recorded whole, so a password split across writes is still redacted.
+        self.pending.extend_from_slice(&buf[..n]);
+
+        while let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+
+            self.record(Direction::Sent, &redact(&line));
+        }
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    io::{self, ErrorKind, Read, Write},
+    net::SocketAddr,
+    path::Path,
+};
+
+use super::{
+    record::{redact, Channel, Direction, Event},
+    transport::Transport,
+};
+
+/// A `Transport` serving a session recorded by a `RecordingTransport` back to a client.
+///
+/// Connections are handed out in the order they were recorded, each one returning the
+/// bytes and errors the client saw. The commands sent on the control connection are
+/// checked against the recording, so a client diverging from it fails instead of reading
+/// replies meant for other commands.
+pub struct ReplayTransport {
+    addr: SocketAddr,
+    controls: VecDeque<io::Result<ReplayStream>>,
+    data: VecDeque<io::Result<ReplayStream>>,
+}
+
+impl ReplayTransport {
+    /// Loads a recording from a JSON Lines file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the recording.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the new `ReplayTransport`.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let events: Vec<Event> = fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+            })
+            .collect::<io::Result<_>>()?;
+
+        Self::from_events(events)
+    }
+
+    /// Builds a replay from recorded events.
+    ///
+    /// # Arguments
+    ///
+    /// * `events` - The events of the recording, in the order they were written.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the new `ReplayTransport`, or an error if the recording
+    /// never opened a control connection.
+    pub fn from_events(events: Vec<Event>) -> io::Result<Self> {
+        let mut addr: Option<SocketAddr> = None;
+        let mut controls: VecDeque<io::Result<ReplayStream>> = VecDeque::new();
+        let mut data: VecDeque<io::Result<ReplayStream>> = VecDeque::new();
+        let mut opened: HashMap<usize, (Channel, usize)> = HashMap::new();
+
+        for event in events {
+            let payload: &[u8] = event.payload.as_bytes();
+
+            match (event.direction, opened.get(&event.connection)) {
+                (Direction::Connect, _) => {
+                    let connections: &mut VecDeque<io::Result<ReplayStream>> =
+                        Self::channel(&mut controls, &mut data, event.channel);
+
+                    if event.channel == Channel::Control && addr.is_none() {
+                        addr = String::from_utf8_lossy(payload).parse().ok();
+                    }
+
+                    opened.insert(event.connection, (event.channel, connections.len()));
+                    connections.push_back(Ok(ReplayStream::default()));
+                }
+                (Direction::Error, None) => {
+                    Self::channel(&mut controls, &mut data, event.channel)
+                        .push_back(Err(Self::error(payload)));
+                }
+                (direction, Some(&(channel, index))) => {
+                    if let Some(Ok(stream)) =
+                        Self::channel(&mut controls, &mut data, channel).get_mut(index)
+                    {
+                        match direction {
+                            Direction::Sent => stream.expected.push_back(payload.to_vec()),
+                            Direction::Received => {
+                                stream.items.push_back(Item::Bytes(payload.to_vec()))
+                            }
+                            _ => stream
+                                .items
+                                .push_back(Item::Error(Self::error(payload).kind())),
+                        }
+                    }
+                }
+                (_, None) => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "event on connection {} before it was opened",
+                            event.connection
+                        ),
+                    ))
+                }
+            }
+        }
+
+        let addr: SocketAddr = addr.ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                "the recording does not open a control connection",
+            )
+        })?;
+
+        Ok(ReplayTransport {
+            addr,
+            controls,
+            data,
+        })
+    }
+
+    /// Returns the recorded connections of a channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `controls` - The recorded control connections.
+    /// * `data` - The recorded data connections.
+    /// * `channel` - The channel to pick.
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the connections of the channel.
+    fn channel<'a>(
+        controls: &'a mut VecDeque<io::Result<ReplayStream>>,
+        data: &'a mut VecDeque<io::Result<ReplayStream>>,
+        channel: Channel,
+    ) -> &'a mut VecDeque<io::Result<ReplayStream>> {
+        match channel {
+            Channel::Control => controls,
+            Channel::Data => data,
+        }
+    }
+
+    /// Rebuilds a recorded IO error from the name of its kind.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload of the error event.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Error` of the recorded kind, `ErrorKind::Other` for unknown kinds.
+    fn error(payload: &[u8]) -> io::Error {
+        let name: String = String::from_utf8_lossy(payload).to_string();
+        let kind: ErrorKind = match name.as_str() {
+            "ConnectionRefused" => ErrorKind::ConnectionRefused,
+            "ConnectionReset" => ErrorKind::ConnectionReset,
+            "ConnectionAborted" => ErrorKind::ConnectionAborted,
+            "NotConnected" => ErrorKind::NotConnected,
+            "BrokenPipe" => ErrorKind::BrokenPipe,
+            "WouldBlock" => ErrorKind::WouldBlock,
+            "TimedOut" => ErrorKind::TimedOut,
+            "UnexpectedEof" => ErrorKind::UnexpectedEof,
+            _ => ErrorKind::Other,
+        };
+
+        io::Error::new(kind, format!("recorded {}", name))
+    }
+
+    /// Hands out the next recorded connection of a channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `connections` - The recorded connections of the channel.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the stream, or the recorded error.
+    fn next(connections: &mut VecDeque<io::Result<ReplayStream>>) -> io::Result<ReplayStream> {
+        connections.pop_front().unwrap_or_else(|| {
+            Err(io::Error::new(
+                ErrorKind::ConnectionRefused,
+                "no more connections in the recording",
+            ))
+        })
+    }
+}
+
+impl Transport for ReplayTransport {
+    type Control = ReplayStream;
+    type Data = ReplayStream;
+
+    fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    fn connect_control(&mut self) -> io::Result<ReplayStream> {
+        Self::next(&mut self.controls)
+    }
+
+    fn connect_data(&mut self, _addr: SocketAddr) -> io::Result<ReplayStream> {
+        Self::next(&mut self.data)
+    }
+}
+
+/// Something the client reads from a replayed connection.
+#[derive(Debug)]
+enum Item {
+    Bytes(Vec<u8>),
+    Error(ErrorKind),
+}
+
+/// A connection replaying the bytes and errors of a recording.
+#[derive(Debug, Default)]
+pub struct ReplayStream {
+    items: VecDeque<Item>,
+    expected: VecDeque<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+impl Read for ReplayStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.items.pop_front() {
+            Some(Item::Bytes(mut bytes)) => {
+                let n: usize = bytes.len().min(buf.len());
+
+                buf[..n].copy_from_slice(&bytes[..n]);
+
+                if n < bytes.len() {
+                    self.items.push_front(Item::Bytes(bytes.split_off(n)));
+                }
+
+                Ok(n)
+            }
+            Some(Item::Error(kind)) => Err(io::Error::new(kind, "recorded error")),
+            None => Ok(0),
+        }
+    }
+}
+
+impl Write for ReplayStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+
+        while let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = redact(&self.pending.drain(..=end).collect::<Vec<u8>>());
+            let expected: Option<Vec<u8>> = self.expected.pop_front();
+
+            if expected.as_deref() != Some(line.as_slice()) {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "replay diverged: sent {:?}, recorded {:?}",
+                        String::from_utf8_lossy(&line).trim_end(),
+                        expected.map(|e| String::from_utf8_lossy(&e).trim_end().to_string())
+                    ),
+                ));
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use std::io::BufRead;
+
+use log::{debug, error};
+
+use super::error::{Error, Result};
+use super::stream::map_read_error;
+
+/// The class of an FTP reply, given by the first digit of its code (RFC 959, section 4.2).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplyClass {
+    /// `1yz`: the action is being started, another reply follows.
+    Preliminary,
+
+    /// `2yz`: the action has been successfully completed.
+    Completion,
+
+    /// `3yz`: the command has been accepted, but more information is needed.
+    Intermediate,
+
+    /// `4yz`: the action was not taken, but it may succeed if retried later.
+    TransientFailure,
+
+    /// `5yz`: the action was not taken and should not be retried as is.
+    PermanentFailure,
+}
+
+impl ReplyClass {
+    /// Returns the class of the given reply code.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The three digits reply code.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the class, or `None` if the code is not a valid reply code.
+    pub fn from_code(code: u16) -> Option<Self> {
+        match code / 100 {
+            1 => Some(ReplyClass::Preliminary),
+            2 => Some(ReplyClass::Completion),
+            3 => Some(ReplyClass::Intermediate),
+            4 => Some(ReplyClass::TransientFailure),
+            5 => Some(ReplyClass::PermanentFailure),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a complete reply from the FTP server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reply {
+    /// The three digits reply code.
+    pub code: u16,
+
+    /// The class of the reply, derived from its code.
+    pub class: ReplyClass,
+
+    /// The text of every line of the reply, without the leading code.
+    pub lines: Vec<String>,
+}
+
+impl Reply {
+    /// Creates a new reply.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The three digits reply code.
+    /// * `lines` - The text of every line of the reply, without the leading code.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the new `Reply`, or `None` if the code is not a valid reply code.
+    pub fn new(code: u16, lines: Vec<String>) -> Option<Self> {
+        let class: ReplyClass = ReplyClass::from_code(code)?;
+
+        Some(Reply { code, class, lines })
+    }
+
+    /// Returns the text of the reply, with its lines joined by newlines.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the message of the reply.
+    pub fn message(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Returns whether the reply is a transient or permanent failure.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the reply code is `4yz` or `5yz`.
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self.class,
+            ReplyClass::TransientFailure | ReplyClass::PermanentFailure
+        )
+    }
+
+    /// Reads one complete reply from the given reader.
+    ///
+    /// A single line reply is `NNN text`. A multi-line reply opens with `NNN-text` and only
+    /// ends at the first line that starts with the same code followed by a space, as
+    /// described in RFC 959, section 4.2. Lines in between are kept verbatim, even when
+    /// they start with digits that look like another code, except that a repeated `NNN-`
+    /// prefix is stripped.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The buffered reader of the control connection.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the reply, `Error::ConnectionLost` if the connection is
+    /// closed before the reply is complete, or another `Error`.
+    pub fn read_from<R: BufRead>(reader: &mut R) -> Result<Reply> {
+        let first: String = Self::read_line(reader)?;
+        let code: u16 = Self::parse_code(&first).ok_or_else(|| Error::InvalidParsedData {
+            context: "reply",
+            data: first.clone(),
+        })?;
+        let mut lines: Vec<String> = Vec::new();
+
+        if first.as_bytes().get(3) == Some(&b'-') {
+            let terminator: String = format!("{} ", code);
+            let continuation: String = format!("{}-", code);
+
+            lines.push(first[4..].to_string());
+
+            loop {
+                let line: String = Self::read_line(reader)?;
+
+                if line.starts_with(&terminator) {
+                    lines.push(line[4..].to_string());
+                    break;
+                } else if line == code.to_string() {
+                    break;
+                } else if let Some(text) = line.strip_prefix(&continuation) {
+                    lines.push(text.to_string());
+                } else {
+                    lines.push(line);
+                }
+            }
+        } else {
+            lines.push(first.get(4..).unwrap_or_default().to_string());
+        }
+
+        let reply: Reply = Reply::new(code, lines).ok_or(Error::InvalidParsedData {
+            context: "reply code",
+            data: first,
+        })?;
+
+        debug!("Full reply: {:?}", reply);
+
+        Ok(reply)
+    }
+
+    /// Reads a single line from the reader, without its line ending.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The buffered reader of the control connection.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the line, or `Error::ConnectionLost` at the end of the stream.
+    fn read_line<R: BufRead>(reader: &mut R) -> Result<String> {
+        let mut buffer: Vec<u8> = Vec::new();
+
+        if reader
+            .read_until(b'\n', &mut buffer)
+            .map_err(|e| map_read_error(e, "control connection"))?
+            == 0
+        {
+            error!("Connection closed by the server");
+
+            return Err(Error::ConnectionLost {
+                reason: "connection closed by the server".to_string(),
+                source: None,
+            });
+        }
+
+        let line: String = String::from_utf8_lossy(&buffer)
+            .trim_end_matches(['\r', '\n'])
+            .to_string();
+
+        debug!("Read line: {}", line);
+
+        Ok(line)
+    }
+
+    /// Parses the three digits code at the start of a reply line.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The first line of a reply.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the code, or `None` if the line does not start with a code
+    /// followed by a space, a hyphen or the end of the line.
+    fn parse_code(line: &str) -> Option<u16> {
+        let bytes: &[u8] = line.as_bytes();
+
+        if bytes.len() < 3 || !bytes[..3].iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+
+        match bytes.get(3) {
+            None | Some(b' ') | Some(b'-') => line[..3].parse().ok(),
+            _ => None,
+        }
+    }
+}
@@ -1,135 +1,91 @@
 use crate::ftp::error::{Error, Result};
-use log::{debug, error, info};
-use std::io::{BufRead, BufReader};
-use std::{
-    net::{SocketAddr, TcpStream},
-    thread::sleep,
-    time::{Duration, Instant},
-};
+use crate::ftp::reply::Reply;
+use log::error;
+use std::io::{self, BufReader, ErrorKind, Read};
 
-/// Represents a response from the FTP server.
-pub type Response = (u16, String);
-
-/// Represents multiple responses from the FTP server.
-pub type Responses = Vec<Response>;
-
-/// A trait for managing TCP streams in FTP operations.
+/// A trait for managing buffered connections in FTP operations.
 pub trait Stream {
-    /// Returns a reference to the TCP stream.
-    fn get_stream(&self) -> &TcpStream;
+    /// The underlying connection.
+    type Inner: Read;
 
-    /// Returns the address of the data stream.
-    fn get_addr(&self) -> SocketAddr;
-
-    /// Sets the TCP stream.
-    ///
-    /// # Arguments
+    /// Returns the buffered reader wrapping the connection.
     ///
-    /// * `stream` - The new TCP stream.
-    fn set_stream(&mut self, stream: TcpStream);
+    /// The reader is kept for the whole life of the connection, so bytes buffered past the
+    /// end of one reply are not lost before the next one is read.
+    fn get_reader(&mut self) -> &mut BufReader<Self::Inner>;
 
-    /// Sets the reconnected status.
+    /// Sets the connection, for instance after reconnecting.
     ///
     /// # Arguments
     ///
-    /// * `reconnected` - The new reconnected status.
-    fn set_reconnected(&mut self, reconnected: bool);
-
-    /// Returns whether the stream has been reconnected.
-    fn is_reconnected(&mut self) -> bool;
+    /// * `stream` - The new connection.
+    fn set_stream(&mut self, stream: Self::Inner);
 
-    /// Attempts to reconnect the TCP stream.
+    /// Reads a reply from the connection.
     ///
     /// # Returns
     ///
-    /// A `Result` indicating whether the reconnection was successful.
-    fn reconnect(&mut self) -> Result<bool> {
-        let addr: SocketAddr = self.get_addr();
-        let start_time: Instant = Instant::now();
-        let timeout: Duration = Duration::from_secs(300); // 5 minutes
-        let retry_interval: Duration = Duration::from_secs(5); // Retry every 5 seconds
-
-        sleep(Duration::from_secs(5)); // For localhost docker ftp
-
-        while start_time.elapsed() < timeout {
-            match TcpStream::connect(addr) {
-                Ok(new_stream) => {
-                    self.set_stream(new_stream);
-
-                    info!("Reconnected to the server at {}", addr);
-
-                    self.set_reconnected(true);
-                    self.read_responses()?;
-
-                    return Ok(true);
-                }
-                Err(_) => {
-                    error!(
-                        "Failed to reconnect to the server at {}. Retrying in 5 seconds...",
-                        addr
-                    );
-
-                    sleep(retry_interval);
-                }
-            }
-        }
-
-        error!(
-            "Failed to reconnect to the server at {} after 5 minutes",
-            addr
-        );
-
-        Err(Error::ReconnectError)
+    /// A `Result` containing the reply of the FTP server, `Error::ConnectionLost` if the
+    /// server went away, or another `Error` if reading fails.
+    fn read_reply(&mut self) -> Result<Reply> {
+        Reply::read_from(self.get_reader())
     }
+}
 
-    /// Reads responses from the TCP stream.
-    ///
-    /// # Returns
-    ///
-    /// A `Result` containing a vector of responses from the FTP server, or an `Error` if reading fails.
-    fn read_responses(&mut self) -> Result<Vec<Response>> {
-        let stream: &TcpStream = self.get_stream();
-        let mut reader: BufReader<&TcpStream> = BufReader::new(stream);
-        let mut responses: Vec<Response> = Vec::new();
-
-        loop {
-            let mut line: String = String::new();
-            let bytes_read: usize = match reader.read_line(&mut line) {
-                Ok(bytes_read) => bytes_read,
-                Err(e) => {
-                    if let Some(10053) | Some(libc::ECONNABORTED) = e.raw_os_error() {
-                        error!("Connection was aborted by the software in your host machine. Attempting to reconnect...");
-
-                        self.reconnect()?;
-
-                        return Ok(Vec::new());
-                    }
-
-                    return Err(Error::ReadError);
-                }
-            };
-
-            if bytes_read == 0 {
-                break;
-            }
-
-            debug!("Read line: {}", line.trim_end());
-
-            let code = if line.len() >= 3 {
-                line[0..3].parse::<u16>().unwrap_or(0)
-            } else {
-                0
-            };
-
-            responses.push((code, line.trim_end().to_string()));
-
-            if line.len() >= 4 && &line[3..4] == " " {
-                break;
-            }
+/// Maps an IO error raised while reading from a stream to an `Error`.
+///
+/// # Arguments
+///
+/// * `e` - The IO error.
+/// * `context` - The name of the connection being read.
+///
+/// # Returns
+///
+/// `Error::TimedOut` if the read timeout expired, `Error::ConnectionLost` if the connection
+/// was aborted or reset, `Error::ReadError` otherwise.
+pub(crate) fn map_read_error(e: io::Error, context: &'static str) -> Error {
+    if is_timeout(&e) {
+        error!("Timed out while reading from the {}", context);
+
+        Error::TimedOut { context, source: e }
+    } else if is_connection_lost(&e) {
+        error!("Connection was aborted by the software in your host machine");
+
+        Error::ConnectionLost {
+            reason: format!("{} aborted", context),
+            source: Some(e),
         }
+    } else {
+        Error::ReadError { context, source: e }
+    }
+}
 
-        debug!("Full response: {:?}", responses);
+/// Returns whether an IO error means the connection was dropped.
+///
+/// # Arguments
+///
+/// * `e` - The IO error.
+///
+/// # Returns
+///
+/// `true` if the connection was aborted, reset or closed.
+pub(crate) fn is_connection_lost(e: &io::Error) -> bool {
+    matches!(e.raw_os_error(), Some(10053) | Some(libc::ECONNABORTED))
+        || matches!(
+            e.kind(),
+            ErrorKind::ConnectionAborted | ErrorKind::ConnectionReset | ErrorKind::BrokenPipe
+        )
+}
 
-        Ok(responses)
-    }
+/// Returns whether an IO error means a read or write timeout expired.
+///
+/// # Arguments
+///
+/// * `e` - The IO error.
+///
+/// # Returns
+///
+/// `true` if the operation timed out.
+pub(crate) fn is_timeout(e: &io::Error) -> bool {
+    matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
 }
@@ -0,0 +1,118 @@
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+
+use log::debug;
+
+use super::options::FtpOptions;
+
+/// Opens the connections an `FtpClient` talks over.
+///
+/// The client only relies on this trait to reach the server, so anything able to produce
+/// `Read + Write` streams can carry an FTP session: plain TCP, TLS, a proxy, an in-memory
+/// test double or a recorded session.
+pub trait Transport {
+    /// The stream of the control connection.
+    type Control: Read + Write;
+
+    /// The stream of a data connection.
+    type Data: Read;
+
+    /// Returns the address of the server the control connection is opened to.
+    ///
+    /// # Returns
+    ///
+    /// The `SocketAddr` of the server, also used to reach the data port announced by EPSV.
+    fn addr(&self) -> SocketAddr;
+
+    /// Opens a new control connection to the server.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the connected stream.
+    fn connect_control(&mut self) -> io::Result<Self::Control>;
+
+    /// Opens a data connection to the address announced by the server in passive mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the data port.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the connected stream.
+    fn connect_data(&mut self, addr: SocketAddr) -> io::Result<Self::Data>;
+}
+
+/// A `Transport` over plain TCP connections, applying the timeouts of the client options.
+#[derive(Clone, Debug)]
+pub struct TcpTransport {
+    addr: SocketAddr,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    data_timeout: Option<Duration>,
+}
+
+impl TcpTransport {
+    /// Creates a new `TcpTransport` to the given server.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The socket address of the FTP server.
+    /// * `options` - The options holding the connect, read and data timeouts.
+    ///
+    /// # Returns
+    ///
+    /// A new `TcpTransport` instance.
+    pub fn new(addr: SocketAddr, options: &FtpOptions) -> Self {
+        TcpTransport {
+            addr,
+            connect_timeout: options.connect_timeout,
+            read_timeout: options.read_timeout,
+            data_timeout: options.data_timeout,
+        }
+    }
+
+    /// Opens a TCP connection to the given address and applies the read and write timeouts.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The socket address to connect to.
+    /// * `io_timeout` - The maximum time a read or write may block, `None` to wait forever.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the connected `TcpStream`.
+    fn connect(&self, addr: SocketAddr, io_timeout: Option<Duration>) -> io::Result<TcpStream> {
+        let stream: TcpStream = match self.connect_timeout {
+            Some(timeout) => TcpStream::connect_timeout(&addr, timeout)?,
+            None => TcpStream::connect(addr)?,
+        };
+
+        stream.set_read_timeout(io_timeout)?;
+        stream.set_write_timeout(io_timeout)?;
+
+        debug!("Connected to {}", addr);
+
+        Ok(stream)
+    }
+}
+
+impl Transport for TcpTransport {
+    type Control = TcpStream;
+    type Data = TcpStream;
+
+    fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    fn connect_control(&mut self) -> io::Result<TcpStream> {
+        self.connect(self.addr, self.read_timeout)
+    }
+
+    fn connect_data(&mut self, addr: SocketAddr) -> io::Result<TcpStream> {
+        self.connect(addr, self.data_timeout)
+    }
+}
@@ -0,0 +1,372 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+    thread::sleep,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use log::{error, info, warn};
+use serde::Serialize;
+
+use crate::{
+    error::{Error, ErrorClass, Result},
+    fs::{
+        diff::{self, Change},
+        directory::Directory,
+        node::NodeEnum,
+        snapshot,
+    },
+};
+
+use super::{client::FtpClient, transport::Transport};
+
+/// Options controlling how a `Watcher` re-crawls the server and reports changes.
+#[derive(Clone, Debug)]
+pub struct WatchOptions {
+    /// The time between the start of two crawls.
+    pub interval: Duration,
+
+    /// The time between two NOOP commands keeping the connection alive between crawls,
+    /// zero to send none.
+    pub keepalive: Duration,
+
+    /// The depth of directory traversal.
+    pub depth: usize,
+
+    /// Whether to crawl breadth-first.
+    pub bfs: bool,
+
+    /// The file holding the last snapshot, so a restarted watch reports what changed
+    /// while it was stopped.
+    pub state: PathBuf,
+
+    /// The file every change is appended to as a JSON line, if any.
+    pub events: Option<PathBuf>,
+
+    /// The shell command run once per change, if any.
+    pub hook: Option<String>,
+
+    /// Whether to print the changes to the standard output.
+    pub print: bool,
+
+    /// The number of crawls after which the watch stops, `None` to watch forever.
+    pub max_cycles: Option<usize>,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        WatchOptions {
+            interval: Duration::from_secs(300),
+            keepalive: Duration::from_secs(60),
+            depth: 1,
+            bfs: false,
+            state: PathBuf::from("tree-ftp-state.json"),
+            events: None,
+            hook: None,
+            print: true,
+            max_cycles: None,
+        }
+    }
+}
+
+/// A change found by a watch, written as one JSON line to the event log.
+#[derive(Clone, Debug, Serialize)]
+pub struct WatchEvent<'a> {
+    /// The time the change was found, in milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+
+    /// The index of the crawl that found the change, starting at 1.
+    pub cycle: usize,
+
+    /// The change.
+    #[serde(flatten)]
+    pub change: &'a Change,
+}
+
+/// Re-crawls a server on an interval and reports what changed between two crawls.
+///
+/// The control connection is kept open between crawls with NOOP commands, and the last
+/// snapshot is written to the state file after every crawl.
+pub struct Watcher<T: Transport> {
+    client: FtpClient<T>,
+    options: WatchOptions,
+    previous: Option<Directory>,
+    events: Option<File>,
+    cycle: usize,
+}
+
+impl<T: Transport> Watcher<T> {
+    /// Creates a new `Watcher`, loading the last snapshot from the state file if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client connected to the watched server.
+    /// * `options` - The interval, state file and reporting options.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the new `Watcher` or an `Error` if the state file or the
+    /// event log cannot be opened.
+    pub fn new(client: FtpClient<T>, options: WatchOptions) -> Result<Self> {
+        let previous: Option<Directory> = match fs::read_to_string(&options.state) {
+            Ok(json) => {
+                info!("Loaded the last snapshot from {}", options.state.display());
+
+                Some(snapshot::from_json(&json)?)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(Error::InputError {
+                    path: options.state.display().to_string(),
+                    source: e,
+                })
+            }
+        };
+        let events: Option<File> = match &options.events {
+            Some(path) => Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| Error::OutputError {
+                        path: path.display().to_string(),
+                        source: e,
+                    })?,
+            ),
+            None => None,
+        };
+
+        Ok(Watcher {
+            client,
+            options,
+            previous,
+            events,
+            cycle: 0,
+        })
+    }
+
+    /// Crawls the server until the maximum number of cycles is reached, keeping the
+    /// connection alive in between.
+    ///
+    /// A crawl failing because the server went away is logged and retried at the next
+    /// cycle. Failures that retrying cannot fix, such as refused credentials, stop the
+    /// watch.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn run(&mut self) -> Result<()> {
+        loop {
+            let started: Instant = Instant::now();
+
+            match self.run_once() {
+                Ok(_) => {}
+                Err(e) if Self::is_fatal(e.class()) => return Err(e),
+                Err(e) => error!("Crawl {} failed: {}", self.cycle, e),
+            }
+
+            if self
+                .options
+                .max_cycles
+                .is_some_and(|max_cycles| self.cycle >= max_cycles)
+            {
+                return Ok(());
+            }
+
+            self.wait_until(started + self.options.interval)?;
+        }
+    }
+
+    /// Crawls the server once, reports the changes since the last crawl and saves the
+    /// new snapshot.
+    ///
+    /// Nothing is reported by the first crawl when there is no previous snapshot.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the changes since the last crawl.
+    pub fn run_once(&mut self) -> Result<Vec<Change>> {
+        self.cycle += 1;
+
+        info!("Starting crawl {}", self.cycle);
+
+        let root: Directory = match self.client.list_dir(self.options.depth, self.options.bfs)? {
+            NodeEnum::Directory(root) => root,
+            NodeEnum::File(_) => unreachable!("a crawl always starts from a directory"),
+        };
+        let changes: Vec<Change> = match &self.previous {
+            Some(previous) => diff::diff(previous, &root),
+            None => {
+                info!("No previous snapshot, recording the first one");
+
+                Vec::new()
+            }
+        };
+
+        info!("Crawl {} found {} change(s)", self.cycle, changes.len());
+
+        self.report(&changes)?;
+        self.save(&root)?;
+        self.previous = Some(root);
+
+        Ok(changes)
+    }
+
+    /// Sends NOOP commands until the given instant.
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - The instant the next crawl starts.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn wait_until(&mut self, deadline: Instant) -> Result<()> {
+        loop {
+            let remaining: Duration = deadline.saturating_duration_since(Instant::now());
+
+            if remaining.is_zero() {
+                return Ok(());
+            }
+
+            if self.options.keepalive.is_zero() {
+                sleep(remaining);
+
+                return Ok(());
+            }
+
+            sleep(remaining.min(self.options.keepalive));
+
+            if Instant::now() < deadline {
+                match self.client.noop() {
+                    Ok(()) => {}
+                    Err(e) if Self::is_fatal(e.class()) => return Err(e.into()),
+                    Err(e) => warn!("Keepalive failed: {}", e),
+                }
+            }
+        }
+    }
+
+    /// Returns whether an error cannot be fixed by crawling again later.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - The class of the error.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the watch should stop.
+    fn is_fatal(class: ErrorClass) -> bool {
+        matches!(
+            class,
+            ErrorClass::Usage
+                | ErrorClass::Authentication
+                | ErrorClass::Output
+                | ErrorClass::Data
+                | ErrorClass::Input
+        )
+    }
+
+    /// Reports the changes to the standard output, the event log and the hook.
+    ///
+    /// # Arguments
+    ///
+    /// * `changes` - The changes found by the last crawl.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn report(&mut self, changes: &[Change]) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        if self.options.print {
+            println!("{}", diff::to_text(changes, false));
+        }
+
+        let timestamp_ms: u64 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+
+        for change in changes {
+            let event: WatchEvent = WatchEvent {
+                timestamp_ms,
+                cycle: self.cycle,
+                change,
+            };
+            let line: String =
+                serde_json::to_string(&event).expect("events only hold serializable values");
+
+            if let (Some(file), Some(path)) = (&mut self.events, &self.options.events) {
+                writeln!(file, "{}", line).map_err(|e| Error::OutputError {
+                    path: path.display().to_string(),
+                    source: e,
+                })?;
+            }
+
+            if let Some(hook) = &self.options.hook {
+                Self::run_hook(hook, change, &line);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the hook for a change, logging its failures.
+    ///
+    /// The hook is run by `sh -c` with the change described by the environment variables
+    /// `TREE_FTP_CHANGE` (its kind), `TREE_FTP_PATH` and `TREE_FTP_EVENT` (its JSON line).
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The shell command.
+    /// * `change` - The change.
+    /// * `line` - The change as a JSON line.
+    fn run_hook(hook: &str, change: &Change, line: &str) {
+        let kind: String = serde_json::to_value(change.kind)
+            .ok()
+            .and_then(|kind| kind.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let status: io::Result<ExitStatus> = Command::new("sh")
+            .arg("-c")
+            .arg(hook)
+            .env("TREE_FTP_CHANGE", kind)
+            .env("TREE_FTP_PATH", &change.path)
+            .env("TREE_FTP_EVENT", line)
+            .status();
+
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!("Hook failed for {}: {}", change.path, status),
+            Err(e) => warn!("Cannot run the hook for {}: {}", change.path, e),
+        }
+    }
+
+    /// Writes the snapshot to the state file.
+    ///
+    /// The snapshot is written next to the state file first and then renamed, so an
+    /// interrupted watch never leaves a truncated state behind.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The root of the last crawl.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn save(&self, root: &Directory) -> Result<()> {
+        let state: &Path = &self.options.state;
+        let temporary: PathBuf = state.with_extension("tmp");
+        let output_error = |e| Error::OutputError {
+            path: state.display().to_string(),
+            source: e,
+        };
+        let json: String = snapshot::to_json(root);
+
+        fs::write(&temporary, json).map_err(output_error)?;
+        fs::rename(&temporary, state).map_err(output_error)
+    }
+}
@@ -1,3 +1,5 @@
+pub mod error;
 pub mod fs;
 pub mod ftp;
-pub mod utils;
\ No newline at end of file
+pub mod server;
+pub mod utils;
@@ -1,21 +1,41 @@
 extern crate dotenv;
-extern crate libc;
-
-mod fs;
-mod ftp;
-mod utils;
 
 use clap::Parser;
 use dotenv::dotenv;
-use fs::node::{NodeEnum, TraversalType};
-use ftp::client::FtpClient;
 use log::info;
-use serde_json::to_string;
-use std::{env, fs::File, io::Write, net::SocketAddr};
-use utils::{domain::resolve_domain_to_socket_addr, parser::Args, validator::DomainAllowPort};
+use std::error::Error as _;
+#[cfg(feature = "sqlite")]
+use std::time::SystemTime;
+use std::{
+    env, fs, fs::File, io, io::BufWriter, io::Write, net::SocketAddr, path::Path,
+    process::ExitCode, time::Duration,
+};
+use tree_ftp::error::{Error, Result};
+use tree_ftp::fs::diff::{self, Change};
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::find::{self, Match};
+use tree_ftp::fs::format::OutputFormat;
+use tree_ftp::fs::jsonl::JsonLinesWriter;
+use tree_ftp::fs::node::NodeEnum;
+use tree_ftp::fs::snapshot;
+#[cfg(feature = "sqlite")]
+use tree_ftp::fs::sqlite::SqliteIndex;
+use tree_ftp::fs::usage::{self, DuEntry, Usage};
+use tree_ftp::ftp::client::FtpClient;
+use tree_ftp::ftp::error::Error as FtpError;
+use tree_ftp::ftp::options::FtpOptions;
+use tree_ftp::ftp::record::RecordingTransport;
+use tree_ftp::ftp::transport::{TcpTransport, Transport};
+use tree_ftp::ftp::watch::Watcher;
+use tree_ftp::server::ftp_server::FtpServer;
+use tree_ftp::utils::{
+    domain::resolve_domain_to_socket_addr,
+    parser::{Args, Command, DiffArgs, DuArgs, FindArgs, ServeArgs, SourceArgs},
+    validator::DomainAllowPort,
+};
 use validators::traits::ValidateString;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> ExitCode {
     dotenv().ok();
 
     let debug_level: String = env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
@@ -25,26 +45,490 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     info!("Debug level: {}", debug_level);
 
-    let args: Args = Args::parse();
-    let domain: DomainAllowPort = DomainAllowPort::parse_str(&args.address).unwrap();
-    let socket_addr: SocketAddr = resolve_domain_to_socket_addr(&domain)?;
-    let mut client: FtpClient = FtpClient::new(socket_addr, &args.username, &args.password, args.extended)?;
-    let root: NodeEnum = client.list_dir(args.depth, args.bfs)?;
+    let mut args: Args = Args::parse();
+    let result: Result<ExitCode> = match args.command.take() {
+        Some(Command::Serve(serve_args)) => serve(&serve_args),
+        Some(Command::Diff(diff_args)) => compare(&diff_args),
+        Some(Command::Du(du_args)) => disk_usage(&du_args),
+        Some(Command::Find(find_args)) => search(&find_args),
+        None => run(args),
+    };
 
-    if args.json {
-        let json = to_string(&root).unwrap();
-        let mut file = File::create("output.json").unwrap();
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("tree-ftp: {}", e);
+
+            let mut source: Option<&dyn std::error::Error> = e.source();
+
+            while let Some(cause) = source {
+                eprintln!("  caused by: {}", cause);
+
+                source = cause.source();
+            }
+
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+/// Crawls the FTP server described by the command-line arguments and prints the result.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command-line arguments.
+///
+/// # Returns
+///
+/// A `Result` containing the exit code, 1 when `--diff` found differences.
+fn run(args: Args) -> Result<ExitCode> {
+    let address: &str = args
+        .address
+        .as_deref()
+        .expect("the address is required without a subcommand");
+    let socket_addr: SocketAddr = resolve_address(address)?;
+    let options: FtpOptions = args.ftp_options();
+    let transport: TcpTransport = TcpTransport::new(socket_addr, &options);
+
+    if let Some(path) = args.output_path() {
+        if !args.force && path.exists() {
+            return Err(Error::OutputExists {
+                path: path.display().to_string(),
+            });
+        }
+    }
+
+    if let Some(interval) = args.watch {
+        return match &args.record {
+            Some(path) => watch(record(transport, path)?, &args, options, interval),
+            None => watch(transport, &args, options, interval),
+        };
+    }
+
+    if args.output_format() == OutputFormat::Jsonl {
+        return match &args.record {
+            Some(path) => stream(record(transport, path)?, &args, options),
+            None => stream(transport, &args, options),
+        };
+    }
+
+    #[cfg(feature = "sqlite")]
+    let started_at: SystemTime = SystemTime::now();
+    let root: NodeEnum = match &args.record {
+        Some(path) => crawl(record(transport, path)?, &args, options)?,
+        None => crawl(transport, &args, options)?,
+    };
+
+    if let Some(path) = &args.diff {
+        let old: Directory = load_snapshot(path)?;
+        let new: Directory = match root {
+            NodeEnum::Directory(dir) => dir,
+            NodeEnum::File(_) => unreachable!("a crawl always starts from a directory"),
+        };
+        let changes: Vec<Change> = diff::diff(&old, &new);
+
+        println!(
+            "{}",
+            diff::render(
+                &changes,
+                args.diff_format,
+                args.color.enabled(),
+                &path.display().to_string(),
+                args.address.as_deref().unwrap_or_default(),
+            )
+        );
 
-        file.write_all(json.as_bytes()).unwrap();
+        return Ok(diff_exit_code(&changes));
+    }
+
+    let NodeEnum::Directory(dir) = &root else {
+        unreachable!("a crawl always starts from a directory");
+    };
+
+    #[cfg(feature = "sqlite")]
+    if let Some(path) = &args.sqlite {
+        let mut index: SqliteIndex = SqliteIndex::open(path)?;
+        let crawl_id: i64 = index.insert_crawl(address, started_at, dir)?;
+
+        println!("Crawl {} appended to {}.", crawl_id, path.display());
+
+        return Ok(ExitCode::SUCCESS);
+    }
 
-        println!("JSON file created successfully.");
+    let output: String = if args.flat {
+        dir.to_string_flat(args.traversal_type(), args.long)
     } else {
-        if args.bfs {
-            println!("{}", root.to_string("", TraversalType::BFS));
+        args.formatter().format(dir)
+    };
+
+    match args.output_path() {
+        Some(path) => {
+            write_file(&path, &output, args.force)?;
+
+            println!("Output written to {}.", path.display());
+        }
+        None => print!("{}", output),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Serves a JSON snapshot over a read-only FTP server until the program is stopped.
+///
+/// # Arguments
+///
+/// * `args` - The parsed arguments of the `serve` subcommand.
+///
+/// # Returns
+///
+/// A `Result` indicating success or failure.
+fn serve(args: &ServeArgs) -> Result<ExitCode> {
+    let root: Directory = load_snapshot(&args.snapshot)?;
+    let server: FtpServer =
+        FtpServer::bind(args.listen, root, args.server_options()).map_err(|e| {
+            Error::ServeError {
+                addr: args.listen,
+                source: e,
+            }
+        })?;
+
+    println!(
+        "Serving {} on ftp://{}, press Ctrl+C to stop.",
+        args.snapshot.display(),
+        server.addr()
+    );
+
+    server.wait();
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Compares two JSON snapshots and prints the differences.
+///
+/// # Arguments
+///
+/// * `args` - The parsed arguments of the `diff` subcommand.
+///
+/// # Returns
+///
+/// A `Result` containing the exit code, 1 when the snapshots differ.
+fn compare(args: &DiffArgs) -> Result<ExitCode> {
+    let old: Directory = load_snapshot(&args.old)?;
+    let new: Directory = load_snapshot(&args.new)?;
+    let changes: Vec<Change> = diff::diff(&old, &new);
+
+    println!(
+        "{}",
+        diff::render(
+            &changes,
+            args.format,
+            args.color.enabled(),
+            &args.old.display().to_string(),
+            &args.new.display().to_string(),
+        )
+    );
+
+    Ok(diff_exit_code(&changes))
+}
+
+/// Prints the largest directories of a server or of a JSON snapshot.
+///
+/// # Arguments
+///
+/// * `args` - The parsed arguments of the `du` subcommand.
+///
+/// # Returns
+///
+/// A `Result` containing the exit code.
+fn disk_usage(args: &DuArgs) -> Result<ExitCode> {
+    let root: Directory = load_source(&args.source)?;
+    let (mut entries, total): (Vec<DuEntry>, Usage) = usage::du(&root, args.max_depth);
+
+    if let Some(top) = args.top {
+        entries.truncate(top);
+    }
+
+    println!("{}", usage::to_text(&entries, &total, !args.bytes));
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Prints the entries of a server or of a JSON snapshot passing the given tests, running
+/// the command given with `--exec` for each of them.
+///
+/// # Arguments
+///
+/// * `args` - The parsed arguments of the `find` subcommand.
+///
+/// # Returns
+///
+/// A `Result` containing the exit code, 1 when a command failed.
+fn search(args: &FindArgs) -> Result<ExitCode> {
+    let root: Directory = load_source(&args.source)?;
+    let found: Vec<Match> = find::find(&root, &args.query());
+
+    if let Some(command) = &args.exec {
+        let failures: usize = found
+            .iter()
+            .filter(|found| !find::exec(command, found))
+            .count();
+
+        return Ok(if failures == 0 {
+            ExitCode::SUCCESS
         } else {
-            println!("{}", root.to_string("", TraversalType::DFS));
+            ExitCode::from(1)
+        });
+    }
+
+    if args.json {
+        println!("{}", find::to_json(&found));
+    } else {
+        let separator: char = if args.print0 { '\0' } else { '\n' };
+        let mut stdout: io::StdoutLock = io::stdout().lock();
+
+        for found in &found {
+            write!(stdout, "{}{}", found.path, separator).map_err(|e| Error::OutputError {
+                path: "standard output".to_string(),
+                source: e,
+            })?;
         }
     }
 
-    Ok(())
-}
\ No newline at end of file
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Returns the exit code reporting whether a diff found differences.
+///
+/// # Arguments
+///
+/// * `changes` - The differences found.
+///
+/// # Returns
+///
+/// `ExitCode::SUCCESS` when there are none, 1 otherwise.
+fn diff_exit_code(changes: &[Change]) -> ExitCode {
+    if changes.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    }
+}
+
+/// Creates an output file of the program.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+/// * `force` - Whether to overwrite the file when it already exists.
+///
+/// # Returns
+///
+/// A `Result` containing the file, or `Error::OutputExists` when it exists and `force` is
+/// not set.
+fn create_file(path: &Path, force: bool) -> Result<File> {
+    File::options()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .create_new(!force)
+        .open(path)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => Error::OutputExists {
+                path: path.display().to_string(),
+            },
+            _ => Error::OutputError {
+                path: path.display().to_string(),
+                source: e,
+            },
+        })
+}
+
+/// Writes the output of the program to a file.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+/// * `content` - The content to write.
+/// * `force` - Whether to overwrite the file when it already exists.
+///
+/// # Returns
+///
+/// A `Result` indicating success or failure.
+fn write_file(path: &Path, content: &str, force: bool) -> Result<()> {
+    create_file(path, force)?
+        .write_all(content.as_bytes())
+        .map_err(|e| Error::OutputError {
+            path: path.display().to_string(),
+            source: e,
+        })
+}
+
+/// Reads and loads a JSON snapshot.
+///
+/// # Arguments
+///
+/// * `path` - The path of the snapshot.
+///
+/// # Returns
+///
+/// A `Result` containing the root directory of the snapshot.
+fn load_snapshot(path: &Path) -> Result<Directory> {
+    let json: String = fs::read_to_string(path).map_err(|e| Error::InputError {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+
+    snapshot::from_json(&json)
+}
+
+/// Resolves the address of an FTP server given on the command line.
+///
+/// # Arguments
+///
+/// * `address` - The domain name or IP address, with an optional port.
+///
+/// # Returns
+///
+/// A `Result` containing the `SocketAddr` of the server.
+fn resolve_address(address: &str) -> Result<SocketAddr> {
+    let domain: DomainAllowPort =
+        DomainAllowPort::parse_str(address).map_err(|_| FtpError::InvalidAddress {
+            address: address.to_string(),
+        })?;
+
+    Ok(resolve_domain_to_socket_addr(&domain)?)
+}
+
+/// Reads the tree a subcommand works on, crawling the server or loading the snapshot.
+///
+/// # Arguments
+///
+/// * `source` - The parsed arguments selecting the server or the snapshot.
+///
+/// # Returns
+///
+/// A `Result` containing the root directory of the tree.
+fn load_source(source: &SourceArgs) -> Result<Directory> {
+    if let Some(path) = &source.snapshot {
+        return load_snapshot(path);
+    }
+
+    let address: &str = source
+        .address
+        .as_deref()
+        .expect("the address is required without --snapshot");
+    let mut client: FtpClient = FtpClient::with_options(
+        resolve_address(address)?,
+        &source.username,
+        &source.password,
+        source.ftp_options(),
+    )?;
+
+    match client.list_dir(source.depth, false)? {
+        NodeEnum::Directory(dir) => Ok(dir),
+        NodeEnum::File(_) => unreachable!("a crawl always starts from a directory"),
+    }
+}
+
+/// Connects through the given transport and crawls the server.
+///
+/// # Arguments
+///
+/// * `transport` - The transport opening the connections.
+/// * `args` - The parsed command-line arguments.
+/// * `options` - The options of the FTP client.
+///
+/// # Returns
+///
+/// A `Result` containing the root of the crawled tree.
+fn crawl<T: Transport>(transport: T, args: &Args, options: FtpOptions) -> Result<NodeEnum> {
+    let mut client: FtpClient<T> =
+        FtpClient::with_transport(transport, &args.username, &args.password, options)?;
+
+    Ok(client.list_dir(args.depth, args.bfs)?)
+}
+
+/// Connects through the given transport and crawls the server, writing every entry as a
+/// JSON line as soon as it is listed.
+///
+/// # Arguments
+///
+/// * `transport` - The transport opening the connections.
+/// * `args` - The parsed command-line arguments.
+/// * `options` - The options of the FTP client.
+///
+/// # Returns
+///
+/// A `Result` containing the exit code.
+fn stream<T: Transport>(transport: T, args: &Args, options: FtpOptions) -> Result<ExitCode> {
+    let (output, name): (Box<dyn Write>, String) = match args.output_path() {
+        Some(path) => (
+            Box::new(create_file(&path, args.force)?),
+            path.display().to_string(),
+        ),
+        None => (Box::new(io::stdout()), "standard output".to_string()),
+    };
+    let mut writer: JsonLinesWriter<BufWriter<Box<dyn Write>>> =
+        JsonLinesWriter::new(BufWriter::new(output));
+    let mut client: FtpClient<T> =
+        FtpClient::with_transport(transport, &args.username, &args.password, options)?;
+
+    client.list_dir_with(args.depth, args.bfs, |node, depth| {
+        writer.write(node, depth).map_err(|e| Error::OutputError {
+            path: name.clone(),
+            source: e,
+        })
+    })?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Connects through the given transport and re-crawls the server on an interval,
+/// reporting the changes, until the program is stopped.
+///
+/// # Arguments
+///
+/// * `transport` - The transport opening the connections.
+/// * `args` - The parsed command-line arguments.
+/// * `options` - The options of the FTP client.
+/// * `interval` - The time between the start of two crawls.
+///
+/// # Returns
+///
+/// A `Result` containing the exit code.
+fn watch<T: Transport>(
+    transport: T,
+    args: &Args,
+    options: FtpOptions,
+    interval: Duration,
+) -> Result<ExitCode> {
+    let client: FtpClient<T> =
+        FtpClient::with_transport(transport, &args.username, &args.password, options)?;
+    let mut watcher: Watcher<T> = Watcher::new(client, args.watch_options(interval))?;
+
+    watcher.run()?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Wraps the transport so every connection is recorded to the given file.
+///
+/// # Arguments
+///
+/// * `transport` - The transport opening the connections.
+/// * `path` - The file the session is recorded to.
+///
+/// # Returns
+///
+/// A `Result` containing the recording transport.
+fn record(transport: TcpTransport, path: &Path) -> Result<RecordingTransport<TcpTransport>> {
+    let transport: RecordingTransport<TcpTransport> = RecordingTransport::create(transport, path)
+        .map_err(|e| Error::OutputError {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+
+    info!("Recording the session to {}", path.display());
+
+    Ok(transport)
+}
@@ -0,0 +1,108 @@
+use std::{collections::HashMap, time::Duration};
+
+/// A misbehaviour the test server can be told to show when it receives a command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Close the control connection without replying.
+    Drop,
+
+    /// Reply `421` and close the control connection, as a server shutting down does.
+    ServiceUnavailable,
+
+    /// Wait for the given duration before handling the command.
+    Delay(Duration),
+}
+
+/// Triggers a `Fault` when the server receives a given command.
+#[derive(Clone, Debug)]
+pub struct FaultRule {
+    /// The verb of the command triggering the fault, such as `CWD`.
+    pub command: String,
+
+    /// The occurrence of the command triggering the fault, counted from 1 across all the
+    /// sessions of the server, or 0 to trigger it on every occurrence.
+    pub occurrence: usize,
+
+    /// The fault to trigger.
+    pub fault: Fault,
+}
+
+impl FaultRule {
+    /// Creates a new `FaultRule`.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The verb of the command triggering the fault.
+    /// * `occurrence` - The occurrence of the command triggering the fault, 0 for every one.
+    /// * `fault` - The fault to trigger.
+    ///
+    /// # Returns
+    ///
+    /// A new `FaultRule` instance.
+    pub fn new(command: &str, occurrence: usize, fault: Fault) -> Self {
+        FaultRule {
+            command: command.to_ascii_uppercase(),
+            occurrence,
+            fault,
+        }
+    }
+}
+
+/// Counts the commands received by the server and decides which faults they trigger.
+#[derive(Debug, Default)]
+pub(crate) struct FaultInjector {
+    rules: Vec<FaultRule>,
+    counts: HashMap<String, usize>,
+}
+
+impl FaultInjector {
+    /// Creates a new `FaultInjector` with the given rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - The rules to apply.
+    ///
+    /// # Returns
+    ///
+    /// A new `FaultInjector` instance.
+    pub(crate) fn new(rules: Vec<FaultRule>) -> Self {
+        FaultInjector {
+            rules,
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Adds a rule, applying to the commands received from now on.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - The rule to add.
+    pub(crate) fn add(&mut self, rule: FaultRule) {
+        self.rules.push(rule);
+    }
+
+    /// Records that a command was received and returns the faults it triggers.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The verb of the received command, in upper case.
+    ///
+    /// # Returns
+    ///
+    /// The faults to apply, in the order their rules were added.
+    pub(crate) fn record(&mut self, command: &str) -> Vec<Fault> {
+        let count: &mut usize = self.counts.entry(command.to_string()).or_insert(0);
+
+        *count += 1;
+
+        let count: usize = *count;
+
+        self.rules
+            .iter()
+            .filter(|rule| {
+                rule.command == command && (rule.occurrence == 0 || rule.occurrence == count)
+            })
+            .map(|rule| rule.fault.clone())
+            .collect()
+    }
+}
@@ -0,0 +1,238 @@
+use std::{
+    collections::HashMap,
+    io::{self, ErrorKind},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, sleep, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+use log::{debug, info};
+
+use crate::fs::{directory::Directory, node::NodeEnum};
+
+#[cfg(feature = "test-server")]
+use std::sync::Mutex;
+
+#[cfg(feature = "test-server")]
+use super::fault::{FaultInjector, FaultRule};
+use super::session::{ServerState, Session};
+
+/// How often the accept loop checks whether the server was stopped.
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// How the server makes up the content of files it has no content for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Fill {
+    /// Zero bytes, as many as the recorded size of the file.
+    #[default]
+    Zeros,
+
+    /// A repeated printable pattern, as many bytes as the recorded size of the file.
+    Pattern,
+}
+
+/// Configures the behaviour of an `FtpServer`.
+#[derive(Clone, Debug, Default)]
+pub struct ServerOptions {
+    /// The only accepted username and password, any login is accepted when `None`.
+    pub credentials: Option<(String, String)>,
+
+    /// The contents of the files by absolute path.
+    pub contents: HashMap<String, Vec<u8>>,
+
+    /// How the content of the other files is made up from their recorded size.
+    pub fill: Fill,
+
+    /// The faults to inject from the start.
+    #[cfg(feature = "test-server")]
+    pub faults: Vec<FaultRule>,
+}
+
+/// A read-only FTP server serving an in-memory `Directory` tree.
+///
+/// Every control connection is served by its own thread. The server answers USER, PASS,
+/// SYST, FEAT, NOOP, TYPE, PWD, CWD, CDUP, PASV, EPSV, LIST, MLSD, RETR and QUIT, refuses
+/// commands modifying the tree, and, with the `test-server` feature, can be told to
+/// misbehave with `FaultRule`s. It stops accepting connections when dropped.
+pub struct FtpServer {
+    addr: SocketAddr,
+    state: Arc<ServerState>,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl FtpServer {
+    /// Starts a server on a free port of localhost, accepting any login.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The tree to serve, its root directory standing for `/`.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the running `FtpServer`.
+    pub fn start(root: Directory) -> io::Result<Self> {
+        Self::with_options(root, ServerOptions::default())
+    }
+
+    /// Starts a server on a free port of localhost with the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The tree to serve, its root directory standing for `/`.
+    /// * `options` - The credentials, file contents and faults of the server.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the running `FtpServer`.
+    pub fn with_options(root: Directory, options: ServerOptions) -> io::Result<Self> {
+        Self::bind(SocketAddr::from(([127, 0, 0, 1], 0)), root, options)
+    }
+
+    /// Starts a server listening on the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the control port, port 0 picking a free one.
+    /// * `root` - The tree to serve, its root directory standing for `/`.
+    /// * `options` - The credentials, file contents and faults of the server.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the running `FtpServer`.
+    pub fn bind(addr: SocketAddr, root: Directory, options: ServerOptions) -> io::Result<Self> {
+        let listener: TcpListener = TcpListener::bind(addr)?;
+        let addr: SocketAddr = listener.local_addr()?;
+        let state: Arc<ServerState> = Arc::new(ServerState {
+            root: NodeEnum::Directory(root),
+            credentials: options.credentials,
+            contents: options.contents,
+            fill: options.fill,
+            #[cfg(feature = "test-server")]
+            faults: Mutex::new(FaultInjector::new(options.faults)),
+            connections: AtomicUsize::new(0),
+            started_at: SystemTime::now(),
+        });
+        let running: Arc<AtomicBool> = Arc::new(AtomicBool::new(true));
+
+        listener.set_nonblocking(true)?;
+
+        let handle: JoinHandle<()> = {
+            let state: Arc<ServerState> = Arc::clone(&state);
+            let running: Arc<AtomicBool> = Arc::clone(&running);
+
+            thread::spawn(move || Self::accept_loop(listener, state, running))
+        };
+
+        info!("FTP server listening on {}", addr);
+
+        Ok(FtpServer {
+            addr,
+            state,
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the address the server listens on.
+    ///
+    /// # Returns
+    ///
+    /// The `SocketAddr` of the control port.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Adds a fault, applying to the commands received from now on.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - The rule triggering the fault.
+    #[cfg(feature = "test-server")]
+    pub fn inject(&self, rule: FaultRule) {
+        self.state
+            .faults
+            .lock()
+            .expect("fault injector lock poisoned")
+            .add(rule);
+    }
+
+    /// Returns the number of control connections accepted so far.
+    ///
+    /// # Returns
+    ///
+    /// The number of connections, reconnections included.
+    pub fn connections(&self) -> usize {
+        self.state.connections.load(Ordering::SeqCst)
+    }
+
+    /// Serves connections until the accept loop stops, which only happens on errors.
+    ///
+    /// # Returns
+    ///
+    /// Once the server no longer accepts connections.
+    pub fn wait(mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    /// Accepts control connections until the server is stopped.
+    ///
+    /// # Arguments
+    ///
+    /// * `listener` - The non-blocking listener of the control port.
+    /// * `state` - The state shared by the sessions.
+    /// * `running` - Cleared when the server is dropped.
+    fn accept_loop(listener: TcpListener, state: Arc<ServerState>, running: Arc<AtomicBool>) {
+        while running.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    debug!("FTP server accepted {}", peer);
+
+                    state.connections.fetch_add(1, Ordering::SeqCst);
+
+                    let state: Arc<ServerState> = Arc::clone(&state);
+
+                    thread::spawn(move || Self::serve(state, stream));
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => sleep(ACCEPT_POLL_INTERVAL),
+                Err(e) => {
+                    debug!("FTP server stopped accepting: {}", e);
+
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Serves an accepted control connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state shared by the sessions.
+    /// * `stream` - The accepted control connection.
+    fn serve(state: Arc<ServerState>, stream: TcpStream) {
+        match stream
+            .set_nonblocking(false)
+            .and_then(|_| Session::new(state, stream))
+        {
+            Ok(session) => session.run(),
+            Err(e) => debug!("FTP server could not start a session: {}", e),
+        }
+    }
+}
+
+impl Drop for FtpServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,4 @@
+#[cfg(feature = "test-server")]
+pub mod fault;
+pub mod ftp_server;
+mod session;
@@ -0,0 +1,684 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    net::{IpAddr, SocketAddr, TcpListener, TcpStream},
+    sync::{atomic::AtomicUsize, Arc},
+    thread::sleep,
+    time::{Duration, Instant, SystemTime},
+};
+
+use log::debug;
+
+use crate::{
+    fs::{
+        metadata::{permission_mode, Metadata},
+        node::{Node, NodeEnum},
+        timestamp::{list_time, machine_time, modified_time},
+    },
+    utils::path,
+};
+
+#[cfg(feature = "test-server")]
+use std::sync::Mutex;
+
+#[cfg(feature = "test-server")]
+use super::fault::{Fault, FaultInjector};
+use super::ftp_server::Fill;
+
+/// How long a transfer waits for the client to open the data connection.
+const DATA_ACCEPT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The number of bytes of made up content written at once.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The pattern repeated by `Fill::Pattern`.
+const PATTERN: &[u8] = b"tree-ftp\n";
+
+/// The state shared by the sessions of a test server.
+pub(crate) struct ServerState {
+    /// The tree served, its root directory standing for `/`.
+    pub(crate) root: NodeEnum,
+
+    /// The only accepted username and password, any login is accepted when `None`.
+    pub(crate) credentials: Option<(String, String)>,
+
+    /// The contents of the files by absolute path.
+    pub(crate) contents: HashMap<String, Vec<u8>>,
+
+    /// How the content of the other files is made up from their recorded size.
+    pub(crate) fill: Fill,
+
+    /// The faults injected into the sessions.
+    #[cfg(feature = "test-server")]
+    pub(crate) faults: Mutex<FaultInjector>,
+
+    /// The number of control connections accepted so far.
+    pub(crate) connections: AtomicUsize,
+
+    /// The time the server started, listed as the modification time of the entries
+    /// recording none.
+    pub(crate) started_at: SystemTime,
+}
+
+impl ServerState {
+    /// Returns the node at the given absolute path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The normalized absolute path of the node.
+    ///
+    /// # Returns
+    ///
+    /// The node, or `None` if nothing exists at that path.
+    fn find(&self, path: &str) -> Option<&NodeEnum> {
+        path.split('/')
+            .filter(|component| !component.is_empty())
+            .try_fold(&self.root, |node, component| match node {
+                NodeEnum::Directory(dir) => dir.nodes.iter().find(|n| n.name() == component),
+                NodeEnum::File(_) => None,
+            })
+    }
+
+    /// Returns the size of a node.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The absolute path of the node.
+    /// * `node` - The node.
+    ///
+    /// # Returns
+    ///
+    /// The size of the content given for the file, or else its recorded size, in bytes.
+    fn size(&self, path: &str, node: &NodeEnum) -> u64 {
+        match (self.contents.get(path), node) {
+            (Some(content), _) => content.len() as u64,
+            (None, NodeEnum::File(file)) => file.metadata.size.unwrap_or(0),
+            (None, NodeEnum::Directory(dir)) => dir.metadata.size.unwrap_or(0),
+        }
+    }
+
+    /// Writes the content of a file.
+    ///
+    /// Made up content is written in chunks, so files of any recorded size can be served
+    /// without holding them in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The absolute path of the file.
+    /// * `node` - The file.
+    /// * `writer` - The data connection.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether the content given for the file, or else content
+    /// of its recorded size made up as configured, was written.
+    fn write_content(&self, path: &str, node: &NodeEnum, writer: &mut dyn Write) -> io::Result<()> {
+        if let Some(content) = self.contents.get(path) {
+            return writer.write_all(content);
+        }
+
+        let chunk: Vec<u8> = match self.fill {
+            Fill::Zeros => vec![0; CHUNK_SIZE],
+            Fill::Pattern => PATTERN
+                .iter()
+                .copied()
+                .cycle()
+                .take(CHUNK_SIZE - CHUNK_SIZE % PATTERN.len())
+                .collect(),
+        };
+        let mut remaining: u64 = self.size(path, node);
+
+        while remaining > 0 {
+            let length: usize = remaining.min(chunk.len() as u64) as usize;
+
+            writer.write_all(&chunk[..length])?;
+            remaining -= length as u64;
+        }
+
+        Ok(())
+    }
+}
+
+/// What to do with the control connection once a command has been handled.
+enum Flow {
+    Continue,
+    Close,
+}
+
+/// A control connection of a test server.
+pub(crate) struct Session {
+    state: Arc<ServerState>,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    username: Option<String>,
+    logged_in: bool,
+    cwd: String,
+    passive: Option<TcpListener>,
+}
+
+impl Session {
+    /// Creates a new `Session` over an accepted control connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state shared by the sessions of the server.
+    /// * `stream` - The accepted control connection.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the new `Session`.
+    pub(crate) fn new(state: Arc<ServerState>, stream: TcpStream) -> io::Result<Self> {
+        Ok(Session {
+            state,
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            username: None,
+            logged_in: false,
+            cwd: "/".to_string(),
+            passive: None,
+        })
+    }
+
+    /// Serves the session until the client quits, the connection drops or a fault closes it.
+    pub(crate) fn run(mut self) {
+        if let Err(e) = self.serve() {
+            debug!("FTP server session ended: {}", e);
+        }
+    }
+
+    /// Sends the banner, then reads and answers commands.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating how the session ended.
+    fn serve(&mut self) -> io::Result<()> {
+        self.reply(220, "tree-ftp server ready")?;
+
+        let mut line: String = String::new();
+
+        loop {
+            line.clear();
+
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+
+            let line: &str = line.trim_end_matches(['\r', '\n']);
+            let (verb, argument): (&str, &str) = line.split_once(' ').unwrap_or((line, ""));
+            let verb: String = verb.to_ascii_uppercase();
+
+            debug!("FTP server received: {} {}", verb, argument);
+
+            #[cfg(feature = "test-server")]
+            if let Flow::Close = self.inject_faults(&verb)? {
+                return Ok(());
+            }
+
+            if let Flow::Close = self.handle(&verb, argument.to_string())? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Applies the faults triggered by a command, before it is answered.
+    ///
+    /// # Arguments
+    ///
+    /// * `verb` - The command, in upper case.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing whether to keep the control connection open.
+    #[cfg(feature = "test-server")]
+    fn inject_faults(&mut self, verb: &str) -> io::Result<Flow> {
+        let faults: Vec<Fault> = self
+            .state
+            .faults
+            .lock()
+            .expect("fault injector lock poisoned")
+            .record(verb);
+
+        for fault in faults {
+            match fault {
+                Fault::Drop => return Ok(Flow::Close),
+                Fault::ServiceUnavailable => {
+                    self.reply(421, "Service not available, closing control connection")?;
+
+                    return Ok(Flow::Close);
+                }
+                Fault::Delay(delay) => sleep(delay),
+            }
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    /// Answers a single command.
+    ///
+    /// # Arguments
+    ///
+    /// * `verb` - The verb of the command, in upper case.
+    /// * `argument` - The argument of the command, empty if there is none.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing whether the session goes on.
+    fn handle(&mut self, verb: &str, argument: String) -> io::Result<Flow> {
+        match verb {
+            "USER" => {
+                self.username = Some(argument);
+                self.logged_in = false;
+                self.reply(331, "Password required")?;
+            }
+            "PASS" => self.login(&argument)?,
+            "QUIT" => {
+                self.reply(221, "Goodbye")?;
+
+                return Ok(Flow::Close);
+            }
+            "SYST" => self.reply(215, "UNIX Type: L8")?,
+            "FEAT" => self.send("211-Features:\r\n EPSV\r\n MLSD\r\n PASV\r\n211 End\r\n")?,
+            "NOOP" => self.reply(200, "NOOP ok")?,
+            _ if !self.logged_in => self.reply(530, "Please login with USER and PASS")?,
+            "TYPE" => self.reply(200, &format!("Type set to {}", argument))?,
+            "PWD" => self.reply(
+                257,
+                &format!(
+                    "\"{}\" is the current directory",
+                    self.cwd.replace('"', "\"\"")
+                ),
+            )?,
+            "CWD" => self.change_directory(&argument)?,
+            "CDUP" => self.change_directory("..")?,
+            "PASV" | "EPSV" => self.enter_passive_mode(verb == "EPSV")?,
+            "LIST" => self.list(&argument)?,
+            "MLSD" => self.machine_list(&argument)?,
+            "RETR" => self.retrieve(&argument)?,
+            "STOR" | "STOU" | "APPE" | "DELE" | "MKD" | "RMD" | "RNFR" | "RNTO" => {
+                self.reply(550, "Permission denied, the server is read-only")?
+            }
+            _ => self.reply(502, "Command not implemented")?,
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    /// Checks the password sent after USER.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - The password sent by the client.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether the reply was sent.
+    fn login(&mut self, password: &str) -> io::Result<()> {
+        let username: &str = match &self.username {
+            Some(username) => username,
+            None => return self.reply(503, "Login with USER first"),
+        };
+
+        self.logged_in = match &self.state.credentials {
+            Some((expected_username, expected_password)) => {
+                username == expected_username && password == expected_password
+            }
+            None => true,
+        };
+
+        if self.logged_in {
+            self.reply(230, "Login successful")
+        } else {
+            self.reply(530, "Login incorrect")
+        }
+    }
+
+    /// Changes the working directory, answering CWD and CDUP.
+    ///
+    /// # Arguments
+    ///
+    /// * `argument` - The path of the new working directory.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether the reply was sent.
+    fn change_directory(&mut self, argument: &str) -> io::Result<()> {
+        let target: String = path::resolve(&self.cwd, argument);
+
+        match self.state.find(&target) {
+            Some(NodeEnum::Directory(_)) => {
+                self.cwd = target;
+                self.reply(250, "Directory successfully changed")
+            }
+            _ => self.reply(550, "Failed to change directory"),
+        }
+    }
+
+    /// Opens a listener for the next data connection, answering PASV and EPSV.
+    ///
+    /// # Arguments
+    ///
+    /// * `extended` - Whether the client sent EPSV.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether the reply was sent.
+    fn enter_passive_mode(&mut self, extended: bool) -> io::Result<()> {
+        let ip: IpAddr = self.writer.local_addr()?.ip();
+        let listener: TcpListener = TcpListener::bind(SocketAddr::new(ip, 0))?;
+        let port: u16 = listener.local_addr()?.port();
+
+        self.passive = Some(listener);
+
+        match ip {
+            IpAddr::V4(ipv4) if !extended => {
+                let [h1, h2, h3, h4] = ipv4.octets();
+
+                self.reply(
+                    227,
+                    &format!(
+                        "Entering Passive Mode ({},{},{},{},{},{})",
+                        h1,
+                        h2,
+                        h3,
+                        h4,
+                        port / 256,
+                        port % 256
+                    ),
+                )
+            }
+            _ => self.reply(
+                229,
+                &format!("Entering Extended Passive Mode (|||{}|)", port),
+            ),
+        }
+    }
+
+    /// Sends a LIST listing in the format of `ls -l`.
+    ///
+    /// Hidden entries are only listed when the `-a` option is given, along with `.` and `..`.
+    /// The options are the leading words starting with `-`, and the rest of the argument is
+    /// the path, which may hold spaces.
+    ///
+    /// # Arguments
+    ///
+    /// * `argument` - The options and the optional path of the listed directory.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether the listing was sent.
+    fn list(&mut self, argument: &str) -> io::Result<()> {
+        let mut rest: &str = argument.trim();
+        let mut all: bool = false;
+
+        while rest.starts_with('-') {
+            let (option, tail): (&str, &str) =
+                rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+            all |= option.contains('a');
+            rest = tail.trim_start();
+        }
+
+        let target: String = path::resolve(&self.cwd, if rest.is_empty() { "." } else { rest });
+        let now: SystemTime = SystemTime::now();
+        let mut lines: Vec<String> = Vec::new();
+
+        match self.state.find(&target) {
+            Some(node @ NodeEnum::Directory(dir)) => {
+                if all {
+                    lines.push(self.long_line(node, 0, ".", now));
+                    lines.push(self.long_line(node, 0, "..", now));
+                }
+
+                for child in &dir.nodes {
+                    if !all && child.name().starts_with('.') {
+                        continue;
+                    }
+
+                    let child_path: String = path::join(&target, child.name());
+
+                    lines.push(self.long_line(
+                        child,
+                        self.state.size(&child_path, child),
+                        child.name(),
+                        now,
+                    ));
+                }
+            }
+            Some(node @ NodeEnum::File(file)) => {
+                lines.push(self.long_line(node, self.state.size(&target, node), &file.name, now))
+            }
+            None => return self.reply(550, "No such file or directory"),
+        }
+
+        let listing: String = Self::join_lines(lines);
+
+        self.transfer(|stream| stream.write_all(listing.as_bytes()))
+    }
+
+    /// Sends an MLSD listing, as described in RFC 3659.
+    ///
+    /// Every entry holds its `type`, `size`, `modify` and `UNIX.mode` facts, the last two
+    /// when the tree records them.
+    ///
+    /// # Arguments
+    ///
+    /// * `argument` - The optional path of the listed directory.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether the listing was sent.
+    fn machine_list(&mut self, argument: &str) -> io::Result<()> {
+        let target: String = path::resolve(&self.cwd, argument);
+        let dir = match self.state.find(&target) {
+            Some(NodeEnum::Directory(dir)) => dir,
+            Some(NodeEnum::File(_)) => return self.reply(501, "Not a directory"),
+            None => return self.reply(550, "No such directory"),
+        };
+        let lines: Vec<String> = dir
+            .nodes
+            .iter()
+            .map(|node| {
+                format!(
+                    "{} {}",
+                    Self::facts(
+                        node,
+                        self.state.size(&path::join(&target, node.name()), node)
+                    ),
+                    node.name()
+                )
+            })
+            .collect();
+        let listing: String = Self::join_lines(lines);
+
+        self.transfer(|stream| stream.write_all(listing.as_bytes()))
+    }
+
+    /// Sends the content of a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `argument` - The path of the file.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether the content was sent.
+    fn retrieve(&mut self, argument: &str) -> io::Result<()> {
+        let target: String = path::resolve(&self.cwd, argument);
+
+        let state: Arc<ServerState> = Arc::clone(&self.state);
+
+        match state.find(&target) {
+            Some(node @ NodeEnum::File(_)) => {
+                self.transfer(|stream| state.write_content(&target, node, stream))
+            }
+            _ => self.reply(550, "Failed to open file"),
+        }
+    }
+
+    /// Sends data over the data connection opened in passive mode, framed by the `150`
+    /// and `226` replies.
+    ///
+    /// # Arguments
+    ///
+    /// * `send` - Writes the data to the data connection.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether the transfer completed.
+    fn transfer(&mut self, send: impl FnOnce(&mut TcpStream) -> io::Result<()>) -> io::Result<()> {
+        let listener: TcpListener = match self.passive.take() {
+            Some(listener) => listener,
+            None => return self.reply(425, "Use PASV or EPSV first"),
+        };
+
+        self.reply(150, "Opening data connection")?;
+
+        let mut stream: TcpStream = match Self::accept(&listener) {
+            Ok(stream) => stream,
+            Err(_) => return self.reply(425, "Failed to establish connection"),
+        };
+
+        send(&mut stream)?;
+        drop(stream);
+
+        self.reply(226, "Transfer complete")
+    }
+
+    /// Waits for the client to open the data connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `listener` - The listener opened in passive mode.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the data connection.
+    fn accept(listener: &TcpListener) -> io::Result<TcpStream> {
+        let deadline: Instant = Instant::now() + DATA_ACCEPT_TIMEOUT;
+
+        listener.set_nonblocking(true)?;
+
+        loop {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(false)?;
+
+                    return Ok(stream);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock && Instant::now() < deadline => {
+                    sleep(Duration::from_millis(5));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Formats an entry of a LIST listing from the metadata of its node.
+    ///
+    /// The modification time is printed in the form of `ls -l` whatever form it was
+    /// recorded in, and entries recording no time get the one the server started at.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node of the entry.
+    /// * `size` - The size of the entry, in bytes.
+    /// * `name` - The name of the entry.
+    /// * `now` - The current time, to choose between the time of day and the year.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the line, without its line ending.
+    fn long_line(&self, node: &NodeEnum, size: u64, name: &str, now: SystemTime) -> String {
+        let (metadata, permissions): (&Metadata, &str) = match node {
+            NodeEnum::Directory(dir) => (&dir.metadata, "drwxr-xr-x"),
+            NodeEnum::File(file) => (&file.metadata, "-rw-r--r--"),
+        };
+        let modified: SystemTime = metadata
+            .modified
+            .as_deref()
+            .and_then(|modified| modified_time(modified, now))
+            .unwrap_or(self.state.started_at);
+
+        format!(
+            "{} 1 {} {} {:>8} {} {}",
+            metadata.permissions.as_deref().unwrap_or(permissions),
+            metadata.owner.as_deref().unwrap_or("ftp"),
+            metadata.group.as_deref().unwrap_or("ftp"),
+            size,
+            list_time(modified, now),
+            name
+        )
+    }
+
+    /// Formats the facts of an entry of an MLSD listing from the metadata of its node.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node of the entry.
+    /// * `size` - The size of the entry, in bytes.
+    ///
+    /// # Returns
+    ///
+    /// A `String` such as `type=file;size=1024;modify=20250101000000;UNIX.mode=0644;`.
+    fn facts(node: &NodeEnum, size: u64) -> String {
+        let (metadata, mut facts): (&Metadata, String) = match node {
+            NodeEnum::Directory(dir) => (&dir.metadata, String::from("type=dir;")),
+            NodeEnum::File(file) => (&file.metadata, format!("type=file;size={};", size)),
+        };
+
+        if let Some(time) = metadata
+            .modified
+            .as_deref()
+            .and_then(|modified| modified_time(modified, SystemTime::now()))
+        {
+            facts.push_str(&format!("modify={};", machine_time(time)));
+        }
+
+        if let Some(mode) = metadata.permissions.as_deref().and_then(permission_mode) {
+            facts.push_str(&format!("UNIX.mode={:04o};", mode));
+        }
+
+        facts
+    }
+
+    /// Joins listing lines with CRLF line endings.
+    ///
+    /// # Arguments
+    ///
+    /// * `lines` - The lines of the listing.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the listing.
+    fn join_lines(lines: Vec<String>) -> String {
+        lines.iter().map(|line| format!("{}\r\n", line)).collect()
+    }
+
+    /// Sends a single-line reply.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The reply code.
+    /// * `message` - The text of the reply.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether the reply was sent.
+    fn reply(&mut self, code: u16, message: &str) -> io::Result<()> {
+        self.send(&format!("{} {}\r\n", code, message))
+    }
+
+    /// Sends raw text on the control connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to send, including its line endings.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether the text was sent.
+    fn send(&mut self, text: &str) -> io::Result<()> {
+        self.writer.write_all(text.as_bytes())?;
+        self.writer.flush()
+    }
+}
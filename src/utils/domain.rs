@@ -14,19 +14,15 @@ use super::validator::DomainAllowPort;
 ///
 /// A `Result` containing the resolved `SocketAddr`, or an `Error` if resolution fails.
 pub fn resolve_domain_to_socket_addr(addr: &DomainAllowPort) -> Result<SocketAddr> {
-    let mut addrs: IntoIter<SocketAddr> = if let Some(port) = addr.port {
-        (addr.domain.as_str(), port)
-            .to_socket_addrs()
-            .map_err(|_| Error::DomainResolutionError)?
-    } else {
-        (addr.domain.as_str(), 21)
-            .to_socket_addrs()
-            .map_err(|_| Error::DomainResolutionError)?
-    };
+    let mut addrs: IntoIter<SocketAddr> = (addr.domain.as_str(), addr.port.unwrap_or(21))
+        .to_socket_addrs()
+        .map_err(|e| Error::DomainResolutionError {
+            domain: addr.domain.clone(),
+            source: Some(e),
+        })?;
 
-    if let Some(addr) = addrs.next() {
-        Ok(addr)
-    } else {
-        Err(Error::DomainResolutionError)
-    }
+    addrs.next().ok_or_else(|| Error::DomainResolutionError {
+        domain: addr.domain.clone(),
+        source: None,
+    })
 }
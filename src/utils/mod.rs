@@ -1,3 +1,4 @@
 pub mod domain;
 pub mod parser;
-pub mod validator;
\ No newline at end of file
+pub mod path;
+pub mod validator;
@@ -1,12 +1,42 @@
-use clap::Parser;
+use std::{
+    io::{self, IsTerminal},
+    net::SocketAddr,
+    path::PathBuf,
+    time::Duration,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::fs::diff::{DiffFormat, NodeType};
+use crate::fs::dot::Dot;
+use crate::fs::filter::{Filter, Pattern};
+use crate::fs::find::{PermissionBits, Query, SizeBound};
+use crate::fs::format::{OutputFormat, TreeFormatter, TreeText};
+use crate::fs::node::TraversalType;
+use crate::fs::tree::{Charset, SortKey, TreeOptions};
+use crate::ftp::options::{FtpOptions, ReconnectPolicy};
+use crate::ftp::watch::WatchOptions;
+use crate::server::ftp_server::{Fill, ServerOptions};
 
 /// Command-line arguments for the FTP client.
+///
+/// Without a subcommand, the server at the given address is crawled.
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 pub struct Args {
+    /// The subcommand to run instead of crawling a server.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// The address of the FTP server.
-    #[arg(index = 1)]
-    pub address: String,
+    #[arg(index = 1, required = true)]
+    pub address: Option<String>,
 
     /// The username for FTP authentication. Defaults to "anonymous".
     #[arg(short, long, default_value_t = String::from("anonymous"))]
@@ -20,15 +50,638 @@ pub struct Args {
     #[arg(short, long, default_value_t = 1)]
     pub depth: usize,
 
-    /// Output the result in JSON format. Defaults to false.
-    #[arg(short, long, default_value_t = false)]
+    /// The format the tree is written in. "jsonl" writes every entry as soon as it is
+    /// listed.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Tree)]
+    pub format: OutputFormat,
+
+    /// With "--format dot", the deepest level drawn, 1 for the entries of the root. Deeper
+    /// entries are still counted by --dot-sizes.
+    #[arg(long, value_name = "DEPTH")]
+    pub dot_depth: Option<usize>,
+
+    /// With "--format dot", draw directories as boxes holding their entries. Defaults to
+    /// false.
+    #[arg(long, default_value_t = false)]
+    pub dot_cluster: bool,
+
+    /// With "--format dot", label files with their size and directories with their total
+    /// size and number of files. Defaults to false.
+    #[arg(long, default_value_t = false)]
+    pub dot_sizes: bool,
+
+    /// With the tree format, the characters the branches are drawn with.
+    #[arg(long, value_enum, default_value_t = Charset::Utf8)]
+    pub charset: Charset,
+
+    /// With the tree format, list directories before files. Defaults to false.
+    #[arg(long, default_value_t = false)]
+    pub dirsfirst: bool,
+
+    /// With the tree format, sort the entries of every directory. Defaults to the order of
+    /// the listing.
+    #[arg(long, value_enum, value_name = "KEY")]
+    pub sort: Option<SortKey>,
+
+    /// With the tree format, print the size of every entry in bytes, like "tree -s".
+    /// Defaults to false.
+    #[arg(short = 's', long = "size", default_value_t = false)]
+    pub print_size: bool,
+
+    /// With the tree format, print sizes with units such as "1.5K", like "tree -h". There is
+    /// no "-h" short form, which prints the help. Defaults to false.
+    #[arg(long, default_value_t = false)]
+    pub human: bool,
+
+    /// With the tree format, print the modification time of every entry, like "tree -D".
+    /// Defaults to false.
+    #[arg(short = 'D', long = "date", default_value_t = false)]
+    pub print_date: bool,
+
+    /// With the tree format, print the permissions of every entry, like "tree -p". There is
+    /// no "-p" short form, which gives the password. Defaults to false.
+    #[arg(long = "perms", default_value_t = false)]
+    pub print_permissions: bool,
+
+    /// With the tree format, append "/" to directories and "*" to executable files, like
+    /// "tree -F". Defaults to false.
+    #[arg(short = 'F', long, default_value_t = false)]
+    pub classify: bool,
+
+    /// With the tree format, leave out the line counting directories and files. Defaults to
+    /// false.
+    #[arg(long, default_value_t = false)]
+    pub noreport: bool,
+
+    /// Write the tree as a JSON snapshot, like "--format json" but to "output.json" unless
+    /// --output is given. Defaults to false.
+    #[arg(short, long, default_value_t = false, conflicts_with = "format")]
     pub json: bool,
 
-    /// Use breadth-first search for directory traversal. Defaults to false.
+    /// Print every entry as a JSON line as soon as it is listed, with its path, type, depth
+    /// and metadata, like "--format jsonl". Defaults to false.
+    #[arg(long, default_value_t = false, conflicts_with_all = ["format", "json", "ncdu", "diff"])]
+    pub jsonl: bool,
+
+    /// The file the tree is written to, "-" for the standard output. Defaults to the
+    /// standard output, or to "output.json" for --json.
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite the files written by --output, --json and --ncdu when they already exist.
+    /// Defaults to false.
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+
+    /// Write the tree to this file in the JSON export format of ncdu, to browse it with
+    /// "ncdu -f PATH". A shorthand for "--format ncdu --output PATH".
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["format", "json", "output"])]
+    pub ncdu: Option<PathBuf>,
+
+    /// Append the crawl to this SQLite database, creating it if needed, to query crawls of
+    /// several servers and dates with SQL.
+    #[cfg(feature = "sqlite")]
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["format", "json", "jsonl", "output", "ncdu", "flat", "diff", "watch"])]
+    pub sqlite: Option<PathBuf>,
+
+    /// Print one full remote path per line instead of the tree. Defaults to false.
+    #[arg(long, default_value_t = false, conflicts_with_all = ["format", "json", "jsonl", "ncdu"])]
+    pub flat: bool,
+
+    /// Precede every path printed by --flat with its type, size and modification time,
+    /// separated by tabs. Defaults to false.
+    #[arg(short, long, default_value_t = false, requires = "flat")]
+    pub long: bool,
+
+    /// Use breadth-first search for directory traversal. Only the order of the crawl and
+    /// of --flat changes, the tree is always drawn directory by directory. Defaults to false.
     #[arg(short, long, default_value_t = false)]
     pub bfs: bool,
 
     /// Use extended passive mode for data connections. Defaults to false.
     #[arg(short, long, default_value_t = false)]
     pub extended: bool,
+
+    /// List hidden files too, with MLSD when the server supports it or LIST -a otherwise.
+    /// Defaults to false.
+    #[arg(short, long, default_value_t = false)]
+    pub all: bool,
+
+    /// Maximum time to wait for a connection to be established. "0s" waits forever.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "30s")]
+    pub connect_timeout: Duration,
+
+    /// Maximum time to wait for a reply on the control connection. "0s" waits forever.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "60s")]
+    pub read_timeout: Duration,
+
+    /// Maximum time to wait for data on a data connection. "0s" waits forever.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "60s")]
+    pub data_timeout: Duration,
+
+    /// Time after which an unused control connection is re-established. "0s" never does.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "5m")]
+    pub idle_timeout: Duration,
+
+    /// Maximum number of attempts to re-establish a lost connection.
+    #[arg(long, default_value_t = 8)]
+    pub reconnect_attempts: usize,
+
+    /// Delay before the first reconnection attempt, doubled after every failed attempt.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "1s")]
+    pub reconnect_delay: Duration,
+
+    /// Upper bound of the delay between two reconnection attempts.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "1m")]
+    pub reconnect_max_delay: Duration,
+
+    /// Record every command, reply and data payload of the session to this file as JSON
+    /// Lines, with the password redacted.
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+
+    /// Only keep the files matching this glob, such as "*.csv". Matched against the full
+    /// path when it contains a "/", against the name otherwise. Can be repeated.
+    #[arg(long, value_name = "GLOB", value_parser = Pattern::glob)]
+    pub include: Vec<Pattern>,
+
+    /// Skip the files and directories matching this glob, such as ".git" or "*.tmp".
+    /// Skipped directories are not listed. Can be repeated.
+    #[arg(long, value_name = "GLOB", value_parser = Pattern::glob)]
+    pub exclude: Vec<Pattern>,
+
+    /// Only keep the files matching this regular expression. Can be repeated.
+    #[arg(long, value_name = "REGEX", value_parser = Pattern::regex)]
+    pub include_regex: Vec<Pattern>,
+
+    /// Skip the files and directories matching this regular expression. Can be repeated.
+    #[arg(long, value_name = "REGEX", value_parser = Pattern::regex)]
+    pub exclude_regex: Vec<Pattern>,
+
+    /// Match --include-regex and --exclude-regex against the full remote path of entries,
+    /// such as "^/pub/[^/]*\.csv$", instead of their name. Defaults to false.
+    #[arg(long, default_value_t = false)]
+    pub regex_full_path: bool,
+
+    /// Re-crawl the server on this interval, such as "10m", and report the changes
+    /// between two crawls instead of printing the tree.
+    #[arg(long, value_name = "INTERVAL", value_parser = humantime::parse_duration, conflicts_with_all = ["format", "json", "jsonl", "output", "ncdu", "flat", "diff"])]
+    pub watch: Option<Duration>,
+
+    /// The file keeping the last snapshot of --watch, so a restarted watch reports what
+    /// changed while it was stopped.
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_value = "tree-ftp-state.json",
+        requires = "watch"
+    )]
+    pub state: PathBuf,
+
+    /// Append every change found by --watch to this file as a JSON line.
+    #[arg(long, value_name = "PATH", requires = "watch")]
+    pub events: Option<PathBuf>,
+
+    /// Run this shell command for every change found by --watch. The change is described
+    /// by the TREE_FTP_CHANGE, TREE_FTP_PATH and TREE_FTP_EVENT environment variables.
+    #[arg(long, value_name = "COMMAND", requires = "watch")]
+    pub hook: Option<String>,
+
+    /// Time the control connection may stay unused before a NOOP command keeps it alive,
+    /// during long transfers and between two crawls of --watch. "0s" never sends one.
+    /// Transfers are checked after every line, so one stalling without sending a line
+    /// sends none until --data-timeout ends it.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "1m")]
+    pub keepalive: Duration,
+
+    /// Compare the crawled tree with this JSON snapshot and report the differences
+    /// instead of the tree, in the format given by --diff-format rather than --format.
+    /// The report is written to the standard output. Exits with 1 when the trees differ.
+    #[arg(long, value_name = "SNAPSHOT", conflicts_with_all = ["format", "json", "output", "ncdu", "flat"])]
+    pub diff: Option<PathBuf>,
+
+    /// The format of the report written by --diff.
+    #[arg(long, value_enum, default_value_t = DiffFormat::Text, requires = "diff")]
+    pub diff_format: DiffFormat,
+
+    /// When to colour the report written by --diff.
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+}
+
+impl Args {
+    /// Builds the options of a watch from the command-line arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The time between the start of two crawls.
+    ///
+    /// # Returns
+    ///
+    /// The `WatchOptions` matching the arguments.
+    pub fn watch_options(&self, interval: Duration) -> WatchOptions {
+        WatchOptions {
+            interval,
+            keepalive: self.keepalive,
+            depth: self.depth,
+            bfs: self.bfs,
+            state: self.state.clone(),
+            events: self.events.clone(),
+            hook: self.hook.clone(),
+            print: true,
+            max_cycles: None,
+        }
+    }
+
+    /// Returns the format the tree is written in, taking the --json, --jsonl and --ncdu
+    /// shorthands into account.
+    ///
+    /// # Returns
+    ///
+    /// The `OutputFormat` of the tree.
+    pub fn output_format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else if self.jsonl {
+            OutputFormat::Jsonl
+        } else if self.ncdu.is_some() {
+            OutputFormat::Ncdu
+        } else {
+            self.format
+        }
+    }
+
+    /// Builds the formatter writing the tree from the command-line arguments.
+    ///
+    /// # Returns
+    ///
+    /// A `Box` holding the formatter of the output format.
+    pub fn formatter(&self) -> Box<dyn TreeFormatter> {
+        match self.output_format() {
+            OutputFormat::Dot => Box::new(Dot {
+                max_depth: self.dot_depth,
+                cluster: self.dot_cluster,
+                sizes: self.dot_sizes,
+            }),
+            OutputFormat::Tree => Box::new(TreeText {
+                options: self.tree_options(),
+            }),
+            format => format.formatter(),
+        }
+    }
+
+    /// Builds the options of the tree format from the command-line arguments.
+    ///
+    /// # Returns
+    ///
+    /// The `TreeOptions` matching the tree(1) style flags.
+    pub fn tree_options(&self) -> TreeOptions {
+        TreeOptions {
+            charset: self.charset,
+            dirs_first: self.dirsfirst,
+            sort: self.sort,
+            sizes: self.print_size,
+            human: self.human,
+            dates: self.print_date,
+            permissions: self.print_permissions,
+            classify: self.classify,
+            no_report: self.noreport,
+        }
+    }
+
+    /// Returns the order the entries of the tree are listed in.
+    ///
+    /// # Returns
+    ///
+    /// `TraversalType::BFS` with --bfs, `TraversalType::DFS` otherwise.
+    pub fn traversal_type(&self) -> TraversalType {
+        if self.bfs {
+            TraversalType::BFS
+        } else {
+            TraversalType::DFS
+        }
+    }
+
+    /// Returns the file the tree is written to.
+    ///
+    /// # Returns
+    ///
+    /// The path of the file, or `None` for the standard output.
+    pub fn output_path(&self) -> Option<PathBuf> {
+        match &self.output {
+            Some(path) if path.as_os_str() == "-" => None,
+            Some(path) => Some(path.clone()),
+            None if self.json => Some(PathBuf::from("output.json")),
+            None => self.ncdu.clone(),
+        }
+    }
+
+    /// Builds the filter selecting the entries of the tree from the command-line arguments.
+    ///
+    /// # Returns
+    ///
+    /// The `Filter` holding the glob and regex patterns.
+    pub fn filter(&self) -> Filter {
+        let regex = |patterns: &[Pattern]| -> Vec<Pattern> {
+            patterns
+                .iter()
+                .map(|pattern| pattern.clone().with_full_path(self.regex_full_path))
+                .collect()
+        };
+
+        Filter {
+            include: [self.include.clone(), regex(&self.include_regex)].concat(),
+            exclude: [self.exclude.clone(), regex(&self.exclude_regex)].concat(),
+        }
+    }
+
+    /// Builds the options of the FTP client from the command-line arguments.
+    ///
+    /// # Returns
+    ///
+    /// The `FtpOptions` matching the arguments, where zero timeouts mean waiting forever.
+    pub fn ftp_options(&self) -> FtpOptions {
+        let timeout = |duration: Duration| (!duration.is_zero()).then_some(duration);
+
+        FtpOptions {
+            extended: self.extended,
+            hidden: self.all,
+            connect_timeout: timeout(self.connect_timeout),
+            read_timeout: timeout(self.read_timeout),
+            data_timeout: timeout(self.data_timeout),
+            idle_timeout: timeout(self.idle_timeout),
+            keepalive: timeout(self.keepalive),
+            reconnect: ReconnectPolicy {
+                max_attempts: self.reconnect_attempts,
+                initial_delay: self.reconnect_delay,
+                max_delay: self.reconnect_max_delay,
+                ..ReconnectPolicy::default()
+            },
+            filter: self.filter(),
+        }
+    }
+}
+
+/// The subcommands of the program.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Serve a JSON snapshot written by --json over a read-only FTP server.
+    Serve(ServeArgs),
+
+    /// Compare two JSON snapshots written by --json. Exits with 1 when they differ.
+    Diff(DiffArgs),
+
+    /// Print the largest directories of a server, or of a JSON snapshot written by --json.
+    Du(DuArgs),
+
+    /// Print the full remote paths of the entries of a server, or of a JSON snapshot
+    /// written by --json, passing every given test.
+    Find(FindArgs),
+}
+
+/// When to colour the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Colour the output when it is written to a terminal.
+    #[default]
+    Auto,
+
+    /// Always colour the output.
+    Always,
+
+    /// Never colour the output.
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns whether the standard output should be coloured.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the output should be coloured.
+    pub fn enabled(&self) -> bool {
+        match self {
+            ColorChoice::Auto => io::stdout().is_terminal(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// Command-line arguments of the `serve` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct ServeArgs {
+    /// The JSON snapshot to serve.
+    pub snapshot: PathBuf,
+
+    /// The address to listen on.
+    #[arg(short, long, default_value = "127.0.0.1:2121")]
+    pub listen: SocketAddr,
+
+    /// How to make up the content of files, from their recorded size.
+    #[arg(short, long, value_enum, default_value_t = Fill::Zeros)]
+    pub fill: Fill,
+
+    /// The only accepted username. Any login is accepted when not set.
+    #[arg(short, long, requires = "password")]
+    pub username: Option<String>,
+
+    /// The password of the accepted username.
+    #[arg(short, long, requires = "username")]
+    pub password: Option<String>,
+}
+
+impl ServeArgs {
+    /// Builds the options of the FTP server from the command-line arguments.
+    ///
+    /// # Returns
+    ///
+    /// The `ServerOptions` matching the arguments.
+    pub fn server_options(&self) -> ServerOptions {
+        ServerOptions {
+            credentials: self.username.clone().zip(self.password.clone()),
+            fill: self.fill,
+            ..ServerOptions::default()
+        }
+    }
+}
+
+/// Command-line arguments of the `diff` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct DiffArgs {
+    /// The JSON snapshot of the old tree.
+    pub old: PathBuf,
+
+    /// The JSON snapshot of the new tree.
+    pub new: PathBuf,
+
+    /// The format of the report.
+    #[arg(short, long, value_enum, default_value_t = DiffFormat::Text)]
+    pub format: DiffFormat,
+
+    /// When to colour the text report.
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+}
+
+/// Where a subcommand reads its tree from: a crawl of a server, like the main command, or
+/// a JSON snapshot written by --json.
+#[derive(clap::Args, Debug)]
+pub struct SourceArgs {
+    /// The address of the FTP server to crawl.
+    #[arg(index = 1, required_unless_present = "snapshot")]
+    pub address: Option<String>,
+
+    /// Read the tree from this JSON snapshot instead of crawling a server.
+    #[arg(long, value_name = "PATH", conflicts_with = "address")]
+    pub snapshot: Option<PathBuf>,
+
+    /// The username for FTP authentication. Defaults to "anonymous".
+    #[arg(short, long, default_value_t = String::from("anonymous"))]
+    pub username: String,
+
+    /// The password for FTP authentication. Defaults to "anonymous".
+    #[arg(short, long, default_value_t = String::from("anonymous"))]
+    pub password: String,
+
+    /// The depth of directory traversal. Defaults to 1.
+    #[arg(long, default_value_t = 1)]
+    pub depth: usize,
+
+    /// Use extended passive mode for data connections. Defaults to false.
+    #[arg(short, long, default_value_t = false)]
+    pub extended: bool,
+
+    /// List hidden files too, with MLSD when the server supports it or LIST -a otherwise.
+    /// Defaults to false.
+    #[arg(short, long, default_value_t = false)]
+    pub all: bool,
+}
+
+impl SourceArgs {
+    /// Builds the options of the FTP client from the command-line arguments.
+    ///
+    /// # Returns
+    ///
+    /// The `FtpOptions` matching the arguments, with the default timeouts.
+    pub fn ftp_options(&self) -> FtpOptions {
+        FtpOptions {
+            extended: self.extended,
+            hidden: self.all,
+            ..FtpOptions::default()
+        }
+    }
+}
+
+/// Command-line arguments of the `du` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct DuArgs {
+    /// The server or snapshot to measure.
+    #[command(flatten)]
+    pub source: SourceArgs,
+
+    /// The deepest level of directories to report, deeper ones being rolled up into their
+    /// ancestors.
+    #[arg(short = 'd', long, default_value_t = 1)]
+    pub max_depth: usize,
+
+    /// Only print this many of the largest directories.
+    #[arg(short = 'n', long)]
+    pub top: Option<usize>,
+
+    /// Print sizes in bytes rather than with binary units.
+    #[arg(short, long, default_value_t = false)]
+    pub bytes: bool,
+}
+
+/// Command-line arguments of the `find` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct FindArgs {
+    /// The server or snapshot to search.
+    #[command(flatten)]
+    pub source: SourceArgs,
+
+    /// Find the entries whose name matches this glob, such as "*.csv". Matched against
+    /// the full path when it contains a "/". Can be repeated, one glob has to match.
+    #[arg(long, value_name = "GLOB", value_parser = Pattern::glob)]
+    pub name: Vec<Pattern>,
+
+    /// Find the entries whose name matches this regular expression. Can be repeated, one
+    /// regular expression has to match.
+    #[arg(long, value_name = "REGEX", value_parser = Pattern::regex)]
+    pub regex: Vec<Pattern>,
+
+    /// Match --regex against the full remote path of entries instead of their name.
+    /// Defaults to false.
+    #[arg(long, default_value_t = false)]
+    pub regex_full_path: bool,
+
+    /// Find only the entries of this type.
+    #[arg(short = 't', long = "type", value_name = "TYPE", value_enum)]
+    pub node_type: Option<NodeType>,
+
+    /// Find the files larger ("+1M"), smaller ("-10k") or exactly as large ("0") as this
+    /// size. Can be repeated to give a range.
+    #[arg(long, value_name = "SIZE", value_parser = SizeBound::parse, allow_hyphen_values = true)]
+    pub size: Vec<SizeBound>,
+
+    /// Find the entries modified less than this long ago, such as "7d".
+    #[arg(long, value_name = "AGE", value_parser = humantime::parse_duration)]
+    pub newer_than: Option<Duration>,
+
+    /// Find the entries modified more than this long ago, such as "30d".
+    #[arg(long, value_name = "AGE", value_parser = humantime::parse_duration)]
+    pub older_than: Option<Duration>,
+
+    /// Find the entries with exactly these octal permission bits ("644"), all of them
+    /// ("-444") or any of them ("/022").
+    #[arg(long, value_name = "MODE", value_parser = PermissionBits::parse, allow_hyphen_values = true)]
+    pub perm: Option<PermissionBits>,
+
+    /// Find the entries owned by this user.
+    #[arg(long)]
+    pub owner: Option<String>,
+
+    /// Find the entries owned by this group.
+    #[arg(long)]
+    pub group: Option<String>,
+
+    /// Separate the paths with NUL characters rather than newlines, for "xargs -0".
+    #[arg(short = '0', long, default_value_t = false)]
+    pub print0: bool,
+
+    /// Print the entries found as a JSON array, with their type and metadata.
+    #[arg(short, long, default_value_t = false, conflicts_with = "print0")]
+    pub json: bool,
+
+    /// Run this shell command for every entry found, with "{}" replaced by its quoted
+    /// remote path. The path and type are also in the TREE_FTP_PATH and TREE_FTP_TYPE
+    /// environment variables. Exits with 1 when a command fails.
+    #[arg(long, value_name = "COMMAND")]
+    pub exec: Option<String>,
+}
+
+impl FindArgs {
+    /// Builds the query selecting the entries from the command-line arguments.
+    ///
+    /// # Returns
+    ///
+    /// The `Query` holding the tests.
+    pub fn query(&self) -> Query {
+        Query {
+            name: self.name.clone(),
+            regex: self
+                .regex
+                .iter()
+                .map(|pattern| pattern.clone().with_full_path(self.regex_full_path))
+                .collect(),
+            node_type: self.node_type,
+            size: self.size.clone(),
+            newer_than: self.newer_than,
+            older_than: self.older_than,
+            permissions: self.perm,
+            owner: self.owner.clone(),
+            group: self.group.clone(),
+        }
+    }
 }
@@ -0,0 +1,53 @@
+/// Joins a remote directory path and an entry name into a full remote path.
+///
+/// # Arguments
+///
+/// * `parent` - The full path of the parent directory.
+/// * `name` - The name of the entry inside the parent directory.
+///
+/// # Returns
+///
+/// A `String` containing the full remote path of the entry.
+pub fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else if parent.ends_with('/') {
+        format!("{}{}", parent, name)
+    } else {
+        format!("{}/{}", parent, name)
+    }
+}
+
+/// Resolves a path given to a command against the current working directory.
+///
+/// The result is absolute and normalized: `.` components are dropped and `..`
+/// components remove the previous one, never going above the root.
+///
+/// # Arguments
+///
+/// * `cwd` - The absolute path of the current working directory.
+/// * `path` - The absolute or relative path to resolve.
+///
+/// # Returns
+///
+/// A `String` containing the normalized absolute path.
+pub fn resolve(cwd: &str, path: &str) -> String {
+    let joined: String = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        join(cwd, path)
+    };
+    let mut components: Vec<&str> = Vec::new();
+
+    for component in joined.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            _ => components.push(component),
+        }
+    }
+
+    format!("/{}", components.join("/"))
+}
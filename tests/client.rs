@@ -1,38 +1,314 @@
-// // use tree_ftp::ftp::client::FtpClient;
-// // use tree_ftp::ftp::command_stream::MockCommandStream;
-// // use tree_ftp::ftp::data_stream::MockFtpDataStream;
-// // use tree_ftp::ftp::error::Result;
-
-// #[test]
-// fn test_new() {
-//     let addr = "127.0.0.1:21";
-//     let mut mock_stream = MockCommandStream::new();
-//     mock_stream.expect_read_response().returning(|| Ok("220 Welcome".to_string()));
-
-//     let client = FtpClient::new(addr);
-//     assert!(client.is_ok());
-// }
-
-// #[test]
-// fn test_authenticate() {
-//     let addr = "127.0.0.1:21";
-//     let mut mock_stream = MockCommandStream::new();
-//     mock_stream.expect_send_command().returning(|_| Ok(()));
-//     mock_stream.expect_read_response().returning(|| Ok("230 User logged in".to_string()));
-
-//     let mut client = FtpClient::new(addr).unwrap();
-//     let result = client.authenticate("user", "pass");
-//     assert!(result.is_ok());
-// }
-
-// #[test]
-// fn test_retrieve_server_info() {
-//     let addr = "127.0.0.1:21";
-//     let mut mock_stream = MockCommandStream::new();
-//     mock_stream.expect_send_command().returning(|_| Ok(()));
-//     mock_stream.expect_read_response().returning(|| Ok("215 UNIX Type: L8".to_string()));
-
-//     let mut client = FtpClient::new(addr).unwrap();
-//     let result = client.retrieve_server_info();
-//     assert!(result.is_ok());
-// }
\ No newline at end of file
+use mockall::mock;
+use std::{
+    collections::VecDeque,
+    io::{self, Cursor, Read, Write},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tree_ftp::fs::node::{Node, NodeEnum};
+use tree_ftp::ftp::client::FtpClient;
+use tree_ftp::ftp::error::Error;
+use tree_ftp::ftp::options::FtpOptions;
+use tree_ftp::ftp::transport::Transport;
+
+/// A control connection replaying a server transcript and recording the commands sent.
+struct ScriptedStream {
+    replies: Cursor<Vec<u8>>,
+    sent: Arc<Mutex<Vec<u8>>>,
+}
+
+impl ScriptedStream {
+    fn new(replies: &[&str], sent: &Arc<Mutex<Vec<u8>>>) -> Self {
+        let transcript: String = replies.iter().map(|r| format!("{}\r\n", r)).collect();
+
+        ScriptedStream {
+            replies: Cursor::new(transcript.into_bytes()),
+            sent: Arc::clone(sent),
+        }
+    }
+}
+
+impl Read for ScriptedStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.replies.read(buf)
+    }
+}
+
+impl Write for ScriptedStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sent.lock().unwrap().extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+mock! {
+    Transport {}
+
+    impl Transport for Transport {
+        type Control = ScriptedStream;
+        type Data = Cursor<Vec<u8>>;
+
+        fn addr(&self) -> SocketAddr;
+        fn connect_control(&mut self) -> io::Result<ScriptedStream>;
+        fn connect_data(&mut self, addr: SocketAddr) -> io::Result<Cursor<Vec<u8>>>;
+    }
+}
+
+const LOGIN: [&str; 2] = ["331 Password required", "230 Logged in"];
+const SERVER_INFO: [&str; 3] = ["215 UNIX Type: L8", "211 No features", "200 Type set to I"];
+const LISTING: [&str; 4] = [
+    "250 Directory changed",
+    "227 Entering Passive Mode (127,0,0,1,4,1)",
+    "150 Here comes the listing",
+    "226 Transfer complete",
+];
+
+fn transport(controls: Vec<Vec<&'static str>>, sent: &Arc<Mutex<Vec<u8>>>) -> MockTransport {
+    let mut controls: VecDeque<ScriptedStream> = controls
+        .iter()
+        .map(|replies| ScriptedStream::new(replies, sent))
+        .collect();
+    let mut transport: MockTransport = MockTransport::new();
+
+    transport
+        .expect_addr()
+        .returning(|| "127.0.0.1:21".parse().unwrap());
+    transport
+        .expect_connect_control()
+        .returning(move || Ok(controls.pop_front().expect("unexpected control connection")));
+
+    transport
+}
+
+fn options() -> FtpOptions {
+    let mut options: FtpOptions = FtpOptions::default();
+
+    options.reconnect.initial_delay = Duration::ZERO;
+
+    options
+}
+
+fn sent_commands(sent: &Arc<Mutex<Vec<u8>>>) -> String {
+    String::from_utf8(sent.lock().unwrap().clone()).unwrap()
+}
+
+#[test]
+fn test_with_transport_reads_banner() {
+    let sent: Arc<Mutex<Vec<u8>>> = Arc::default();
+    let transport: MockTransport = transport(vec![vec!["220 Welcome"]], &sent);
+
+    let client = FtpClient::with_transport(transport, &"user".into(), &"pass".into(), options());
+
+    assert!(client.is_ok());
+}
+
+#[test]
+fn test_with_transport_rejected_banner() {
+    let sent: Arc<Mutex<Vec<u8>>> = Arc::default();
+    let transport: MockTransport = transport(vec![vec!["530 Go away"]], &sent);
+
+    let client = FtpClient::with_transport(transport, &"user".into(), &"pass".into(), options());
+
+    assert!(matches!(client, Err(Error::ReplyError { .. })));
+}
+
+#[test]
+fn test_authenticate() {
+    let sent: Arc<Mutex<Vec<u8>>> = Arc::default();
+    let transport: MockTransport = transport(vec![[&["220 Welcome"][..], &LOGIN].concat()], &sent);
+    let mut client: FtpClient<MockTransport> =
+        FtpClient::with_transport(transport, &"user".into(), &"pass".into(), options()).unwrap();
+
+    assert!(client
+        .authenticate(&"user".to_string(), &"pass".to_string())
+        .is_ok());
+    assert_eq!(sent_commands(&sent), "USER user\r\nPASS pass\r\n");
+}
+
+#[test]
+fn test_authenticate_bad_login() {
+    let sent: Arc<Mutex<Vec<u8>>> = Arc::default();
+    let transport: MockTransport = transport(
+        vec![vec![
+            "220 Welcome",
+            "331 Password required",
+            "530 Login incorrect",
+        ]],
+        &sent,
+    );
+    let mut client: FtpClient<MockTransport> =
+        FtpClient::with_transport(transport, &"user".into(), &"pass".into(), options()).unwrap();
+
+    let result = client.authenticate(&"user".to_string(), &"wrong".to_string());
+
+    assert!(matches!(result, Err(Error::BadLogin { .. })));
+}
+
+#[test]
+fn test_list_dir() {
+    let sent: Arc<Mutex<Vec<u8>>> = Arc::default();
+    let control: Vec<&str> = [
+        &["220 Welcome"][..],
+        &LOGIN,
+        &SERVER_INFO,
+        &["257 \"/\" is the current directory"],
+        &LISTING,
+        &LISTING,
+    ]
+    .concat();
+    let mut transport: MockTransport = transport(vec![control], &sent);
+    let mut listings: VecDeque<&str> = VecDeque::from([
+        "drwxr-xr-x 2 ftp ftp 4096 Jan 01 00:00 pub\r\n-rw-r--r-- 1 ftp ftp 12 Jan 01 00:00 a b.txt\r\n",
+        "-rw-r--r-- 1 ftp ftp 3 Jan 01 00:00 c.txt\r\n",
+    ]);
+
+    transport
+        .expect_connect_data()
+        .withf(|addr| *addr == "127.0.0.1:1025".parse().unwrap())
+        .times(2)
+        .returning(move |_| Ok(Cursor::new(listings.pop_front().unwrap().into())));
+
+    let mut client: FtpClient<MockTransport> =
+        FtpClient::with_transport(transport, &"user".into(), &"pass".into(), options()).unwrap();
+    let root: Vec<NodeEnum> = match client.list_dir(2, false).unwrap() {
+        NodeEnum::Directory(root) => root.nodes,
+        NodeEnum::File(_) => panic!("the root is a directory"),
+    };
+
+    assert_eq!(root.len(), 2);
+    assert_eq!(root[0].path(), "/pub");
+    assert_eq!(root[1].name(), "a b.txt");
+
+    match &root[0] {
+        NodeEnum::Directory(pub_dir) => assert_eq!(pub_dir.nodes[0].path(), "/pub/c.txt"),
+        NodeEnum::File(_) => panic!("pub is a directory"),
+    }
+
+    let commands: String = sent_commands(&sent);
+
+    assert!(commands.contains("CWD /\r\nPASV\r\nLIST\r\n"));
+    assert!(commands.contains("CWD /pub\r\nPASV\r\nLIST\r\n"));
+}
+
+#[test]
+fn test_list_dir_resumes_after_reconnect() {
+    let sent: Arc<Mutex<Vec<u8>>> = Arc::default();
+    let first: Vec<&str> = [
+        &["220 Welcome"][..],
+        &LOGIN,
+        &SERVER_INFO,
+        &["257 \"/home\" is the current directory"],
+        &["421 Timeout"],
+    ]
+    .concat();
+    let second: Vec<&str> = [
+        &["220 Welcome"][..],
+        &LOGIN,
+        &[
+            "200 Type set to I",
+            "257 \"/home\" is the current directory",
+        ],
+        &LISTING,
+    ]
+    .concat();
+    let mut transport: MockTransport = transport(vec![first, second], &sent);
+
+    transport.expect_connect_data().times(1).returning(|_| {
+        Ok(Cursor::new(
+            b"-rw-r--r-- 1 ftp ftp 3 Jan 01 00:00 x\r\n".to_vec(),
+        ))
+    });
+
+    let mut client: FtpClient<MockTransport> =
+        FtpClient::with_transport(transport, &"user".into(), &"pass".into(), options()).unwrap();
+    let root: NodeEnum = client.list_dir(1, false).unwrap();
+
+    match root {
+        NodeEnum::Directory(root) => assert_eq!(root.nodes[0].path(), "/home/x"),
+        NodeEnum::File(_) => panic!("the root is a directory"),
+    }
+
+    assert_eq!(sent_commands(&sent).matches("CWD /home\r\n").count(), 2);
+}
+
+#[test]
+fn test_list_dir_keepalive_and_quit() {
+    let sent: Arc<Mutex<Vec<u8>>> = Arc::default();
+    let control: Vec<&str> = [
+        &["220 Welcome"][..],
+        &LOGIN,
+        &SERVER_INFO,
+        &["257 \"/\" is the current directory"],
+        &LISTING,
+        &["200 NOOP ok", "200 NOOP ok"],
+    ]
+    .concat();
+    let mut transport: MockTransport = transport(vec![control], &sent);
+
+    transport.expect_connect_data().times(1).returning(|_| {
+        Ok(Cursor::new(
+            b"-rw-r--r-- 1 ftp ftp 3 Jan 01 00:00 x\r\n-rw-r--r-- 1 ftp ftp 3 Jan 01 00:00 y\r\n"
+                .to_vec(),
+        ))
+    });
+
+    let options: FtpOptions = FtpOptions {
+        keepalive: Some(Duration::ZERO),
+        ..options()
+    };
+    let mut client: FtpClient<MockTransport> =
+        FtpClient::with_transport(transport, &"user".into(), &"pass".into(), options).unwrap();
+    let root: NodeEnum = client.list_dir(1, false).unwrap();
+
+    drop(client);
+
+    match root {
+        NodeEnum::Directory(root) => assert_eq!(root.nodes.len(), 2),
+        NodeEnum::File(_) => panic!("the root is a directory"),
+    }
+
+    assert!(sent_commands(&sent).ends_with("LIST\r\nNOOP\r\nNOOP\r\nQUIT\r\n"));
+}
+
+#[test]
+fn test_list_dir_falls_back_when_list_a_is_ignored() {
+    let sent: Arc<Mutex<Vec<u8>>> = Arc::default();
+    let control: Vec<&str> = [
+        &["220 Welcome"][..],
+        &LOGIN,
+        &SERVER_INFO,
+        &["257 \"/\" is the current directory"],
+        &LISTING,
+        &LISTING,
+    ]
+    .concat();
+    let mut transport: MockTransport = transport(vec![control], &sent);
+
+    transport.expect_connect_data().times(2).returning(|_| {
+        Ok(Cursor::new(
+            b"-rw-r--r-- 1 ftp ftp 3 Jan 01 00:00 x\r\n".to_vec(),
+        ))
+    });
+
+    let options: FtpOptions = FtpOptions {
+        hidden: true,
+        ..options()
+    };
+    let mut client: FtpClient<MockTransport> =
+        FtpClient::with_transport(transport, &"user".into(), &"pass".into(), options).unwrap();
+    let root: NodeEnum = client.list_dir(1, false).unwrap();
+
+    match root {
+        NodeEnum::Directory(root) => assert_eq!(root.nodes.len(), 1),
+        NodeEnum::File(_) => panic!("the root is a directory"),
+    }
+
+    assert!(!client.supports("MLSD"));
+    assert!(sent_commands(&sent).contains("LIST -a\r\nCWD /\r\nPASV\r\nLIST\r\n"));
+}
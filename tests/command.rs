@@ -0,0 +1,28 @@
+use tree_ftp::ftp::command::FtpCommand;
+
+#[test]
+fn test_command_to_line() {
+    assert_eq!(
+        FtpCommand::Cwd("/my dir".to_string()).to_line(),
+        "CWD /my dir"
+    );
+    assert_eq!(
+        FtpCommand::Pass("secret".to_string()).to_line(),
+        "PASS secret"
+    );
+    assert_eq!(FtpCommand::Noop.to_line(), "NOOP");
+    assert_eq!(FtpCommand::List(None).to_line(), "LIST");
+    assert_eq!(
+        FtpCommand::List(Some("-a /pub".to_string())).to_line(),
+        "LIST -a /pub"
+    );
+}
+
+#[test]
+fn test_command_display_redacts_password() {
+    assert_eq!(
+        FtpCommand::Pass("secret".to_string()).to_string(),
+        "PASS ****"
+    );
+    assert_eq!(FtpCommand::User("one".to_string()).to_string(), "USER one");
+}
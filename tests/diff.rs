@@ -0,0 +1,125 @@
+use tree_ftp::fs::diff::{diff, to_json, to_text, to_unified, Change, ChangeKind};
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::metadata::Metadata;
+use tree_ftp::fs::node::NodeEnum;
+
+fn file(path: &str, size: u64) -> File {
+    let name: &str = path.rsplit('/').next().unwrap();
+    let mut file: File = File::with_path(name.to_string(), path.to_string());
+
+    file.metadata.size = Some(size);
+    file.metadata.permissions = Some("-rw-r--r--".to_string());
+
+    file
+}
+
+fn old_tree() -> Directory {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/".to_string());
+    let mut public: Directory = Directory::with_path("pub".to_string(), "/pub".to_string());
+    let mut logs: Directory = Directory::with_path("logs".to_string(), "/logs".to_string());
+
+    public.add(file("/pub/readme.txt", 6));
+    public.add(file("/pub/data.bin", 10));
+    logs.add(file("/logs/1.log", 1));
+    root.add(public);
+    root.add(logs);
+    root.add(file("/swap", 0));
+
+    root
+}
+
+fn new_tree() -> Directory {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/home".to_string());
+    let mut public: Directory = Directory::with_path("pub".to_string(), "/home/pub".to_string());
+
+    public.add(file("/home/pub/readme.txt", 6));
+    public.add(file("/home/pub/data.bin", 12));
+    public.add(file("/home/pub/new.txt", 1));
+    root.add(public);
+    root.add(Directory::with_path(
+        "swap".to_string(),
+        "/home/swap".to_string(),
+    ));
+
+    root
+}
+
+#[test]
+fn test_diff_identical_trees() {
+    assert!(diff(&old_tree(), &old_tree()).is_empty());
+}
+
+#[test]
+fn test_diff_reports_every_kind_of_change() {
+    let changes: Vec<Change> = diff(&old_tree(), &new_tree());
+    let summary: Vec<(ChangeKind, &str)> = changes
+        .iter()
+        .map(|change| (change.kind, change.path.as_str()))
+        .collect();
+
+    assert_eq!(
+        summary,
+        vec![
+            (ChangeKind::Removed, "/logs"),
+            (ChangeKind::Removed, "/logs/1.log"),
+            (ChangeKind::Modified, "/pub/data.bin"),
+            (ChangeKind::Added, "/pub/new.txt"),
+            (ChangeKind::TypeChanged, "/swap"),
+        ]
+    );
+    assert_eq!(changes[2].fields[0].field, "size");
+    assert_eq!(changes[2].fields[0].old, "10");
+    assert_eq!(changes[2].fields[0].new, "12");
+}
+
+#[test]
+fn test_diff_ignores_unknown_metadata() {
+    let old: Directory = old_tree();
+    let mut new: Directory = old_tree();
+
+    if let NodeEnum::File(swap) = &mut new.nodes[2] {
+        swap.metadata = Metadata::default();
+    }
+
+    assert!(diff(&old, &new).is_empty());
+}
+
+#[test]
+fn test_diff_compares_modification_times_as_times() {
+    let mut old: Directory = old_tree();
+    let mut new: Directory = old_tree();
+
+    if let (NodeEnum::File(old_swap), NodeEnum::File(new_swap)) =
+        (&mut old.nodes[2], &mut new.nodes[2])
+    {
+        old_swap.metadata.modified = Some("Jun 01 2020".to_string());
+        new_swap.metadata.modified = Some("20200601120000".to_string());
+    }
+
+    assert!(diff(&old, &new).is_empty());
+
+    if let NodeEnum::File(new_swap) = &mut new.nodes[2] {
+        new_swap.metadata.modified = Some("Jun 02 2020".to_string());
+    }
+
+    assert_eq!(diff(&old, &new)[0].fields[0].field, "modified");
+}
+
+#[test]
+fn test_diff_reports() {
+    let changes: Vec<Change> = diff(&old_tree(), &new_tree());
+    let text: String = to_text(&changes, false);
+    let unified: String = to_unified(&changes, "old.json", "new.json");
+    let json: serde_json::Value = serde_json::from_str(&to_json(&changes)).unwrap();
+
+    assert!(text.contains("~ /pub/data.bin  (size 10 -> 12)\n"));
+    assert!(text.contains("! /swap/  (type file -> directory)\n"));
+    assert!(text.ends_with("1 added, 2 removed, 1 modified, 1 type changed"));
+    assert!(unified.starts_with("--- old.json\n+++ new.json\n"));
+    assert!(unified
+        .contains("-/pub/data.bin\tsize=10\tpermissions=-rw-r--r--\n+/pub/data.bin\tsize=12\t"));
+    assert_eq!(json["summary"]["removed"], 2);
+    assert_eq!(json["changes"][3]["kind"], "added");
+    assert_eq!(json["changes"][3]["new"]["type"], "file");
+}
@@ -1,6 +1,6 @@
 use tree_ftp::fs::directory::Directory;
 use tree_ftp::fs::file::File;
-use tree_ftp::fs::node::Node;
+use tree_ftp::fs::node::{Node, TraversalType};
 
 #[test]
 fn test_directory_new() {
@@ -42,3 +42,36 @@ fn test_directory_to_string_bfs() {
 
     assert!(dir_str.contains("test_file"));
 }
+
+#[test]
+fn test_directory_with_path() {
+    let dir: Directory = Directory::with_path("data".to_string(), "/pub/data".to_string());
+
+    assert_eq!(dir.name(), "data");
+    assert_eq!(dir.path(), "/pub/data");
+}
+
+#[test]
+fn test_directory_to_string_flat() {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/".to_string());
+    let mut data: Directory = Directory::with_path("data".to_string(), "/data".to_string());
+    let mut file: File = File::with_path("a.csv".to_string(), "/data/a.csv".to_string());
+
+    file.metadata.size = Some(42);
+    file.metadata.modified = Some("20250101000000".to_string());
+    data.add(file);
+    root.add(data);
+    root.add(File::with_path(
+        "my file".to_string(),
+        "/my file".to_string(),
+    ));
+
+    assert_eq!(
+        root.to_string_flat(TraversalType::DFS, false),
+        "/data\n/data/a.csv\n/my file\n"
+    );
+    assert_eq!(
+        root.to_string_flat(TraversalType::BFS, true),
+        "d\t-\t-\t/data\nf\t-\t-\t/my file\nf\t42\t20250101000000\t/data/a.csv\n"
+    );
+}
@@ -0,0 +1,62 @@
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::dot::Dot;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::format::TreeFormatter;
+
+fn tree() -> Directory {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/".to_string());
+    let mut drop: Directory = Directory::with_path("drop".to_string(), "/drop".to_string());
+    let mut inbox: Directory = Directory::with_path("inbox".to_string(), "/drop/inbox".to_string());
+    let mut file: File = File::with_path(
+        "a \"b\".csv".to_string(),
+        "/drop/inbox/a \"b\".csv".to_string(),
+    );
+
+    file.metadata.size = Some(2048);
+    inbox.add(file);
+    drop.add(inbox);
+    root.add(drop);
+
+    root
+}
+
+#[test]
+fn test_dot_edges() {
+    let graph: String = Dot {
+        max_depth: Some(2),
+        sizes: true,
+        ..Dot::default()
+    }
+    .format(&tree());
+
+    assert!(graph.starts_with("digraph tree {\n"));
+    assert!(graph.ends_with("}\n"));
+    assert!(graph.contains(
+        "  n1 [label=\"drop\\n2.0 KiB in 1 file\", tooltip=\"/drop\", shape=folder];\n  n0 -> n1;\n"
+    ));
+    assert!(graph.contains("n1 -> n2;"));
+    assert!(!graph.contains("a \\\"b\\\".csv"));
+    assert!(Dot::default()
+        .format(&tree())
+        .contains("  n3 [label=\"a \\\"b\\\".csv\", tooltip=\"/drop/inbox/a \\\"b\\\".csv\", shape=note];\n  n2 -> n3;\n"));
+}
+
+#[test]
+fn test_dot_clusters() {
+    let graph: String = Dot {
+        cluster: true,
+        ..Dot::default()
+    }
+    .format(&tree());
+
+    assert_eq!(graph.matches("subgraph cluster_").count(), 3);
+    assert!(graph.contains("      subgraph cluster_2 {\n        label=\"inbox\";\n"));
+    assert!(!graph.contains("->"));
+    assert!(Dot {
+        cluster: true,
+        max_depth: Some(1),
+        ..Dot::default()
+    }
+    .format(&tree())
+    .contains("style=invis"));
+}
@@ -0,0 +1,92 @@
+use std::error::Error as _;
+use std::io;
+
+use tree_ftp::error::{self, ErrorClass};
+use tree_ftp::ftp::error::Error;
+use tree_ftp::ftp::reply::Reply;
+
+#[test]
+fn test_reply_error_carries_command_and_reply() {
+    let error: Error = Error::ReplyError {
+        command: "CWD /pub".to_string(),
+        reply: Reply::new(550, vec!["No such directory.".to_string()]).unwrap(),
+    };
+
+    assert_eq!(error.to_string(), "CWD /pub failed: 550 No such directory.");
+    assert_eq!(error.class(), ErrorClass::Protocol);
+}
+
+#[test]
+fn test_transient_reply_error_has_its_own_exit_code() {
+    let error: Error = Error::ReplyError {
+        command: "LIST".to_string(),
+        reply: Reply::new(450, vec!["Busy.".to_string()]).unwrap(),
+    };
+
+    assert_eq!(error.class(), ErrorClass::TransientReply);
+    assert_eq!(error.exit_code(), 75);
+}
+
+#[test]
+fn test_bad_login_exit_code() {
+    let error: Error = Error::BadLogin {
+        username: "one".to_string(),
+        reply: Reply::new(530, vec!["Login incorrect.".to_string()]).unwrap(),
+    };
+
+    assert_eq!(error.exit_code(), 77);
+}
+
+#[test]
+fn test_read_error_exposes_io_source() {
+    let error: Error = Error::ReadError {
+        context: "data connection",
+        source: io::Error::new(io::ErrorKind::TimedOut, "timed out"),
+    };
+
+    assert_eq!(error.to_string(), "could not read from the data connection");
+    assert_eq!(error.source().unwrap().to_string(), "timed out");
+    assert_eq!(error.exit_code(), 74);
+}
+
+#[test]
+fn test_list_error_keeps_path_and_class_of_cause() {
+    let error: Error = Error::ListError {
+        path: "/pub/data".to_string(),
+        source: Box::new(Error::ConnectionLost {
+            reason: "connection closed by the server".to_string(),
+            source: None,
+        }),
+    };
+
+    assert_eq!(error.to_string(), "could not list /pub/data");
+    assert_eq!(
+        error.source().unwrap().to_string(),
+        "connection lost: connection closed by the server"
+    );
+    assert_eq!(error.class(), ErrorClass::Connection);
+}
+
+#[test]
+fn test_crate_error_wraps_ftp_errors() {
+    let error: error::Error = Error::BadLogin {
+        username: "one".to_string(),
+        reply: Reply::new(530, vec!["Login incorrect.".to_string()]).unwrap(),
+    }
+    .into();
+
+    assert_eq!(
+        error.to_string(),
+        "login as one refused: 530 Login incorrect."
+    );
+    assert_eq!(error.class(), ErrorClass::Authentication);
+
+    let error: error::Error = error::Error::OutputError {
+        path: "tree.json".to_string(),
+        source: io::Error::new(io::ErrorKind::PermissionDenied, "permission denied"),
+    };
+
+    assert_eq!(error.to_string(), "could not write tree.json");
+    assert_eq!(error.source().unwrap().to_string(), "permission denied");
+    assert_eq!(error.exit_code(), 73);
+}
@@ -1,4 +1,4 @@
-use tree_ftp::fs::{file::File, node::Node};
+use tree_ftp::fs::{file::File, metadata::Metadata, node::Node};
 
 #[test]
 fn test_file_new() {
@@ -6,3 +6,15 @@ fn test_file_new() {
 
     assert_eq!(file.name(), "test_file");
 }
+
+#[test]
+fn test_metadata_from_mlsd_facts() {
+    let metadata: Metadata = Metadata::from_mlsd_facts(
+        "modify=20250101120000;perm=r;size=42;type=file;UNIX.mode=0640;UNIX.owner=ftp;",
+    );
+
+    assert_eq!(metadata.size, Some(42));
+    assert_eq!(metadata.modified.as_deref(), Some("20250101120000"));
+    assert_eq!(metadata.permissions.as_deref(), Some("-rw-r-----"));
+    assert_eq!(metadata.owner.as_deref(), Some("ftp"));
+}
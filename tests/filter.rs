@@ -0,0 +1,136 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+use tree_ftp::error::Error;
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::filter::{Filter, Pattern};
+use tree_ftp::fs::node::{Node, NodeEnum};
+use tree_ftp::ftp::client::FtpClient;
+use tree_ftp::ftp::options::FtpOptions;
+use tree_ftp::ftp::record::RecordingTransport;
+use tree_ftp::ftp::transport::TcpTransport;
+use tree_ftp::server::ftp_server::FtpServer;
+
+/// A recording output shared with the test.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn tree() -> Directory {
+    let mut modules: Directory = Directory::new("node_modules".to_string());
+    let mut data: Directory = Directory::new("data".to_string());
+    let mut root: Directory = Directory::new("/".to_string());
+
+    modules.add(File::new("index.js".to_string()));
+    data.add(File::new("sales.csv".to_string()));
+    data.add(File::new("sales.tmp".to_string()));
+    root.add(modules);
+    root.add(data);
+    root.add(File::new("notes.txt".to_string()));
+
+    root
+}
+
+fn paths(dir: &Directory) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+
+    for node in &dir.nodes {
+        found.push(node.path().to_string());
+
+        if let NodeEnum::Directory(subdir) = node {
+            found.extend(paths(subdir));
+        }
+    }
+
+    found
+}
+
+#[test]
+fn test_pattern_matches_name_or_path() {
+    let name: Pattern = Pattern::glob("*.csv").unwrap();
+    let path: Pattern = Pattern::glob("/data/*").unwrap();
+    let regex: Pattern = Pattern::regex(r"^\.git$").unwrap();
+
+    assert!(name.matches("sales.csv", "/data/sales.csv"));
+    assert!(path.matches("sales.csv", "/data/sales.csv"));
+    assert!(!path.matches("a.csv", "/data/deep/a.csv"));
+    assert!(regex.matches(".git", "/.git"));
+    assert!(!regex.matches(".github", "/.github"));
+
+    let name_regex: Pattern = Pattern::regex(r"^[^/]*\.csv$").unwrap();
+    let path_regex: Pattern = name_regex.clone().with_full_path(true);
+
+    assert!(name_regex.matches("a.csv", "/data/a.csv"));
+    assert!(!path_regex.matches("a.csv", "/data/a.csv"));
+    assert!(path_regex.matches("a.csv", "a.csv"));
+    assert!(matches!(
+        Pattern::regex("("),
+        Err(Error::InvalidPattern { .. })
+    ));
+}
+
+#[test]
+fn test_filter_keeps() {
+    let filter: Filter = Filter {
+        include: vec![Pattern::glob("*.csv").unwrap()],
+        exclude: vec![Pattern::glob("/data/old").unwrap()],
+    };
+    let node = |path: &str, is_dir: bool| -> NodeEnum {
+        let name: String = path.rsplit('/').next().unwrap().to_string();
+
+        if is_dir {
+            NodeEnum::Directory(Directory::with_path(name, path.to_string()))
+        } else {
+            NodeEnum::File(File::with_path(name, path.to_string()))
+        }
+    };
+
+    assert!(filter.keeps(&node("/data", true)));
+    assert!(filter.keeps(&node("/data/a.csv", false)));
+    assert!(!filter.keeps(&node("/data/b.tmp", false)));
+    assert!(!filter.keeps(&node("/data/old", true)));
+}
+
+#[test]
+fn test_crawl_prunes_excluded_directories() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let log: SharedBuffer = SharedBuffer::default();
+    let options: FtpOptions = FtpOptions {
+        filter: Filter {
+            include: vec![Pattern::glob("*.csv").unwrap()],
+            exclude: vec![Pattern::regex(r"^node_modules$").unwrap()],
+        },
+        ..FtpOptions::default()
+    };
+    let transport: RecordingTransport<TcpTransport> = RecordingTransport::new(
+        TcpTransport::new(server.addr(), &options),
+        Box::new(log.clone()),
+    );
+    let mut client: FtpClient<RecordingTransport<TcpTransport>> =
+        FtpClient::with_transport(transport, &"user".into(), &"pass".into(), options).unwrap();
+
+    let NodeEnum::Directory(root) = client.list_dir(3, false).unwrap() else {
+        panic!("the root is a directory");
+    };
+
+    drop(client);
+
+    let recording: String = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+
+    assert_eq!(paths(&root), vec!["/data", "/data/sales.csv"]);
+    assert!(recording.contains("CWD /data"));
+    assert!(!recording.contains("CWD /node_modules"));
+}
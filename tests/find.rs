@@ -0,0 +1,145 @@
+use std::time::{Duration, SystemTime};
+use tree_ftp::error::Error;
+use tree_ftp::fs::diff::NodeType;
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::filter::Pattern;
+use tree_ftp::fs::find::{
+    exec, find, Comparison, Match, PermissionBits, PermissionMatch, Query, SizeBound,
+};
+use tree_ftp::fs::metadata::Metadata;
+use tree_ftp::fs::node::NodeEnum;
+
+fn file(path: &str, size: u64, modified: &str, permissions: &str, owner: &str) -> File {
+    let name: &str = path.rsplit('/').next().unwrap();
+    let mut file: File = File::with_path(name.to_string(), path.to_string());
+
+    file.metadata = Metadata {
+        size: Some(size),
+        modified: Some(modified.to_string()),
+        permissions: Some(permissions.to_string()),
+        owner: Some(owner.to_string()),
+        group: None,
+    };
+
+    file
+}
+
+fn tree() -> Directory {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/".to_string());
+    let mut logs: Directory = Directory::with_path("logs".to_string(), "/logs".to_string());
+
+    logs.add(file(
+        "/logs/old.log",
+        2 << 20,
+        "20240101000000",
+        "-rw-------",
+        "root",
+    ));
+    logs.add(file(
+        "/logs/new.log",
+        10,
+        "20250601000000",
+        "-rw-r--r--",
+        "ftp",
+    ));
+    root.add(logs);
+    root.add(file("/readme.txt", 100, "Jan 01 2025", "-rwxr-xr-x", "ftp"));
+
+    root
+}
+
+fn paths(found: &[Match]) -> Vec<&str> {
+    found.iter().map(|found| found.path.as_str()).collect()
+}
+
+#[test]
+fn test_parse_size_and_permissions() {
+    assert_eq!(
+        SizeBound::parse("+1M").unwrap(),
+        SizeBound {
+            comparison: Comparison::Greater,
+            bytes: 1 << 20,
+        }
+    );
+    assert_eq!(SizeBound::parse("-10KiB").unwrap().bytes, 10 << 10);
+    assert_eq!(SizeBound::parse("0").unwrap().comparison, Comparison::Equal);
+    assert!(matches!(
+        SizeBound::parse("+1x"),
+        Err(Error::InvalidPredicate { .. })
+    ));
+
+    let any: PermissionBits = PermissionBits::parse("/022").unwrap();
+
+    assert_eq!(any.kind, PermissionMatch::Any);
+    assert!(any.matches("drwxrwxr-x"));
+    assert!(!any.matches("-rw-r--r--"));
+    assert!(PermissionBits::parse("-444").unwrap().matches("-rwxr--r--"));
+    assert!(PermissionBits::parse("755").unwrap().matches("-rwsr-xr-x"));
+    assert!(PermissionBits::parse("999").is_err());
+}
+
+#[test]
+fn test_query_tests() {
+    let now: SystemTime = humantime::parse_rfc3339("2025-06-02T00:00:00Z").unwrap();
+    let root: Directory = tree();
+    let NodeEnum::Directory(logs) = &root.nodes[0] else {
+        panic!("the first entry is a directory");
+    };
+    let (old, new): (&NodeEnum, &NodeEnum) = (&logs.nodes[0], &logs.nodes[1]);
+    let query = |query: Query| (query.matches(old, now), query.matches(new, now));
+
+    assert_eq!(
+        query(Query {
+            size: vec![SizeBound::parse("+1M").unwrap()],
+            ..Query::default()
+        }),
+        (true, false)
+    );
+    assert_eq!(
+        query(Query {
+            newer_than: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+            ..Query::default()
+        }),
+        (false, true)
+    );
+    assert_eq!(
+        query(Query {
+            older_than: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+            permissions: Some(PermissionBits::parse("600").unwrap()),
+            owner: Some("root".to_string()),
+            ..Query::default()
+        }),
+        (true, false)
+    );
+    assert!(!Query {
+        size: vec![SizeBound::parse("-1M").unwrap()],
+        ..Query::default()
+    }
+    .matches(&root.nodes[0], now));
+}
+
+#[test]
+fn test_find_walks_the_tree() {
+    let root: Directory = tree();
+    let logs: Vec<Match> = find(
+        &root,
+        &Query {
+            name: vec![Pattern::glob("*.log").unwrap()],
+            ..Query::default()
+        },
+    );
+    let dirs: Vec<Match> = find(
+        &root,
+        &Query {
+            node_type: Some(NodeType::Directory),
+            ..Query::default()
+        },
+    );
+
+    assert_eq!(paths(&logs), vec!["/logs/old.log", "/logs/new.log"]);
+    assert_eq!(paths(&dirs), vec!["/logs"]);
+    assert_eq!(paths(&find(&root, &Query::default())).len(), 4);
+    assert!(exec(r#"test {} = "$TREE_FTP_PATH""#, &logs[0]));
+    assert!(!exec("test {} = /other", &logs[0]));
+}
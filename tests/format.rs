@@ -0,0 +1,76 @@
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::format::{OutputFormat, TreeFormatter};
+use tree_ftp::fs::node::NodeEnum;
+use tree_ftp::fs::snapshot;
+
+fn tree() -> Directory {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/".to_string());
+    let mut data: Directory = Directory::with_path("data".to_string(), "/data".to_string());
+    let mut file: File = File::with_path(
+        "a, \"b\" <c>.csv".to_string(),
+        "/data/a, \"b\" <c>.csv".to_string(),
+    );
+
+    file.metadata.size = Some(2048);
+    data.add(file);
+    root.add(data);
+    root.add(File::with_path("true".to_string(), "/true".to_string()));
+
+    root
+}
+
+fn format(format: OutputFormat) -> String {
+    format.formatter().format(&tree())
+}
+
+/// A formatter printing the number of entries, as a library user would write one.
+struct Count;
+
+impl TreeFormatter for Count {
+    fn format(&self, root: &Directory) -> String {
+        format!("{}\n", root.usage().files + root.usage().dirs)
+    }
+}
+
+#[test]
+fn test_json_round_trips() {
+    let root: Directory = snapshot::from_json(&format(OutputFormat::Json)).unwrap();
+
+    assert_eq!(root.nodes.len(), 2);
+    assert!(matches!(&root.nodes[0], NodeEnum::Directory(dir) if dir.nodes.len() == 1));
+}
+
+#[test]
+fn test_csv_quotes_fields() {
+    assert_eq!(
+        format(OutputFormat::Csv),
+        "path,type,depth,size,modified,permissions,owner,group\n\
+         /data,directory,1,,,,,\n\
+         \"/data/a, \"\"b\"\" <c>.csv\",file,2,2048,,,,\n\
+         /true,file,1,,,,,\n"
+    );
+}
+
+#[test]
+fn test_markup_formats_escape_names() {
+    let xml: String = format(OutputFormat::Xml);
+
+    assert!(xml.contains(r#"<file name="a, &quot;b&quot; &lt;c&gt;.csv" path="#));
+    assert!(xml.contains("<directories>1</directories>\n    <files>2</files>"));
+}
+
+#[test]
+fn test_text_formats() {
+    assert_eq!(
+        format(OutputFormat::Markdown),
+        "# /\n\n- **data/**\n  - a, \"b\" \\<c\\>.csv (2.0 KiB)\n- true\n"
+    );
+    assert!(format(OutputFormat::Yaml).contains(
+        "children:\n  - name: \"data\"\n    path: \"/data\"\n    type: directory\n    children:\n      - name:"
+    ));
+    assert!(format(OutputFormat::Yaml)
+        .ends_with("  - name: \"true\"\n    path: \"/true\"\n    type: file\n"));
+    assert_eq!(format(OutputFormat::Jsonl).lines().count(), 3);
+    assert_eq!(Count.format(&tree()), "3\n");
+}
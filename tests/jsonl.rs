@@ -0,0 +1,44 @@
+use serde_json::Value;
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::jsonl::JsonLinesWriter;
+use tree_ftp::fs::node::NodeEnum;
+
+#[test]
+fn test_json_lines_writer() {
+    let mut file: File = File::with_path("a.csv".to_string(), "/data/a.csv".to_string());
+    let mut writer: JsonLinesWriter<Vec<u8>> = JsonLinesWriter::new(Vec::new());
+
+    file.metadata.size = Some(42);
+    writer
+        .write(
+            &NodeEnum::Directory(Directory::with_path(
+                "data".to_string(),
+                "/data".to_string(),
+            )),
+            1,
+        )
+        .unwrap();
+    writer.write(&NodeEnum::File(file), 2).unwrap();
+
+    let output: String = String::from_utf8(writer.into_inner()).unwrap();
+    let lines: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert!(output.ends_with('\n'));
+    assert_eq!(
+        lines[0],
+        serde_json::json!({"path": "/data", "type": "directory", "depth": 1})
+    );
+    assert_eq!(
+        lines[1],
+        serde_json::json!({
+            "path": "/data/a.csv",
+            "type": "file",
+            "depth": 2,
+            "metadata": {"size": 42},
+        })
+    );
+}
@@ -0,0 +1,31 @@
+use serde_json::Value;
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::ncdu::to_ncdu_json;
+
+#[test]
+fn test_ncdu_export() {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/home".to_string());
+    let mut public: Directory = Directory::with_path("pub".to_string(), "/home/pub".to_string());
+    let mut file: File = File::with_path("a.txt".to_string(), "/home/pub/a.txt".to_string());
+
+    file.metadata.size = Some(3);
+    public.add(file);
+    root.add(public);
+    root.add(File::with_path(
+        "unknown".to_string(),
+        "/home/unknown".to_string(),
+    ));
+
+    let export: Value = serde_json::from_str(&to_ncdu_json(&root)).unwrap();
+
+    assert_eq!(export[0], 1);
+    assert_eq!(export[1], 2);
+    assert_eq!(export[2]["progname"], "tree-ftp");
+    assert_eq!(export[3][0]["name"], "/home");
+    assert_eq!(export[3][1][0]["name"], "pub");
+    assert_eq!(export[3][1][1]["asize"], 3);
+    assert_eq!(export[3][1][1]["dsize"], 3);
+    assert_eq!(export[3][2]["name"], "unknown");
+    assert!(export[3][2].get("asize").is_none());
+}
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use tree_ftp::ftp::options::ReconnectPolicy;
+
+#[test]
+fn test_reconnect_delay_backs_off_exponentially() {
+    let policy: ReconnectPolicy = ReconnectPolicy {
+        max_attempts: 5,
+        initial_delay: Duration::from_millis(500),
+        max_delay: Duration::from_secs(60),
+        multiplier: 2,
+    };
+
+    assert_eq!(policy.delay(0), Duration::from_millis(500));
+    assert_eq!(policy.delay(1), Duration::from_secs(1));
+    assert_eq!(policy.delay(3), Duration::from_secs(4));
+}
+
+#[test]
+fn test_reconnect_delay_is_capped() {
+    let policy: ReconnectPolicy = ReconnectPolicy {
+        max_attempts: 100,
+        initial_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(30),
+        multiplier: 2,
+    };
+
+    assert_eq!(policy.delay(10), Duration::from_secs(30));
+    assert_eq!(policy.delay(99), Duration::from_secs(30));
+}
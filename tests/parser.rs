@@ -0,0 +1,109 @@
+use clap::{CommandFactory, Parser};
+use std::path::PathBuf;
+
+use tree_ftp::fs::format::OutputFormat;
+use tree_ftp::utils::parser::{Args, Command, SourceArgs};
+
+#[test]
+fn test_arguments_are_consistent() {
+    Args::command().debug_assert();
+}
+
+#[test]
+fn test_du_crawls_a_server_or_reads_a_snapshot() {
+    let source = |arguments: &[&str]| -> Option<SourceArgs> {
+        match Args::try_parse_from(arguments).ok()?.command {
+            Some(Command::Du(args)) => Some(args.source),
+            _ => None,
+        }
+    };
+
+    let crawl: SourceArgs = source(&["tree-ftp", "du", "ftp.example.com", "--depth", "3"]).unwrap();
+
+    assert_eq!(crawl.address.as_deref(), Some("ftp.example.com"));
+    assert_eq!((crawl.depth, crawl.snapshot), (3, None));
+
+    let snapshot: SourceArgs = source(&["tree-ftp", "du", "--snapshot", "tree.json"]).unwrap();
+
+    assert_eq!(snapshot.address, None);
+    assert_eq!(snapshot.snapshot, Some(PathBuf::from("tree.json")));
+    assert!(source(&["tree-ftp", "du"]).is_none());
+    assert!(source(&[
+        "tree-ftp",
+        "du",
+        "ftp.example.com",
+        "--snapshot",
+        "tree.json"
+    ])
+    .is_none());
+}
+
+#[test]
+fn test_find_crawls_a_server_or_reads_a_snapshot() {
+    let source = |arguments: &[&str]| -> Option<SourceArgs> {
+        match Args::try_parse_from(arguments).ok()?.command {
+            Some(Command::Find(args)) => Some(args.source),
+            _ => None,
+        }
+    };
+
+    let crawl: SourceArgs = source(&["tree-ftp", "find", "ftp.example.com", "-a"]).unwrap();
+
+    assert_eq!(crawl.address.as_deref(), Some("ftp.example.com"));
+    assert!(crawl.all);
+
+    let snapshot: SourceArgs = source(&[
+        "tree-ftp",
+        "find",
+        "--snapshot",
+        "tree.json",
+        "--name",
+        "*.csv",
+    ])
+    .unwrap();
+
+    assert_eq!(snapshot.snapshot, Some(PathBuf::from("tree.json")));
+    assert!(source(&["tree-ftp", "find", "--name", "*.csv"]).is_none());
+}
+
+#[test]
+fn test_ncdu_is_an_output_format() {
+    let args: Args =
+        Args::try_parse_from(["tree-ftp", "ftp.example.com", "--ncdu", "dump.json"]).unwrap();
+
+    assert_eq!(args.output_format(), OutputFormat::Ncdu);
+    assert_eq!(args.output_path(), Some(PathBuf::from("dump.json")));
+}
+
+#[test]
+fn test_diff_rejects_output_formats() {
+    for format in [
+        &["--format", "jsonl"][..],
+        &["--format", "csv"],
+        &["--ncdu", "dump.json"],
+        &["--output", "report.txt"],
+        &["--flat"],
+    ] {
+        let arguments: Vec<&str> = [
+            &["tree-ftp", "ftp.example.com", "--diff", "old.json"][..],
+            format,
+        ]
+        .concat();
+
+        assert!(Args::try_parse_from(arguments).is_err());
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_sqlite_rejects_outputs() {
+    for output in [&["--output", "tree.txt"][..], &["--flat"]] {
+        let arguments: Vec<&str> = [
+            &["tree-ftp", "ftp.example.com", "--sqlite", "index.sqlite"][..],
+            output,
+        ]
+        .concat();
+
+        assert!(Args::try_parse_from(arguments).is_err());
+    }
+}
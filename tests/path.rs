@@ -0,0 +1,22 @@
+use tree_ftp::utils::path::{join, resolve};
+
+#[test]
+fn test_join_absolute_parent() {
+    assert_eq!(join("/pub/data", "file.txt"), "/pub/data/file.txt");
+}
+
+#[test]
+fn test_join_root_parent() {
+    assert_eq!(join("/", "pub"), "/pub");
+}
+
+#[test]
+fn test_join_keeps_spaces_in_name() {
+    assert_eq!(join("/pub", "my dir"), "/pub/my dir");
+}
+
+#[test]
+fn test_resolve_relative_and_parent_components() {
+    assert_eq!(resolve("/pub", "data/../my dir/./sub"), "/pub/my dir/sub");
+    assert_eq!(resolve("/pub", "/../.."), "/");
+}
@@ -0,0 +1,107 @@
+use std::{fs, path::PathBuf, time::Duration};
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::node::{NodeEnum, TraversalType};
+use tree_ftp::ftp::client::FtpClient;
+use tree_ftp::ftp::error::Error;
+use tree_ftp::ftp::options::FtpOptions;
+use tree_ftp::ftp::record::{Channel, Direction, Event, RecordingTransport};
+use tree_ftp::ftp::replay::ReplayTransport;
+use tree_ftp::ftp::transport::{TcpTransport, Transport};
+use tree_ftp::server::fault::{Fault, FaultRule};
+use tree_ftp::server::ftp_server::FtpServer;
+
+fn tree() -> Directory {
+    let mut public: Directory = Directory::new("pub".to_string());
+    let mut root: Directory = Directory::new("/".to_string());
+
+    public.add(File::new("readme.txt".to_string()));
+    root.add(public);
+    root.add(File::new("top.tmp".to_string()));
+
+    root
+}
+
+fn options() -> FtpOptions {
+    let mut options: FtpOptions = FtpOptions::default();
+
+    options.reconnect.initial_delay = Duration::ZERO;
+
+    options
+}
+
+fn crawl<T: Transport>(transport: T, depth: usize) -> Result<String, Error> {
+    let mut client: FtpClient<T> =
+        FtpClient::with_transport(transport, &"user".into(), &"secret".into(), options())?;
+    let root: NodeEnum = client.list_dir(depth, false)?;
+
+    Ok(root.to_string("", TraversalType::DFS))
+}
+
+fn record(server: &FtpServer, name: &str) -> (PathBuf, String) {
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("tree-ftp-{}-{}.log", name, std::process::id()));
+    let transport: RecordingTransport<TcpTransport> =
+        RecordingTransport::create(TcpTransport::new(server.addr(), &options()), &path).unwrap();
+    let tree: String = crawl(transport, 2).unwrap();
+
+    (path, tree)
+}
+
+#[test]
+fn test_recording_redacts_the_password() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let (path, _) = record(&server, "redact");
+    let log: String = fs::read_to_string(&path).unwrap();
+    let events: Vec<Event> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    fs::remove_file(&path).unwrap();
+
+    assert!(!log.contains("secret"));
+    assert!(log.contains("PASS ****"));
+    assert!(events
+        .iter()
+        .any(|e| e.channel == Channel::Data && e.direction == Direction::Received));
+}
+
+#[test]
+fn test_replay_reproduces_the_crawl() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+
+    server.inject(FaultRule::new("CWD", 2, Fault::ServiceUnavailable));
+
+    let (path, recorded) = record(&server, "replay");
+
+    drop(server);
+
+    let replayed: String = crawl(ReplayTransport::open(&path).unwrap(), 2).unwrap();
+
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(replayed, recorded);
+}
+
+#[test]
+fn test_replay_detects_divergence() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let (path, _) = record(&server, "diverge");
+    let options: FtpOptions = FtpOptions {
+        extended: true,
+        ..options()
+    };
+    let mut client: FtpClient<ReplayTransport> = FtpClient::with_transport(
+        ReplayTransport::open(&path).unwrap(),
+        &"user".into(),
+        &"secret".into(),
+        options,
+    )
+    .unwrap();
+    let result = client.list_dir(2, false);
+
+    fs::remove_file(&path).unwrap();
+
+    assert!(matches!(result, Err(Error::ListError { .. })));
+}
@@ -0,0 +1,123 @@
+use std::io::Cursor;
+
+use tree_ftp::ftp::error::Error;
+use tree_ftp::ftp::reply::{Reply, ReplyClass};
+
+#[test]
+fn test_reply_class_from_code() {
+    assert_eq!(ReplyClass::from_code(150), Some(ReplyClass::Preliminary));
+    assert_eq!(ReplyClass::from_code(226), Some(ReplyClass::Completion));
+    assert_eq!(ReplyClass::from_code(331), Some(ReplyClass::Intermediate));
+    assert_eq!(
+        ReplyClass::from_code(421),
+        Some(ReplyClass::TransientFailure)
+    );
+    assert_eq!(
+        ReplyClass::from_code(550),
+        Some(ReplyClass::PermanentFailure)
+    );
+    assert_eq!(ReplyClass::from_code(0), None);
+}
+
+#[test]
+fn test_reply_is_failure() {
+    let ok: Reply = Reply::new(250, vec!["Okay.".to_string()]).unwrap();
+    let failure: Reply = Reply::new(550, vec!["No such directory.".to_string()]).unwrap();
+
+    assert!(!ok.is_failure());
+    assert!(failure.is_failure());
+}
+
+#[test]
+fn test_reply_message_joins_lines() {
+    let reply: Reply = Reply::new(211, vec!["Features:".to_string(), " MLSD".to_string()]).unwrap();
+
+    assert_eq!(reply.message(), "Features:\n MLSD");
+}
+
+#[test]
+fn test_read_single_line_reply() {
+    let mut reader: Cursor<&[u8]> = Cursor::new(b"230 Login successful.\r\n");
+    let reply: Reply = Reply::read_from(&mut reader).unwrap();
+
+    assert_eq!(reply.code, 230);
+    assert_eq!(reply.lines, vec!["Login successful."]);
+}
+
+#[test]
+fn test_read_banner_with_lines_that_look_like_codes() {
+    let mut reader: Cursor<&[u8]> = Cursor::new(
+        b"220-Welcome to the archive\r\n\
+          230 users are allowed at once\r\n\
+          550 bytes per second max\r\n\
+          220-Please be nice\r\n\
+          220 Ready\r\n",
+    );
+    let reply: Reply = Reply::read_from(&mut reader).unwrap();
+
+    assert_eq!(reply.code, 220);
+    assert_eq!(
+        reply.lines,
+        vec![
+            "Welcome to the archive",
+            "230 users are allowed at once",
+            "550 bytes per second max",
+            "Please be nice",
+            "Ready",
+        ]
+    );
+}
+
+#[test]
+fn test_read_feat_reply_with_indented_lines() {
+    let mut reader: Cursor<&[u8]> =
+        Cursor::new(b"211-Features:\r\n MLSD\r\n 211 lookalike\r\n SIZE\r\n211 End\r\n");
+    let reply: Reply = Reply::read_from(&mut reader).unwrap();
+
+    assert_eq!(reply.code, 211);
+    assert_eq!(
+        reply.lines,
+        vec!["Features:", " MLSD", " 211 lookalike", " SIZE", "End"]
+    );
+}
+
+#[test]
+fn test_read_multi_line_reply_ending_with_bare_code() {
+    let mut reader: Cursor<&[u8]> = Cursor::new(b"220-Hello\n220\n");
+    let reply: Reply = Reply::read_from(&mut reader).unwrap();
+
+    assert_eq!(reply.code, 220);
+    assert_eq!(reply.lines, vec!["Hello"]);
+}
+
+#[test]
+fn test_read_consecutive_replies_from_one_buffer() {
+    let mut reader: Cursor<&[u8]> =
+        Cursor::new(b"150-Opening\r\n150 data connection\r\n226 Transfer complete\r\n");
+    let first: Reply = Reply::read_from(&mut reader).unwrap();
+    let second: Reply = Reply::read_from(&mut reader).unwrap();
+
+    assert_eq!(first.class, ReplyClass::Preliminary);
+    assert_eq!(second.code, 226);
+    assert_eq!(second.class, ReplyClass::Completion);
+}
+
+#[test]
+fn test_read_truncated_multi_line_reply() {
+    let mut reader: Cursor<&[u8]> = Cursor::new(b"220-Welcome\r\n220-still going\r\n");
+
+    assert!(matches!(
+        Reply::read_from(&mut reader),
+        Err(Error::ConnectionLost { .. })
+    ));
+}
+
+#[test]
+fn test_read_reply_without_code() {
+    let mut reader: Cursor<&[u8]> = Cursor::new(b"hello\r\n");
+
+    assert!(matches!(
+        Reply::read_from(&mut reader),
+        Err(Error::InvalidParsedData { .. })
+    ));
+}
@@ -0,0 +1,42 @@
+use serde_json::Value;
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::report::to_html;
+
+#[test]
+fn test_html_report() {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/<srv>".to_string());
+    let mut data: Directory = Directory::with_path("data".to_string(), "/<srv>/data".to_string());
+    let mut file: File = File::with_path(
+        "</script>.bin".to_string(),
+        "/<srv>/data/</script>.bin".to_string(),
+    );
+
+    file.metadata.size = Some(3 << 20);
+    file.metadata.modified = Some("20240102030405".to_string());
+    data.add(file);
+    root.add(data);
+    root.add(File::with_path(
+        "unknown".to_string(),
+        "/<srv>/unknown".to_string(),
+    ));
+
+    let html: String = to_html(&root);
+    let (_, rest): (&str, &str) = html
+        .split_once(r#"<script type="application/json" id="tree-data">"#)
+        .unwrap();
+    let (json, _): (&str, &str) = rest.split_once("</script>").unwrap();
+    let tree: Value = serde_json::from_str(json).unwrap();
+
+    assert!(html.contains("<title>/&lt;srv&gt;</title>"));
+    assert!(html.contains(r#"<p class="summary">1 directories, 2 files, 3.0 MiB</p>"#));
+    assert!(html.contains(r#"<input id="search" type="search""#));
+    assert!(!json.contains('<'));
+    assert_eq!(tree["s"], 3 << 20);
+    assert_eq!(tree["c"][0]["f"], 1);
+    assert_eq!(tree["c"][0]["c"][0]["n"], "</script>.bin");
+    assert_eq!(tree["c"][0]["c"][0]["m"], 1_704_164_645);
+    assert_eq!(tree["c"][0]["c"][0]["t"], "20240102030405");
+    assert_eq!(tree["c"][1]["s"], Value::Null);
+    assert_eq!(tree["c"][1]["m"], Value::Null);
+}
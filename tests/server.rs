@@ -0,0 +1,340 @@
+use std::time::Duration;
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::node::{Node, NodeEnum, TraversalType};
+use tree_ftp::fs::tree::TreeOptions;
+use tree_ftp::ftp::client::FtpClient;
+use tree_ftp::ftp::error::Error;
+use tree_ftp::ftp::options::FtpOptions;
+use tree_ftp::server::fault::{Fault, FaultRule};
+use tree_ftp::server::ftp_server::{FtpServer, ServerOptions};
+
+fn tree() -> Directory {
+    let mut data: Directory = Directory::new("data".to_string());
+    let mut public: Directory = Directory::new("pub".to_string());
+    let mut root: Directory = Directory::new("/".to_string());
+
+    data.add(File::new("blob.bin".to_string()));
+    public.add(data);
+    public.add(File::new("readme.txt".to_string()));
+    root.add(public);
+    root.add(Directory::new("my dir".to_string()));
+    root.add(File::new(".hidden".to_string()));
+    root.add(File::new("top.tmp".to_string()));
+
+    root
+}
+
+fn options() -> FtpOptions {
+    let mut options: FtpOptions = FtpOptions::default();
+
+    options.reconnect.initial_delay = Duration::ZERO;
+
+    options
+}
+
+fn client(server: &FtpServer, options: FtpOptions) -> FtpClient {
+    FtpClient::with_options(server.addr(), &"user".into(), &"pass".into(), options).unwrap()
+}
+
+fn crawl(server: &FtpServer, options: FtpOptions, bfs: bool) -> String {
+    let root: NodeEnum = client(server, options).list_dir(3, bfs).unwrap();
+    let traversal: TraversalType = if bfs {
+        TraversalType::BFS
+    } else {
+        TraversalType::DFS
+    };
+
+    root.to_string("", traversal)
+}
+
+#[test]
+fn test_list_dir_dfs_and_bfs() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let dfs: String = crawl(&server, options(), false);
+    let bfs: String = crawl(&server, options(), true);
+
+    for name in ["pub", "data", "blob.bin", "readme.txt", "my dir", "top.tmp"] {
+        assert!(dfs.contains(name), "{} missing from\n{}", name, dfs);
+        assert!(bfs.contains(name), "{} missing from\n{}", name, bfs);
+    }
+
+    assert!(!dfs.contains(".hidden"));
+}
+
+#[test]
+fn test_tree_of_bfs_crawl_is_drawn_by_directory() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let render = |bfs: bool| {
+        let NodeEnum::Directory(root) = client(&server, options()).list_dir(3, bfs).unwrap() else {
+            panic!("the root is a directory");
+        };
+
+        TreeOptions::default().render(&root)
+    };
+
+    assert_eq!(
+        render(true),
+        "/\n├── pub\n│   ├── data\n│   │   └── blob.bin\n│   └── readme.txt\n├── my dir\n└── top.tmp\n\n3 directories, 3 files\n"
+    );
+    assert_eq!(render(true), render(false));
+}
+
+#[test]
+fn test_list_dir_records_absolute_paths() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let root: NodeEnum = client(&server, options()).list_dir(3, false).unwrap();
+
+    let NodeEnum::Directory(root) = root else {
+        panic!("the root is a directory");
+    };
+    let NodeEnum::Directory(public) = &root.nodes[0] else {
+        panic!("pub is a directory");
+    };
+
+    assert_eq!(public.nodes[0].path(), "/pub/data");
+}
+
+#[test]
+fn test_list_dir_hidden_entries() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let options: FtpOptions = FtpOptions {
+        hidden: true,
+        ..options()
+    };
+    let tree: String = crawl(&server, options, false);
+
+    assert!(tree.contains(".hidden"));
+    assert!(tree.contains("blob.bin"));
+}
+
+#[test]
+fn test_list_dir_extended_passive_mode() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let options: FtpOptions = FtpOptions {
+        extended: true,
+        ..options()
+    };
+
+    assert!(crawl(&server, options, false).contains("blob.bin"));
+}
+
+#[test]
+fn test_bad_login() {
+    let server: FtpServer = FtpServer::with_options(
+        tree(),
+        ServerOptions {
+            credentials: Some(("admin".to_string(), "secret".to_string())),
+            ..ServerOptions::default()
+        },
+    )
+    .unwrap();
+    let result = client(&server, options()).list_dir(1, false);
+
+    assert!(matches!(result, Err(Error::BadLogin { .. })));
+    assert_eq!(result.unwrap_err().exit_code(), 77);
+}
+
+#[test]
+fn test_reconnects_after_service_unavailable() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+
+    server.inject(FaultRule::new("CWD", 3, Fault::ServiceUnavailable));
+
+    assert!(crawl(&server, options(), false).contains("blob.bin"));
+    assert_eq!(server.connections(), 2);
+}
+
+#[test]
+fn test_reconnects_after_dropped_connection() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+
+    server.inject(FaultRule::new("LIST", 2, Fault::Drop));
+
+    assert!(crawl(&server, options(), true).contains("blob.bin"));
+    assert_eq!(server.connections(), 2);
+}
+
+#[test]
+fn test_reconnects_after_slow_reply() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let options: FtpOptions = FtpOptions {
+        read_timeout: Some(Duration::from_millis(200)),
+        ..options()
+    };
+
+    server.inject(FaultRule::new(
+        "PASV",
+        2,
+        Fault::Delay(Duration::from_secs(1)),
+    ));
+
+    assert!(crawl(&server, options, false).contains("blob.bin"));
+    assert_eq!(server.connections(), 2);
+}
+
+#[test]
+fn test_gives_up_when_the_server_keeps_failing() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+
+    server.inject(FaultRule::new("LIST", 0, Fault::ServiceUnavailable));
+
+    let result = client(&server, options()).list_dir(1, false);
+
+    assert!(matches!(result, Err(Error::ListError { .. })));
+}
+
+#[test]
+fn test_serves_recorded_metadata() {
+    let mut root: Directory = tree();
+    let mut file: File = File::new("sized.bin".to_string());
+
+    file.metadata.size = Some(4096);
+    file.metadata.owner = Some("alice".to_string());
+    root.add(file);
+
+    let server: FtpServer = FtpServer::start(root).unwrap();
+    let NodeEnum::Directory(crawled) = client(&server, options()).list_dir(1, false).unwrap()
+    else {
+        panic!("the root is a directory");
+    };
+    let Some(NodeEnum::File(sized)) = crawled.nodes.iter().find(|n| n.name() == "sized.bin") else {
+        panic!("sized.bin is a file");
+    };
+
+    assert_eq!(sized.metadata.size, Some(4096));
+    assert_eq!(sized.metadata.owner.as_deref(), Some("alice"));
+}
+
+#[test]
+fn test_serves_recorded_metadata_over_mlsd() {
+    let mut root: Directory = tree();
+    let mut file: File = File::new("dated.bin".to_string());
+
+    file.metadata.size = Some(4096);
+    file.metadata.modified = Some("20240102030405".to_string());
+    file.metadata.permissions = Some("-rwxr-x---".to_string());
+    root.add(file);
+
+    let server: FtpServer = FtpServer::start(root).unwrap();
+    let options: FtpOptions = FtpOptions {
+        hidden: true,
+        ..options()
+    };
+    let NodeEnum::Directory(crawled) = client(&server, options).list_dir(1, false).unwrap() else {
+        panic!("the root is a directory");
+    };
+    let Some(NodeEnum::File(dated)) = crawled.nodes.iter().find(|n| n.name() == "dated.bin") else {
+        panic!("dated.bin is a file");
+    };
+
+    assert_eq!(dated.metadata.size, Some(4096));
+    assert_eq!(dated.metadata.modified.as_deref(), Some("20240102030405"));
+    assert_eq!(dated.metadata.permissions.as_deref(), Some("-rwxr-x---"));
+}
+
+#[test]
+fn test_crawls_back_machine_times_over_list() {
+    let mut root: Directory = tree();
+    let mut spaced: Directory = Directory::new("sub dir".to_string());
+    let mut dated: File = File::new("dated.bin".to_string());
+    let mut inner: File = File::new("inner.txt".to_string());
+
+    dated.metadata.modified = Some("20240102030405".to_string());
+    inner.metadata.modified = Some("20240607080910".to_string());
+    spaced.add(inner);
+    root.add(spaced);
+    root.add(dated);
+
+    let server: FtpServer = FtpServer::start(root).unwrap();
+    let NodeEnum::Directory(crawled) = client(&server, options()).list_dir(2, false).unwrap()
+    else {
+        panic!("the root is a directory");
+    };
+    let Some(NodeEnum::File(dated)) = crawled.nodes.iter().find(|n| n.name() == "dated.bin") else {
+        panic!("dated.bin is a file");
+    };
+    let Some(NodeEnum::Directory(spaced)) = crawled.nodes.iter().find(|n| n.name() == "sub dir")
+    else {
+        panic!("sub dir is a directory");
+    };
+
+    assert_eq!(dated.metadata.modified.as_deref(), Some("Jan 02 2024"));
+    assert_eq!(spaced.nodes.len(), 1);
+    assert_eq!(spaced.nodes[0].path(), "/sub dir/inner.txt");
+    assert!(crawled
+        .nodes
+        .iter()
+        .find(|n| n.name() == "top.tmp")
+        .is_some_and(|n| matches!(n, NodeEnum::File(file) if file.metadata.modified.is_some())));
+}
+
+#[test]
+fn test_list_dir_with_calls_back_as_entries_are_listed() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let mut dfs: Vec<(String, usize)> = Vec::new();
+    let mut bfs: Vec<(String, usize)> = Vec::new();
+
+    client(&server, options())
+        .list_dir_with(3, false, |node, depth| {
+            dfs.push((node.path().to_string(), depth));
+
+            Ok::<(), Error>(())
+        })
+        .unwrap();
+    client(&server, options())
+        .list_dir_with(2, true, |node, depth| {
+            bfs.push((node.path().to_string(), depth));
+
+            Ok::<(), Error>(())
+        })
+        .unwrap();
+
+    let entries = |entries: &[(&str, usize)]| -> Vec<(String, usize)> {
+        entries
+            .iter()
+            .map(|(path, depth)| (path.to_string(), *depth))
+            .collect()
+    };
+
+    assert_eq!(
+        dfs,
+        entries(&[
+            ("/pub", 1),
+            ("/pub/data", 2),
+            ("/pub/data/blob.bin", 3),
+            ("/pub/readme.txt", 2),
+            ("/my dir", 1),
+            ("/top.tmp", 1),
+        ])
+    );
+    assert_eq!(
+        bfs,
+        entries(&[
+            ("/pub", 1),
+            ("/my dir", 1),
+            ("/top.tmp", 1),
+            ("/pub/data", 2),
+            ("/pub/readme.txt", 2),
+        ])
+    );
+}
+
+#[test]
+fn test_list_dir_with_stops_on_callback_error() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let mut seen: usize = 0;
+    let result = client(&server, options()).list_dir_with(3, false, |_, _| {
+        seen += 1;
+
+        Err(tree_ftp::error::Error::InvalidSnapshot {
+            reason: "stop".to_string(),
+        })
+    });
+
+    assert!(matches!(
+        result,
+        Err(tree_ftp::error::Error::InvalidSnapshot { .. })
+    ));
+    assert_eq!(seen, 1);
+}
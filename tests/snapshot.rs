@@ -0,0 +1,63 @@
+use tree_ftp::error::Error;
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::node::{Node, NodeEnum};
+use tree_ftp::fs::snapshot::{from_json, to_json, Snapshot};
+
+#[test]
+fn test_snapshot_round_trip() {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/".to_string());
+    let mut file: File = File::with_path("a.txt".to_string(), "/a.txt".to_string());
+
+    file.metadata.size = Some(3);
+    file.metadata.permissions = Some("-rw-r--r--".to_string());
+    root.add(Directory::with_path(
+        "a.txt".to_string(),
+        "/a.txt".to_string(),
+    ));
+    root.add(file);
+
+    let json: String = Snapshot::new(NodeEnum::Directory(root)).to_json();
+    let loaded: Directory = from_json(&json).unwrap();
+
+    assert!(json.starts_with(r#"{"version":1,"root":{"type":"directory""#));
+    assert_eq!(loaded.nodes.len(), 2);
+    assert_eq!(to_json(&loaded), json);
+    assert_eq!(Snapshot::new(NodeEnum::Directory(loaded)).to_json(), json);
+}
+
+#[test]
+fn test_from_json_rejects_unknown_versions() {
+    let result = from_json(r#"{"version":99,"root":{"type":"directory","name":".","path":"/"}}"#);
+
+    assert!(matches!(result, Err(Error::InvalidSnapshot { .. })));
+}
+
+#[test]
+fn test_from_json_legacy_snapshot() {
+    let json: &str = r#"{"Directory":{"pub":{"readme.txt":{"name":"readme.txt","path":"/pub/readme.txt","metadata":{"size":6}}},"top.tmp":{"name":"top.tmp"}}}"#;
+    let root: Directory = from_json(json).unwrap();
+
+    assert_eq!(root.nodes.len(), 2);
+
+    match &root.nodes[0] {
+        NodeEnum::Directory(public) => match &public.nodes[0] {
+            NodeEnum::File(file) => {
+                assert_eq!(file.path(), "/pub/readme.txt");
+                assert_eq!(file.metadata.size, Some(6));
+            }
+            NodeEnum::Directory(_) => panic!("readme.txt is a file"),
+        },
+        NodeEnum::File(_) => panic!("pub is a directory"),
+    }
+
+    assert!(matches!(root.nodes[1], NodeEnum::File(_)));
+}
+
+#[test]
+fn test_from_json_rejects_a_file_root() {
+    let result = from_json(r#"{"File":{"name":"a.txt","path":"a.txt"}}"#);
+
+    assert!(matches!(result, Err(Error::InvalidSnapshot { .. })));
+    assert_eq!(result.unwrap_err().exit_code(), 65);
+}
@@ -0,0 +1,203 @@
+#![cfg(feature = "sqlite")]
+
+use std::time::{Duration, SystemTime};
+
+use tree_ftp::error::Error;
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::sqlite::{SqliteIndex, SCHEMA_VERSION};
+
+fn tree(files: &[(&str, u64)]) -> Directory {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/".to_string());
+    let mut public: Directory = Directory::with_path("pub".to_string(), "/pub".to_string());
+
+    for (name, size) in files {
+        let mut file: File = File::with_path(name.to_string(), format!("/pub/{}", name));
+
+        file.metadata.size = Some(*size);
+        public.add(file);
+    }
+
+    root.add(public);
+    root
+}
+
+#[test]
+fn test_sqlite_crawls_are_appended() {
+    let mut index: SqliteIndex = SqliteIndex::open_in_memory().unwrap();
+    let started_at: SystemTime = SystemTime::UNIX_EPOCH + Duration::from_secs(86_400);
+
+    let first: i64 = index
+        .insert_crawl(
+            "ftp.example.com:21",
+            started_at,
+            &tree(&[("a.txt", 1), ("b.txt", 2)]),
+        )
+        .unwrap();
+    let second: i64 = index
+        .insert_crawl(
+            "ftp.example.com:21",
+            started_at,
+            &tree(&[("b.txt", 3), ("c.txt", 4)]),
+        )
+        .unwrap();
+
+    assert_ne!(first, second);
+
+    let (host, root, date): (String, String, String) = index
+        .connection()
+        .query_row(
+            "SELECT host, root, started_at FROM crawls WHERE id = ?1",
+            [first],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+
+    assert_eq!(host, "ftp.example.com:21");
+    assert_eq!(root, "/");
+    assert_eq!(date, "1970-01-02T00:00:00Z");
+
+    let (parent, depth, size): (String, i64, i64) = index
+        .connection()
+        .query_row(
+            "SELECT parent, depth, size FROM entries WHERE crawl_id = ?1 AND path = '/pub/a.txt'",
+            [first],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+
+    assert_eq!((parent.as_str(), depth, size), ("/pub", 2, 1));
+
+    let mut statement = index
+        .connection()
+        .prepare(
+            "WITH old AS (SELECT * FROM entries WHERE crawl_id = ?1),
+                  new AS (SELECT * FROM entries WHERE crawl_id = ?2)
+             SELECT coalesce(new.path, old.path),
+                    CASE WHEN old.path IS NULL THEN 'added'
+                         WHEN new.path IS NULL THEN 'removed'
+                         ELSE 'modified' END
+             FROM old FULL JOIN new ON new.path = old.path
+             WHERE old.path IS NULL OR new.path IS NULL OR old.size IS NOT new.size
+             ORDER BY 1",
+        )
+        .unwrap();
+    let changes: Vec<(String, String)> = statement
+        .query_map([first, second], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(
+        changes,
+        vec![
+            ("/pub/a.txt".to_string(), "removed".to_string()),
+            ("/pub/b.txt".to_string(), "modified".to_string()),
+            ("/pub/c.txt".to_string(), "added".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_sqlite_normalizes_times_and_cascades() {
+    let mut index: SqliteIndex = SqliteIndex::open_in_memory().unwrap();
+    let started_at: SystemTime = humantime::parse_rfc3339("2025-03-01T00:00:00Z").unwrap();
+    let mut root: Directory = tree(&[]);
+    let mut listed: File = File::with_path("listed.txt".to_string(), "/listed.txt".to_string());
+    let mut odd: File = File::with_path("odd.txt".to_string(), "/odd.txt".to_string());
+
+    listed.metadata.modified = Some("Dec 31 23:59".to_string());
+    odd.metadata.modified = Some("yesterday".to_string());
+    root.add(listed);
+    root.add(odd);
+
+    let crawl: i64 = index
+        .insert_crawl("ftp.example.com:21", started_at, &root)
+        .unwrap();
+    let mut statement = index
+        .connection()
+        .prepare("SELECT path, modified, modified_at FROM entries WHERE crawl_id = ?1 AND type = 'file' ORDER BY path")
+        .unwrap();
+    let times: Vec<(String, String, Option<String>)> = statement
+        .query_map([crawl], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(
+        times,
+        vec![
+            (
+                "/listed.txt".to_string(),
+                "Dec 31 23:59".to_string(),
+                Some("2024-12-31T23:59:00Z".to_string())
+            ),
+            ("/odd.txt".to_string(), "yesterday".to_string(), None),
+        ]
+    );
+
+    drop(statement);
+    index
+        .connection()
+        .execute("DELETE FROM crawls WHERE id = ?1", [crawl])
+        .unwrap();
+
+    let remaining: i64 = index
+        .connection()
+        .query_row("SELECT count(*) FROM entries", [], |row| row.get(0))
+        .unwrap();
+
+    assert_eq!(remaining, 0);
+}
+
+#[test]
+fn test_sqlite_rejects_newer_schema() {
+    let path: std::path::PathBuf =
+        std::env::temp_dir().join(format!("tree-ftp-newer-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    SqliteIndex::open(&path)
+        .unwrap()
+        .connection()
+        .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+        .unwrap();
+
+    let result: Result<SqliteIndex, Error> = SqliteIndex::open(&path);
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        result,
+        Err(Error::IncompatibleDatabase { version, .. }) if version == SCHEMA_VERSION + 1
+    ));
+}
+
+#[test]
+fn test_sqlite_upgrades_first_schema() {
+    let path: std::path::PathBuf =
+        std::env::temp_dir().join(format!("tree-ftp-upgrade-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    SqliteIndex::open(&path)
+        .unwrap()
+        .connection()
+        .execute_batch("ALTER TABLE entries DROP COLUMN modified_at; PRAGMA user_version = 1;")
+        .unwrap();
+
+    let index: SqliteIndex = SqliteIndex::open(&path).unwrap();
+    let version: i64 = index
+        .connection()
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .unwrap();
+    let columns: i64 = index
+        .connection()
+        .query_row(
+            "SELECT count(*) FROM pragma_table_info('entries') WHERE name = 'modified_at'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+
+    drop(index);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!((version, columns), (SCHEMA_VERSION, 1));
+}
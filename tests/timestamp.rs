@@ -0,0 +1,59 @@
+use std::time::SystemTime;
+use tree_ftp::fs::timestamp::{list_time, machine_time, modified_time, same_modified_time};
+
+#[test]
+fn test_modified_time() {
+    let now: SystemTime = humantime::parse_rfc3339("2025-03-01T00:00:00Z").unwrap();
+    let time = |value: &str| modified_time(value, now).map(humantime::format_rfc3339);
+
+    assert_eq!(
+        time("20250102030405").unwrap().to_string(),
+        "2025-01-02T03:04:05Z"
+    );
+    assert_eq!(
+        time("Jun 10 2023").unwrap().to_string(),
+        "2023-06-10T00:00:00Z"
+    );
+    assert_eq!(
+        time("Feb 01 12:30").unwrap().to_string(),
+        "2025-02-01T12:30:00Z"
+    );
+    assert_eq!(
+        time("Dec 24 08:00").unwrap().to_string(),
+        "2024-12-24T08:00:00Z"
+    );
+    assert!(time("yesterday").is_none());
+}
+
+#[test]
+fn test_same_modified_time() {
+    let now: SystemTime = humantime::parse_rfc3339("2025-03-01T00:00:00Z").unwrap();
+
+    assert!(same_modified_time("Dec 24 08:00", "Dec 24 2024", now));
+    assert!(same_modified_time("Dec 24 08:00", "20241224080059", now));
+    assert!(same_modified_time("yesterday", "yesterday", now));
+    assert!(!same_modified_time("Dec 24 08:00", "Dec 24 08:01", now));
+    assert!(!same_modified_time("Dec 24 08:00", "Dec 25 2024", now));
+    assert!(!same_modified_time("yesterday", "today", now));
+}
+
+#[test]
+fn test_machine_time() {
+    let time: SystemTime = humantime::parse_rfc3339("2025-01-02T03:04:05Z").unwrap();
+
+    assert_eq!(machine_time(time), "20250102030405");
+}
+
+#[test]
+fn test_list_time() {
+    let now: SystemTime = humantime::parse_rfc3339("2025-03-01T00:00:00Z").unwrap();
+    let time = |value: &str| list_time(humantime::parse_rfc3339(value).unwrap(), now);
+
+    assert_eq!(time("2024-12-24T08:00:59Z"), "Dec 24 08:00");
+    assert_eq!(time("2024-06-10T12:00:00Z"), "Jun 10  2024");
+    assert_eq!(time("2025-06-10T12:00:00Z"), "Jun 10  2025");
+    assert_eq!(
+        modified_time(&time("2024-12-24T08:00:59Z"), now),
+        humantime::parse_rfc3339("2024-12-24T08:00:00Z").ok()
+    );
+}
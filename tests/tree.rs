@@ -0,0 +1,104 @@
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::tree::{Charset, SortKey, TreeOptions};
+
+fn tree() -> Directory {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/pub".to_string());
+    let mut data: Directory = Directory::with_path("data".to_string(), "/pub/data".to_string());
+    let mut small: File = File::with_path("small.txt".to_string(), "/pub/small.txt".to_string());
+    let mut run: File = File::with_path("run.sh".to_string(), "/pub/data/run.sh".to_string());
+
+    small.metadata.size = Some(10);
+    small.metadata.modified = Some("20240102030405".to_string());
+    small.metadata.permissions = Some("-rw-r--r--".to_string());
+    run.metadata.size = Some(1536);
+    run.metadata.permissions = Some("-rwxr-xr-x".to_string());
+    data.add(run);
+    root.add(small);
+    root.add(data);
+    root.add(File::with_path(
+        "unknown".to_string(),
+        "/pub/unknown".to_string(),
+    ));
+
+    root
+}
+
+#[test]
+fn test_tree_renders_like_tree() {
+    let expected: &str = "/pub\n\
+                          ├── small.txt\n\
+                          ├── data\n\
+                          │   └── run.sh\n\
+                          └── unknown\n\
+                          \n\
+                          1 directory, 3 files\n";
+
+    assert_eq!(TreeOptions::default().render(&tree()), expected);
+}
+
+#[test]
+fn test_tree_sorts_and_classifies_in_ascii() {
+    let options: TreeOptions = TreeOptions {
+        charset: Charset::Ascii,
+        dirs_first: true,
+        sort: Some(SortKey::Size),
+        classify: true,
+        no_report: true,
+        ..TreeOptions::default()
+    };
+
+    assert_eq!(
+        options.render(&tree()),
+        "/pub\n|-- data/\n|   `-- run.sh*\n|-- small.txt\n`-- unknown\n"
+    );
+}
+
+#[test]
+fn test_tree_prints_details() {
+    let options: TreeOptions = TreeOptions {
+        human: true,
+        dates: true,
+        permissions: true,
+        sort: Some(SortKey::Name),
+        no_report: true,
+        ..TreeOptions::default()
+    };
+    let lines: Vec<String> = options.render(&tree()).lines().map(String::from).collect();
+
+    assert_eq!(lines[1], "├── [?              ? ?               ]  data");
+    assert_eq!(
+        lines[2],
+        "│   └── [-rwxr-xr-x  1.5K ?               ]  run.sh"
+    );
+    assert_eq!(
+        lines[3],
+        "├── [-rw-r--r--    10 2024-01-02 03:04]  small.txt"
+    );
+}
+
+#[test]
+fn test_tree_classifies_permissions_with_suffixes() {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/pub".to_string());
+    let options: TreeOptions = TreeOptions {
+        classify: true,
+        no_report: true,
+        ..TreeOptions::default()
+    };
+
+    for (name, permissions) in [
+        ("acl", "-rwxr-x---+"),
+        ("xattr", "-rw-r--r-x@"),
+        ("plain", "-rw-r--r--+"),
+    ] {
+        let mut file: File = File::with_path(name.to_string(), format!("/pub/{}", name));
+
+        file.metadata.permissions = Some(permissions.to_string());
+        root.add(file);
+    }
+
+    assert_eq!(
+        options.render(&root),
+        "/pub\n├── acl*\n├── xattr*\n└── plain\n"
+    );
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::usage::{du, human_size, usage_by_path, DuEntry, Usage};
+
+fn file(path: &str, size: Option<u64>) -> File {
+    let name: &str = path.rsplit('/').next().unwrap();
+    let mut file: File = File::with_path(name.to_string(), path.to_string());
+
+    file.metadata.size = size;
+
+    file
+}
+
+fn tree() -> Directory {
+    let mut root: Directory = Directory::with_path(".".to_string(), "/".to_string());
+    let mut public: Directory = Directory::with_path("pub".to_string(), "/pub".to_string());
+    let mut data: Directory = Directory::with_path("data".to_string(), "/pub/data".to_string());
+    let mut logs: Directory = Directory::with_path("logs".to_string(), "/logs".to_string());
+
+    data.add(file("/pub/data/big.bin", Some(2048)));
+    public.add(data);
+    public.add(file("/pub/readme.txt", Some(100)));
+    logs.add(file("/logs/1.log", None));
+    root.add(public);
+    root.add(logs);
+    root.add(Directory::with_path(
+        "empty".to_string(),
+        "/empty".to_string(),
+    ));
+    root.add(file("/top.txt", Some(10)));
+
+    root
+}
+
+#[test]
+fn test_directory_usage() {
+    assert_eq!(
+        tree().usage(),
+        Usage {
+            bytes: 2158,
+            files: 4,
+            dirs: 4,
+            depth: 3,
+        }
+    );
+    assert_eq!(
+        Directory::new("empty".to_string()).usage(),
+        Usage::default()
+    );
+}
+
+#[test]
+fn test_du_rolls_up_deeper_directories() {
+    let (entries, total): (Vec<DuEntry>, Usage) = du(&tree(), 1);
+    let rows: Vec<(&str, u64)> = entries
+        .iter()
+        .map(|entry| (entry.path.as_str(), entry.usage.bytes))
+        .collect();
+
+    assert_eq!(rows, vec![("/pub", 2148), ("/empty", 0), ("/logs", 0)]);
+    assert_eq!(total.bytes, 2158);
+    assert_eq!(du(&tree(), 2).0.len(), 4);
+}
+
+#[test]
+fn test_usage_by_path() {
+    let root: Directory = tree();
+    let usages: HashMap<String, Usage> = usage_by_path(&root);
+
+    assert_eq!(usages.len(), 5);
+    assert_eq!(usages["/"], root.usage());
+    assert_eq!(usages["/pub/data"].bytes, 2048);
+    assert_eq!(usages["/pub"].files, 2);
+    assert_eq!(usages["/empty"], Usage::default());
+}
+
+#[test]
+fn test_human_size() {
+    assert_eq!(human_size(0), "0 B");
+    assert_eq!(human_size(1023), "1023 B");
+    assert_eq!(human_size(1536), "1.5 KiB");
+    assert_eq!(human_size(5 * 1024 * 1024 * 1024), "5.0 GiB");
+}
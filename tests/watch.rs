@@ -0,0 +1,118 @@
+use std::{fs, path::PathBuf, time::Duration};
+use tree_ftp::fs::diff::{Change, ChangeKind};
+use tree_ftp::fs::directory::Directory;
+use tree_ftp::fs::file::File;
+use tree_ftp::fs::node::NodeEnum;
+use tree_ftp::fs::snapshot::Snapshot;
+use tree_ftp::ftp::client::FtpClient;
+use tree_ftp::ftp::options::FtpOptions;
+use tree_ftp::ftp::transport::TcpTransport;
+use tree_ftp::ftp::watch::{WatchOptions, Watcher};
+use tree_ftp::server::ftp_server::FtpServer;
+
+fn tree() -> Directory {
+    let mut public: Directory = Directory::new("pub".to_string());
+    let mut root: Directory = Directory::new("/".to_string());
+
+    public.add(File::new("readme.txt".to_string()));
+    root.add(public);
+    root.add(File::new("top.tmp".to_string()));
+
+    root
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("tree-ftp-{}-{}", name, std::process::id()));
+
+    let _ = fs::remove_file(&path);
+
+    path
+}
+
+fn watcher(server: &FtpServer, options: WatchOptions) -> Watcher<TcpTransport> {
+    let client: FtpClient = FtpClient::with_options(
+        server.addr(),
+        &"user".into(),
+        &"pass".into(),
+        FtpOptions::default(),
+    )
+    .unwrap();
+
+    Watcher::new(client, options).unwrap()
+}
+
+#[test]
+fn test_watch_reuses_the_connection() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let state: PathBuf = temp_path("reuse.json");
+    let mut watcher: Watcher<TcpTransport> = watcher(
+        &server,
+        WatchOptions {
+            interval: Duration::from_millis(50),
+            keepalive: Duration::from_millis(10),
+            depth: 2,
+            state: state.clone(),
+            print: false,
+            max_cycles: Some(3),
+            ..WatchOptions::default()
+        },
+    );
+
+    watcher.run().unwrap();
+
+    assert_eq!(server.connections(), 1);
+    assert!(fs::read_to_string(&state)
+        .unwrap()
+        .contains(r#""name":"readme.txt""#));
+    assert!(watcher.run_once().unwrap().is_empty());
+}
+
+#[test]
+fn test_watch_reports_changes_since_the_saved_state() {
+    let server: FtpServer = FtpServer::start(tree()).unwrap();
+    let state: PathBuf = temp_path("changes.json");
+    let events: PathBuf = temp_path("changes.jsonl");
+    let mut previous: Directory = Directory::with_path(".".to_string(), "/".to_string());
+
+    previous.add(File::with_path(
+        "gone.txt".to_string(),
+        "/gone.txt".to_string(),
+    ));
+    previous.add(File::with_path(
+        "top.tmp".to_string(),
+        "/top.tmp".to_string(),
+    ));
+    fs::write(
+        &state,
+        Snapshot::new(NodeEnum::Directory(previous)).to_json(),
+    )
+    .unwrap();
+
+    let mut watcher: Watcher<TcpTransport> = watcher(
+        &server,
+        WatchOptions {
+            state: state.clone(),
+            events: Some(events.clone()),
+            print: false,
+            ..WatchOptions::default()
+        },
+    );
+    let changes: Vec<Change> = watcher.run_once().unwrap();
+    let summary: Vec<(ChangeKind, &str)> = changes
+        .iter()
+        .map(|change| (change.kind, change.path.as_str()))
+        .collect();
+    let log: String = fs::read_to_string(&events).unwrap();
+
+    assert_eq!(
+        summary,
+        vec![
+            (ChangeKind::Removed, "/gone.txt"),
+            (ChangeKind::Added, "/pub")
+        ]
+    );
+    assert_eq!(log.lines().count(), 2);
+    assert!(log.starts_with(r#"{"timestamp_ms":"#));
+    assert!(log.contains(r#""cycle":1,"kind":"removed","path":"/gone.txt""#));
+}